@@ -1,8 +1,12 @@
 use std::collections::HashSet;
 
+use serde::{Deserialize, Serialize};
+
+use crate::actions::registry::action_ref;
 use crate::engine::{
     action_overrides::ActionOverride,
     encounter::EncounterInstance,
+    errors::EngineError,
     side_effects::{ApplicableSideEffect, ConsumeResource, Resource},
     types::Coordinate,
 };
@@ -11,9 +15,19 @@ pub enum TargetingSchema {
     NoArgs,
     SinglePoint,
     SingleActor,
+    // either one actor or one location, e.g. attacks that can hit objects
+    SingleActorOrPoint,
     Custom,
 }
 
+// what computer controlled creatures may use an action for
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ActionRole {
+    Attack { ranged: bool },
+    Heal,
+    Other,
+}
+
 pub trait Action {
     fn name(&self) -> &str;
 
@@ -21,6 +35,10 @@ pub trait Action {
 
     fn targeting_schema(&self) -> TargetingSchema;
 
+    fn role(&self) -> ActionRole {
+        ActionRole::Other
+    }
+
     fn side_effects(
         &self,
         encounter: &mut EncounterInstance,
@@ -52,19 +70,19 @@ pub trait Action {
         // TODO this is gross
         let schema_validation = match self.targeting_schema() {
             TargetingSchema::NoArgs => {
-                if target_ids != None {
+                if target_ids.is_some() {
                     return false;
                 }
-                if target_locations != None {
+                if target_locations.is_some() {
                     return false;
                 }
-                if overrides != None {
+                if overrides.is_some() {
                     return false;
                 }
                 true
             }
             TargetingSchema::SinglePoint => {
-                if target_ids != None {
+                if target_ids.is_some() {
                     return false;
                 }
                 if let Some(tl) = target_locations {
@@ -77,18 +95,15 @@ pub trait Action {
                 true
             }
             TargetingSchema::SingleActor => {
-                return if let Some(target_ids) = target_ids {
-                    if target_ids.len() == 0 { false } else { true }
-                } else {
-                    false
-                };
+                target_locations.is_none() && target_ids.is_some_and(|t| t.len() == 1)
             }
-            TargetingSchema::Custom => {
-                panic!(
-                    "custom targeting validation not implemented for {:?}",
-                    self.name()
-                )
+            TargetingSchema::SingleActorOrPoint => {
+                let n_ids = target_ids.map_or(0, |t| t.len());
+                let n_locations = target_locations.map_or(0, |t| t.len());
+                n_ids + n_locations == 1
             }
+            // custom_validate_input checks the targets itself
+            TargetingSchema::Custom => true,
         };
         if !schema_validation {
             return false;
@@ -100,18 +115,20 @@ pub trait Action {
             target_locations,
             overrides,
         ) {
-            let actor = encounter.actors.get(&caster_id).expect("missing actor");
+            let Some(actor) = encounter.actors.get(&caster_id) else {
+                return false;
+            };
             if !actor.can_consume_resource(cost) {
                 return false;
             }
         }
-        return self.custom_validate_input(
+        self.custom_validate_input(
             encounter,
             caster_id,
             target_ids,
             target_locations,
             overrides,
-        );
+        )
     }
 
     fn custom_validate_input(
@@ -125,6 +142,18 @@ pub trait Action {
         true
     }
 
+    // tiles to highlight on the map before the action is committed
+    fn preview(
+        &self,
+        _encounter: &EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Coordinate> {
+        Vec::new()
+    }
+
     fn execute(
         &self,
         encounter: &mut EncounterInstance,
@@ -132,7 +161,7 @@ pub trait Action {
         target_ids: Option<&Vec<usize>>,
         target_locations: Option<&Vec<Coordinate>>,
         overrides: Option<&HashSet<ActionOverride>>,
-    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+    ) -> Result<Vec<Box<dyn ApplicableSideEffect>>, EngineError> {
         if !self.validate_input(
             encounter,
             caster_id,
@@ -140,10 +169,10 @@ pub trait Action {
             target_locations,
             overrides,
         ) {
-            panic!(
-                "Tried to execute action {:?} with illegal args",
-                self.name()
-            )
+            return Err(EngineError::IllegalAction {
+                action: self.name().to_string(),
+                caster_id,
+            });
         }
         let mut side_effects = self.side_effects(
             encounter,
@@ -164,11 +193,13 @@ pub trait Action {
                 resource: cost,
             }));
         }
-        side_effects
+        Ok(side_effects)
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ActionExecutionInfo {
+    #[serde(with = "action_ref")]
     action: &'static dyn Action,
     caster_id: usize,
     target_ids: Option<Vec<usize>>,
@@ -185,31 +216,75 @@ impl ActionExecutionInfo {
         overrides: Option<HashSet<ActionOverride>>,
     ) -> Self {
         Self {
-            action: action,
-            caster_id: caster_id,
-            target_ids: target_ids,
-            target_locations: target_locations,
-            overrides: overrides,
+            action,
+            caster_id,
+            target_ids,
+            target_locations,
+            overrides,
         }
     }
 
+    pub fn action(&self) -> &'static dyn Action {
+        self.action
+    }
+
+    pub fn action_name(&self) -> &str {
+        self.action.name()
+    }
+
+    pub fn caster_id(&self) -> usize {
+        self.caster_id
+    }
+
+    pub fn target_ids(&self) -> &[usize] {
+        self.target_ids.as_deref().unwrap_or_default()
+    }
+
+    pub fn target_locations(&self) -> &[Coordinate] {
+        self.target_locations.as_deref().unwrap_or_default()
+    }
+
     pub fn validate(&self, encounter: &EncounterInstance) -> bool {
-        return self.action.validate_input(
+        self.action.validate_input(
             encounter,
             self.caster_id,
             self.target_ids.as_ref(),
             self.target_locations.as_ref(),
             self.overrides.as_ref(),
-        );
+        )
     }
 
-    pub fn execute(&self, encounter: &mut EncounterInstance) -> Vec<Box<dyn ApplicableSideEffect>> {
-        return self.action.execute(
+    pub fn preview(&self, encounter: &EncounterInstance) -> Vec<Coordinate> {
+        self.action.preview(
             encounter,
             self.caster_id,
             self.target_ids.as_ref(),
             self.target_locations.as_ref(),
             self.overrides.as_ref(),
-        );
+        )
+    }
+
+    pub fn execute(
+        &self,
+        encounter: &mut EncounterInstance,
+    ) -> Result<Vec<Box<dyn ApplicableSideEffect>>, EngineError> {
+        self.action.execute(
+            encounter,
+            self.caster_id,
+            self.target_ids.as_ref(),
+            self.target_locations.as_ref(),
+            self.overrides.as_ref(),
+        )
+    }
+}
+
+// actions are told apart by name, as they are when typed in
+impl PartialEq for ActionExecutionInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.action.name() == other.action.name()
+            && self.caster_id == other.caster_id
+            && self.target_ids == other.target_ids
+            && self.target_locations == other.target_locations
+            && self.overrides == other.overrides
     }
 }
@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use crate::{
+    actions::action_template::{Action, TargetingSchema},
+    engine::{
+        action_overrides::ActionOverride,
+        encounter::EncounterInstance,
+        side_effects::{ApplicableSideEffect, ChangeAltitude, Resource, StandUp},
+        types::Coordinate,
+    },
+};
+
+// one step up or down, the height of a medium creature
+const ALTITUDE_STEP: f32 = 5.;
+
+// climbing without a climb speed costs double, like climbable terrain
+fn vertical_cost(encounter: &EncounterInstance, caster_id: usize, amount: f32) -> Option<f32> {
+    let actor = encounter.actors.get(&caster_id)?;
+    if actor.fly_speed() > 0. || actor.climb_speed() > 0. {
+        Some(amount)
+    } else {
+        Some(amount * 2.)
+    }
+}
+
+pub struct Ascend {}
+
+impl Action for Ascend {
+    fn name(&self) -> &str {
+        "ascend"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["up"]
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::NoArgs
+    }
+
+    fn cost(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        Some(Resource::Movement(vertical_cost(
+            encounter,
+            caster_id,
+            ALTITUDE_STEP,
+        )?))
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        let Some(actor) = encounter.actors.get(&caster_id) else {
+            return false;
+        };
+        if actor.is_prone() {
+            return false;
+        }
+        if actor.fly_speed() > 0. {
+            return true;
+        }
+        // climbers can go as high as what they are climbing
+        let target_elevation = encounter.actor_elevation(caster_id) + ALTITUDE_STEP;
+        encounter
+            .climb_limit(caster_id)
+            .is_some_and(|limit| limit >= target_elevation)
+    }
+
+    fn side_effects(
+        &self,
+        _encounter: &mut EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        vec![Box::new(ChangeAltitude {
+            actor_id: caster_id,
+            amount: ALTITUDE_STEP,
+        })]
+    }
+}
+
+pub static ASCEND: LazyLock<Ascend> = LazyLock::new(|| Ascend {});
+
+pub struct Descend {}
+
+// nothing to descend for an actor that is not there
+fn descend_amount(encounter: &EncounterInstance, caster_id: usize) -> f32 {
+    encounter
+        .actors
+        .get(&caster_id)
+        .map_or(0., |actor| actor.altitude().min(ALTITUDE_STEP))
+}
+
+impl Action for Descend {
+    fn name(&self) -> &str {
+        "descend"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["down"]
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::NoArgs
+    }
+
+    fn cost(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        let amount = descend_amount(encounter, caster_id);
+        Some(Resource::Movement(vertical_cost(
+            encounter, caster_id, amount,
+        )?))
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        descend_amount(encounter, caster_id) > 0.
+    }
+
+    fn side_effects(
+        &self,
+        encounter: &mut EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        vec![Box::new(ChangeAltitude {
+            actor_id: caster_id,
+            amount: -descend_amount(encounter, caster_id),
+        })]
+    }
+}
+
+pub static DESCEND: LazyLock<Descend> = LazyLock::new(|| Descend {});
+
+// getting up from prone costs half the actor's speed
+pub struct Stand {}
+
+impl Action for Stand {
+    fn name(&self) -> &str {
+        "stand"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["st"]
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::NoArgs
+    }
+
+    fn cost(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        let actor = encounter.actors.get(&caster_id)?;
+        Some(Resource::Movement(actor.speed() / 2.))
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        encounter
+            .actors
+            .get(&caster_id)
+            .is_some_and(|actor| actor.is_prone())
+    }
+
+    fn side_effects(
+        &self,
+        _encounter: &mut EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        vec![Box::new(StandUp {
+            actor_id: caster_id,
+        })]
+    }
+}
+
+pub static STAND: LazyLock<Stand> = LazyLock::new(|| Stand {});
@@ -1,5 +1,8 @@
 use crate::{
     actions::action_template::TargetingSchema,
+    actions::altitude::{ASCEND, DESCEND, STAND},
+    actions::interactions::{CLOSE_DOOR, FORCE_DOOR, OPEN_DOOR, PICK_LOCK},
+    actions::search::{INVESTIGATE, SEARCH},
     engine::{side_effects::GiveResource, types::Coordinate},
 };
 use std::{collections::HashSet, sync::LazyLock};
@@ -10,7 +13,6 @@ use crate::{
         action_overrides::ActionOverride,
         encounter::EncounterInstance,
         side_effects::{MoveActor, Resource, SkipTurn},
-        util::tile_center_dist,
     },
 };
 
@@ -37,9 +39,11 @@ impl Action for Move {
         target_locations: Option<&Vec<Coordinate>>,
         _overrides: Option<&HashSet<ActionOverride>>,
     ) -> Option<Resource> {
-        let actor = encounter.actors.get(&caster_id).expect("missing actor");
-        let dest: Coordinate = *target_locations.unwrap().first().unwrap();
-        let dist = tile_center_dist(actor.location(), dest);
+        let dest: Coordinate = *target_locations?.first()?;
+        let dist = match encounter.find_path(caster_id, dest) {
+            Some(path) => path.cost,
+            None => f32::INFINITY,
+        };
         Some(Resource::Movement(dist))
     }
 
@@ -51,22 +55,49 @@ impl Action for Move {
         target_locations: Option<&Vec<Coordinate>>,
         _overrides: Option<&HashSet<ActionOverride>>,
     ) -> bool {
-        let coord: Coordinate = *target_locations.unwrap().first().unwrap();
-        encounter.can_move_to(caster_id, coord)
+        let Some(&coord) = target_locations.and_then(|tl| tl.first()) else {
+            return false;
+        };
+        encounter.find_path(caster_id, coord).is_some()
+    }
+
+    fn preview(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Coordinate> {
+        let Some(&coord) = target_locations.and_then(|tl| tl.first()) else {
+            return Vec::new();
+        };
+        match encounter.find_path(caster_id, coord) {
+            Some(path) => path.tiles,
+            None => Vec::new(),
+        }
     }
 
     fn side_effects(
         &self,
-        _encounter: &mut EncounterInstance,
+        encounter: &mut EncounterInstance,
         caster_id: usize,
         _target_ids: Option<&Vec<usize>>,
         target_locations: Option<&Vec<Coordinate>>,
         _overrides: Option<&HashSet<ActionOverride>>,
     ) -> Vec<Box<dyn crate::engine::side_effects::ApplicableSideEffect>> {
-        let target_location: Coordinate = target_locations.unwrap().first().unwrap().clone();
+        let Some(&target_location) = target_locations.and_then(|tl| tl.first()) else {
+            return Vec::new();
+        };
+        let (path, movement_paid) = encounter
+            .find_path(caster_id, target_location)
+            .map(|p| (p.tiles, p.cost))
+            .unwrap_or_default();
         vec![Box::new(MoveActor {
             actor_id: caster_id,
             target: target_location,
+            path,
+            movement_paid,
         })]
     }
 }
@@ -147,10 +178,10 @@ impl Action for Dash {
         _target_locations: Option<&Vec<Coordinate>>,
         _overrides: Option<&HashSet<ActionOverride>>,
     ) -> Vec<Box<dyn crate::engine::side_effects::ApplicableSideEffect>> {
-        let speed = encounter
-            .get_actor(caster_id)
-            .expect("missing actor")
-            .speed();
+        let Some(actor) = encounter.get_actor(caster_id) else {
+            return Vec::new();
+        };
+        let speed = actor.speed();
         vec![Box::new(GiveResource {
             actor_id: caster_id,
             resource: Resource::Movement(speed),
@@ -161,4 +192,19 @@ impl Action for Dash {
 pub static DASH: LazyLock<Dash> = LazyLock::new(|| Dash {});
 
 pub static DEFAULT_ACTIONS: LazyLock<Vec<&'static (dyn Action + Send + Sync)>> =
-    LazyLock::new(|| vec![&*MOVE, &*DASH, &*SKIP]);
+    LazyLock::new(|| {
+        vec![
+            &*MOVE,
+            &*DASH,
+            &*SKIP,
+            &*OPEN_DOOR,
+            &*CLOSE_DOOR,
+            &*PICK_LOCK,
+            &*FORCE_DOOR,
+            &*ASCEND,
+            &*DESCEND,
+            &*STAND,
+            &*SEARCH,
+            &*INVESTIGATE,
+        ]
+    });
@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use crate::{
+    actions::action_template::{Action, TargetingSchema},
+    engine::{
+        action_overrides::ActionOverride,
+        encounter::EncounterInstance,
+        side_effects::{ApplicableSideEffect, MoveActor, Resource},
+        types::Coordinate,
+    },
+};
+
+// places the actor anywhere in its team's spawn zone before the fight starts;
+// takes the same coordinates as move
+pub struct Deploy {}
+
+impl Action for Deploy {
+    fn name(&self) -> &str {
+        "deploy"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["dp", "mv"]
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::SinglePoint
+    }
+
+    fn cost(
+        &self,
+        _encounter: &EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        None
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        let Some(&coord) = target_locations.and_then(|tl| tl.first()) else {
+            return false;
+        };
+        encounter.can_deploy_to(caster_id, coord)
+    }
+
+    fn preview(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Coordinate> {
+        match target_locations.and_then(|tl| tl.first()) {
+            Some(&coord) if encounter.can_deploy_to(caster_id, coord) => vec![coord],
+            _ => Vec::new(),
+        }
+    }
+
+    fn side_effects(
+        &self,
+        _encounter: &mut EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        let Some(&target_location) = target_locations.and_then(|tl| tl.first()) else {
+            return Vec::new();
+        };
+        vec![Box::new(MoveActor {
+            actor_id: caster_id,
+            target: target_location,
+            path: Vec::new(),
+            movement_paid: 0.,
+        })]
+    }
+}
+
+pub static DEPLOY: LazyLock<Deploy> = LazyLock::new(|| Deploy {});
+
+// leaves the actor where it spawned
+pub struct Keep {}
+
+impl Action for Keep {
+    fn name(&self) -> &str {
+        "keep"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["k"]
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::NoArgs
+    }
+
+    fn cost(
+        &self,
+        _encounter: &EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        None
+    }
+
+    fn side_effects(
+        &self,
+        _encounter: &mut EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        Vec::new()
+    }
+}
+
+pub static KEEP: LazyLock<Keep> = LazyLock::new(|| Keep {});
+
+pub static DEPLOYMENT_ACTIONS: LazyLock<Vec<&'static (dyn Action + Send + Sync)>> =
+    LazyLock::new(|| vec![&*DEPLOY, &*KEEP]);
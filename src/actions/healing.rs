@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use tyche::Expr;
+
+use crate::{
+    actions::action_template::{Action, ActionRole, TargetingSchema},
+    engine::{
+        action_overrides::ActionOverride,
+        combat::{in_reach, roll_healing},
+        encounter::EncounterInstance,
+        side_effects::{ApplicableSideEffect, Heal, Resource},
+        types::Coordinate,
+        visibility::{Cover, cover_between},
+    },
+};
+
+// restores hit points to a creature in range, the caster included; nothing
+// brings back a creature that is already down
+pub struct Healing {
+    name: &'static str,
+    alias: &'static str,
+    healing: Expr,
+    range: f32,
+}
+
+impl Action for Healing {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec![self.alias]
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::SingleActor
+    }
+
+    fn role(&self) -> ActionRole {
+        ActionRole::Heal
+    }
+
+    fn cost(
+        &self,
+        _encounter: &EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        // TODO: spell slots once creatures have them
+        Some(Resource::Action)
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        let Some(&target_id) = target_ids.and_then(|ti| ti.first()) else {
+            return false;
+        };
+        let Some(target) = encounter.actors.get(&target_id) else {
+            return false;
+        };
+        if target.is_down() {
+            return false;
+        }
+        target_id == caster_id
+            || (in_reach(encounter, caster_id, target_id, self.range)
+                && cover_between(encounter, caster_id, target_id) != Cover::Total)
+    }
+
+    fn side_effects(
+        &self,
+        encounter: &mut EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        let Some(&target_id) = target_ids.and_then(|ti| ti.first()) else {
+            return Vec::new();
+        };
+        let target = encounter.actor_name(target_id);
+        let amount = roll_healing(encounter, caster_id, &self.healing, target);
+        vec![Box::new(Heal {
+            actor_id: target_id,
+            amount,
+        })]
+    }
+}
+
+pub static CURE_WOUNDS: LazyLock<Healing> = LazyLock::new(|| Healing {
+    name: "cure",
+    alias: "cw",
+    healing: "1d8+2".parse().unwrap(),
+    range: 5.,
+});
@@ -0,0 +1,398 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use crate::{
+    actions::action_template::{Action, TargetingSchema},
+    engine::{
+        action_overrides::ActionOverride,
+        combat::{roll_ability_check, tile_gap},
+        encounter::EncounterInstance,
+        side_effects::{ApplicableSideEffect, Resource, SetDoorState},
+        terrain::{DoorState, TerrainType},
+        types::{AbilityScoreType, Coordinate},
+    },
+};
+
+const INTERACTION_REACH: f32 = 5.;
+const PICK_LOCK_DC: i32 = 15;
+const FORCE_DOOR_DC: i32 = 20;
+
+// state of the door at the single target location, if the caster can reach it
+fn reachable_door(
+    encounter: &EncounterInstance,
+    caster_id: usize,
+    target_locations: Option<&Vec<Coordinate>>,
+) -> Option<DoorState> {
+    let coord: Coordinate = *target_locations?.first()?;
+    if tile_gap(encounter, caster_id, coord) >= INTERACTION_REACH {
+        return None;
+    }
+    match encounter.terrain_at(coord)?.terrain_type {
+        TerrainType::Door(state) => Some(state),
+        _ => None,
+    }
+}
+
+pub struct OpenDoor {}
+
+impl Action for OpenDoor {
+    fn name(&self) -> &str {
+        "open"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["op"]
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::SinglePoint
+    }
+
+    fn cost(
+        &self,
+        _encounter: &EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        Some(Resource::ObjectInteraction)
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        reachable_door(encounter, caster_id, target_locations) == Some(DoorState::Closed)
+    }
+
+    fn side_effects(
+        &self,
+        _encounter: &mut EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        let Some(&target) = target_locations.and_then(|tl| tl.first()) else {
+            return Vec::new();
+        };
+        vec![Box::new(SetDoorState {
+            target,
+            state: DoorState::Open,
+        })]
+    }
+}
+
+pub static OPEN_DOOR: LazyLock<OpenDoor> = LazyLock::new(|| OpenDoor {});
+
+pub struct CloseDoor {}
+
+impl Action for CloseDoor {
+    fn name(&self) -> &str {
+        "close"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["cl"]
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::SinglePoint
+    }
+
+    fn cost(
+        &self,
+        _encounter: &EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        Some(Resource::ObjectInteraction)
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        if reachable_door(encounter, caster_id, target_locations) != Some(DoorState::Open) {
+            return false;
+        }
+        // cannot close a door on someone standing in the doorway
+        let Some(&target) = target_locations.and_then(|tl| tl.first()) else {
+            return false;
+        };
+        encounter
+            .door_tiles(target)
+            .iter()
+            .all(|&c| encounter.actor_id_at(c).is_none())
+    }
+
+    fn side_effects(
+        &self,
+        _encounter: &mut EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        let Some(&target) = target_locations.and_then(|tl| tl.first()) else {
+            return Vec::new();
+        };
+        vec![Box::new(SetDoorState {
+            target,
+            state: DoorState::Closed,
+        })]
+    }
+}
+
+pub static CLOSE_DOOR: LazyLock<CloseDoor> = LazyLock::new(|| CloseDoor {});
+
+pub struct PickLock {}
+
+impl Action for PickLock {
+    fn name(&self) -> &str {
+        "picklock"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["pick"]
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::SinglePoint
+    }
+
+    fn cost(
+        &self,
+        _encounter: &EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        Some(Resource::Action)
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        reachable_door(encounter, caster_id, target_locations) == Some(DoorState::Locked)
+    }
+
+    fn side_effects(
+        &self,
+        encounter: &mut EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        let Some(&target) = target_locations.and_then(|tl| tl.first()) else {
+            return Vec::new();
+        };
+        if !roll_ability_check(
+            encounter,
+            caster_id,
+            AbilityScoreType::Dexterity,
+            PICK_LOCK_DC,
+        ) {
+            return Vec::new();
+        }
+        vec![Box::new(SetDoorState {
+            target,
+            state: DoorState::Closed,
+        })]
+    }
+}
+
+pub static PICK_LOCK: LazyLock<PickLock> = LazyLock::new(|| PickLock {});
+
+pub struct ForceDoor {}
+
+impl Action for ForceDoor {
+    fn name(&self) -> &str {
+        "force"
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec!["frc"]
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::SinglePoint
+    }
+
+    fn cost(
+        &self,
+        _encounter: &EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        Some(Resource::Action)
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        matches!(
+            reachable_door(encounter, caster_id, target_locations),
+            Some(DoorState::Closed | DoorState::Locked)
+        )
+    }
+
+    fn side_effects(
+        &self,
+        encounter: &mut EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        let Some(&target) = target_locations.and_then(|tl| tl.first()) else {
+            return Vec::new();
+        };
+        if !roll_ability_check(
+            encounter,
+            caster_id,
+            AbilityScoreType::Strength,
+            FORCE_DOOR_DC,
+        ) {
+            return Vec::new();
+        }
+        vec![Box::new(SetDoorState {
+            target,
+            state: DoorState::Open,
+        })]
+    }
+}
+
+pub static FORCE_DOOR: LazyLock<ForceDoor> = LazyLock::new(|| ForceDoor {});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_util::encounter_from;
+
+    // a zombie with a two tile door beside it and another two tiles away
+    const DOORS: &str = "[legend]\nL locked_door\n[map]\n00{d}.{d}...11\n00{d}.{d}...11\n\
+                         [creatures]\nZombie 0,0 0\nZombie 8,0 1\n";
+
+    fn doors(glyph: char) -> EncounterInstance {
+        encounter_from(&DOORS.replace("{d}", &glyph.to_string()), 0)
+    }
+
+    fn allowed(action: &dyn Action, encounter: &EncounterInstance, x: isize) -> bool {
+        let target = vec![Coordinate::new(x, 0)];
+        action.custom_validate_input(encounter, 0, None, Some(&target), None)
+    }
+
+    fn door_state(encounter: &EncounterInstance, x: isize) -> Vec<DoorState> {
+        (0..2)
+            .filter_map(
+                |y| match encounter.terrain_at(Coordinate::new(x, y))?.terrain_type {
+                    TerrainType::Door(state) => Some(state),
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    // applies the action's side effects without spending anything
+    fn attempt(action: &dyn Action, encounter: &mut EncounterInstance, x: isize) {
+        let target = vec![Coordinate::new(x, 0)];
+        for se in action.side_effects(encounter, 0, None, Some(&target), None) {
+            se.apply(encounter).unwrap();
+        }
+    }
+
+    #[test]
+    fn each_interaction_needs_a_door_in_reach_in_the_right_state() {
+        let closed = doors('+');
+        assert!(allowed(&*OPEN_DOOR, &closed, 2));
+        assert!(!allowed(&*CLOSE_DOOR, &closed, 2));
+        assert!(!allowed(&*PICK_LOCK, &closed, 2));
+        assert!(allowed(&*FORCE_DOOR, &closed, 2));
+        assert!(!allowed(&*OPEN_DOOR, &closed, 4));
+        assert!(!allowed(&*OPEN_DOOR, &closed, 3));
+
+        let open = doors('/');
+        assert!(!allowed(&*OPEN_DOOR, &open, 2));
+        assert!(allowed(&*CLOSE_DOOR, &open, 2));
+        assert!(!allowed(&*FORCE_DOOR, &open, 2));
+
+        let locked = doors('L');
+        assert!(!allowed(&*OPEN_DOOR, &locked, 2));
+        assert!(allowed(&*PICK_LOCK, &locked, 2));
+        assert!(allowed(&*FORCE_DOOR, &locked, 2));
+    }
+
+    #[test]
+    fn opening_and_closing_use_the_object_interaction() {
+        let mut encounter = doors('+');
+        let target = vec![Coordinate::new(2, 0)];
+        for action in [&*OPEN_DOOR as &dyn Action, &*CLOSE_DOOR] {
+            let cost = action.cost(&encounter, 0, None, Some(&target), None);
+            assert!(cost == Some(Resource::ObjectInteraction));
+        }
+        for action in [&*PICK_LOCK as &dyn Action, &*FORCE_DOOR] {
+            let cost = action.cost(&encounter, 0, None, Some(&target), None);
+            assert!(cost == Some(Resource::Action));
+        }
+
+        attempt(&*OPEN_DOOR, &mut encounter, 2);
+        assert_eq!(door_state(&encounter, 2), vec![DoorState::Open; 2]);
+        assert!(!encounter.blocks_sight(Coordinate::new(2, 1)));
+        attempt(&*CLOSE_DOOR, &mut encounter, 2);
+        assert_eq!(door_state(&encounter, 2), vec![DoorState::Closed; 2]);
+        assert!(encounter.blocks_sight(Coordinate::new(2, 1)));
+    }
+
+    #[test]
+    fn a_door_cannot_be_closed_on_someone_in_the_doorway() {
+        let map = "[map]\n00//....11\n00//....11\n[creatures]\nZombie 0,0 0\nZombie 2,0 1\n";
+        let encounter = encounter_from(map, 0);
+        assert!(!allowed(&*CLOSE_DOOR, &encounter, 2));
+    }
+
+    #[test]
+    fn picking_and_forcing_depend_on_the_check() {
+        for (action, unlocked) in [
+            (&*PICK_LOCK as &dyn Action, DoorState::Closed),
+            (&*FORCE_DOOR, DoorState::Open),
+        ] {
+            let (mut held, mut gave) = (false, false);
+            for seed in 0..100 {
+                let mut encounter = encounter_from(&DOORS.replace("{d}", "L"), seed);
+                attempt(action, &mut encounter, 2);
+                match door_state(&encounter, 2)[..] {
+                    [DoorState::Locked, DoorState::Locked] => held = true,
+                    [a, b] if a == unlocked && b == unlocked => gave = true,
+                    ref other => panic!("{} left the door {:?}", action.name(), other),
+                }
+            }
+            assert!(held && gave, "{} always had the same result", action.name());
+        }
+    }
+}
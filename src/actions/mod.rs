@@ -1,3 +1,10 @@
 pub mod action_template;
+pub mod altitude;
 pub mod default_actions;
+pub mod deployment;
+pub mod healing;
+pub mod interactions;
 pub mod monster_attacks;
+pub mod registry;
+pub mod scripted;
+pub mod search;
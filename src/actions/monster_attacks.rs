@@ -1,14 +1,25 @@
 use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use tyche::Expr;
 
 use crate::{
-    actions::action_template::{Action, TargetingSchema},
+    actions::action_template::{Action, ActionRole, TargetingSchema},
     engine::{
-        action_overrides::ActionOverride, encounter::EncounterInstance, side_effects::Resource,
-        types::Coordinate,
+        action_overrides::ActionOverride,
+        combat::{in_reach, roll_attack, roll_attack_terrain, tile_gap},
+        encounter::EncounterInstance,
+        side_effects::Resource,
+        types::{Coordinate, DamageType},
+        visibility::{Cover, cover_between},
     },
 };
 
-pub struct Slam {}
+pub struct Slam {
+    attack_bonus: i32,
+    damage: Expr,
+    reach: f32,
+}
 
 impl Action for Slam {
     fn name(&self) -> &str {
@@ -19,10 +30,115 @@ impl Action for Slam {
         vec!["slm"]
     }
 
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::SingleActorOrPoint
+    }
+
+    fn role(&self) -> ActionRole {
+        ActionRole::Attack { ranged: false }
+    }
+
+    fn cost(
+        &self,
+        _encounter: &EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        Some(Resource::Action)
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        if let Some(&coord) = target_locations.and_then(|tl| tl.first()) {
+            return encounter
+                .terrain_at(coord)
+                .is_some_and(|ti| ti.hitpoints.is_some())
+                && tile_gap(encounter, caster_id, coord) < self.reach;
+        }
+        let Some(&target_id) = target_ids.and_then(|ti| ti.first()) else {
+            return false;
+        };
+        target_id != caster_id
+            && in_reach(encounter, caster_id, target_id, self.reach)
+            && cover_between(encounter, caster_id, target_id) != Cover::Total
+    }
+
+    fn side_effects(
+        &self,
+        encounter: &mut EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn crate::engine::side_effects::ApplicableSideEffect>> {
+        if let Some(&coord) = target_locations.and_then(|tl| tl.first()) {
+            return roll_attack_terrain(
+                encounter,
+                caster_id,
+                coord,
+                self.attack_bonus,
+                &self.damage,
+            );
+        }
+        let Some(&target_id) = target_ids.and_then(|ti| ti.first()) else {
+            return Vec::new();
+        };
+        roll_attack(
+            encounter,
+            caster_id,
+            target_id,
+            self.attack_bonus,
+            &self.damage,
+            DamageType::Bludgeoning,
+        )
+    }
+}
+
+pub static ZOMBIE_SLAM: LazyLock<Slam> = LazyLock::new(|| Slam {
+    attack_bonus: 3,
+    damage: "1d6+1".parse().unwrap(),
+    reach: 5.,
+});
+
+// a weapon attack on a creature; range is the reach of a melee weapon or the
+// normal range of a ranged one
+pub struct WeaponAttack {
+    name: &'static str,
+    alias: &'static str,
+    attack_bonus: i32,
+    damage: Expr,
+    damage_type: DamageType,
+    range: f32,
+    ranged: bool,
+}
+
+impl Action for WeaponAttack {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        vec![self.alias]
+    }
+
     fn targeting_schema(&self) -> TargetingSchema {
         TargetingSchema::SingleActor
     }
 
+    fn role(&self) -> ActionRole {
+        ActionRole::Attack {
+            ranged: self.ranged,
+        }
+    }
+
     fn cost(
         &self,
         _encounter: &EncounterInstance,
@@ -34,6 +150,22 @@ impl Action for Slam {
         Some(Resource::Action)
     }
 
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        let Some(&target_id) = target_ids.and_then(|ti| ti.first()) else {
+            return false;
+        };
+        target_id != caster_id
+            && in_reach(encounter, caster_id, target_id, self.range)
+            && cover_between(encounter, caster_id, target_id) != Cover::Total
+    }
+
     fn side_effects(
         &self,
         encounter: &mut EncounterInstance,
@@ -42,28 +174,46 @@ impl Action for Slam {
         _target_locations: Option<&Vec<Coordinate>>,
         _overrides: Option<&HashSet<ActionOverride>>,
     ) -> Vec<Box<dyn crate::engine::side_effects::ApplicableSideEffect>> {
-        let mut effects: Vec<Box<dyn crate::engine::side_effects::ApplicableSideEffect>> = vec![];
-        // TODO
-        // if let Some(target_ids) = target_ids {
-        //     let target_id = target_ids[0];
-        //     let caster = encounter.actors.get(&caster_id).unwrap();
-        //     let target = encounter.actors.get(&target_id).unwrap();
-
-        //     // Attack roll
-        //     let attack_roll = Roll::d20().roll() + caster.attack_bonus();
-        //     let attack_successful = attack_roll >= target.armor_class();
-
-        //     if attack_successful {
-        //         // Damage roll
-        //         let damage_roll = Roll::from_str("2d6").unwrap().roll() + caster.damage_bonus();
-        //         effects.push(Box::new(DealDamage {
-        //             actor_id: target_id,
-        //             amount: damage_roll,
-        //             damage_type: DamageType::Bludgeoning,
-        //         }));
-        //     }
-        // }
-
-        effects
+        let Some(&target_id) = target_ids.and_then(|ti| ti.first()) else {
+            return Vec::new();
+        };
+        roll_attack(
+            encounter,
+            caster_id,
+            target_id,
+            self.attack_bonus,
+            &self.damage,
+            self.damage_type.clone(),
+        )
     }
 }
+
+pub static SKELETON_SHORTSWORD: LazyLock<WeaponAttack> = LazyLock::new(|| WeaponAttack {
+    name: "shortsword",
+    alias: "ss",
+    attack_bonus: 4,
+    damage: "1d6+2".parse().unwrap(),
+    damage_type: DamageType::Piercing,
+    range: 5.,
+    ranged: false,
+});
+
+pub static SKELETON_SHORTBOW: LazyLock<WeaponAttack> = LazyLock::new(|| WeaponAttack {
+    name: "shortbow",
+    alias: "sb",
+    attack_bonus: 4,
+    damage: "1d6+2".parse().unwrap(),
+    damage_type: DamageType::Piercing,
+    range: 80.,
+    ranged: true,
+});
+
+pub static ACOLYTE_CLUB: LazyLock<WeaponAttack> = LazyLock::new(|| WeaponAttack {
+    name: "club",
+    alias: "cl",
+    attack_bonus: 2,
+    damage: "1d4".parse().unwrap(),
+    damage_type: DamageType::Bludgeoning,
+    range: 5.,
+    ranged: false,
+});
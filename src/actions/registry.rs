@@ -0,0 +1,138 @@
+use std::sync::LazyLock;
+
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::actions::action_template::Action;
+use crate::actions::altitude::{ASCEND, DESCEND, STAND};
+use crate::actions::default_actions::{DASH, MOVE, SKIP};
+use crate::actions::deployment::{DEPLOY, KEEP};
+use crate::actions::healing::CURE_WOUNDS;
+use crate::actions::interactions::{CLOSE_DOOR, FORCE_DOOR, OPEN_DOOR, PICK_LOCK};
+use crate::actions::monster_attacks::{
+    ACOLYTE_CLUB, SKELETON_SHORTBOW, SKELETON_SHORTSWORD, ZOMBIE_SLAM,
+};
+use crate::actions::scripted::scripted_actions;
+use crate::actions::search::{INVESTIGATE, SEARCH};
+
+// every action under the id saved encounters refer to it by; once saves
+// exist an id has to keep meaning the same action
+pub static ACTION_REGISTRY: LazyLock<Vec<(&'static str, &'static (dyn Action + Send + Sync))>> =
+    LazyLock::new(|| {
+        vec![
+            ("move", &*MOVE),
+            ("dash", &*DASH),
+            ("skip", &*SKIP),
+            ("open_door", &*OPEN_DOOR),
+            ("close_door", &*CLOSE_DOOR),
+            ("pick_lock", &*PICK_LOCK),
+            ("force_door", &*FORCE_DOOR),
+            ("ascend", &*ASCEND),
+            ("descend", &*DESCEND),
+            ("stand", &*STAND),
+            ("search", &*SEARCH),
+            ("investigate", &*INVESTIGATE),
+            ("deploy", &*DEPLOY),
+            ("keep", &*KEEP),
+            ("zombie_slam", &*ZOMBIE_SLAM),
+            ("skeleton_shortsword", &*SKELETON_SHORTSWORD),
+            ("skeleton_shortbow", &*SKELETON_SHORTBOW),
+            ("acolyte_club", &*ACOLYTE_CLUB),
+            ("cure_wounds", &*CURE_WOUNDS),
+        ]
+    });
+
+// the built in actions followed by the scripted ones
+fn registered() -> impl Iterator<Item = (&'static str, &'static (dyn Action + Send + Sync))> {
+    let scripted = scripted_actions()
+        .iter()
+        .map(|&a| (a.id(), a as &'static (dyn Action + Send + Sync)));
+    ACTION_REGISTRY.iter().copied().chain(scripted)
+}
+
+pub fn action_id(action: &dyn Action) -> Option<&'static str> {
+    registered()
+        .find(|(_, a)| std::ptr::addr_eq(*a as *const dyn Action, action as *const dyn Action))
+        .map(|(id, _)| id)
+}
+
+pub fn action_by_id(id: &str) -> Option<&'static (dyn Action + Send + Sync)> {
+    registered()
+        .find(|(action_id, _)| *action_id == id)
+        .map(|(_, a)| a)
+}
+
+fn serialize_action<S: Serializer>(action: &dyn Action, serializer: S) -> Result<S::Ok, S::Error> {
+    match action_id(action) {
+        Some(id) => id.serialize(serializer),
+        None => Err(S::Error::custom(format!(
+            "action {:?} is not registered",
+            action.name()
+        ))),
+    }
+}
+
+fn deserialize_action<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<&'static (dyn Action + Send + Sync), D::Error> {
+    let id = String::deserialize(deserializer)?;
+    action_by_id(&id).ok_or_else(|| D::Error::custom(format!("unknown action id {:?}", id)))
+}
+
+// for fields holding one action, as #[serde(with = "action_ref")]
+pub mod action_ref {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        action: &&'static dyn Action,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serialize_action(*action, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<&'static dyn Action, D::Error> {
+        Ok(deserialize_action(deserializer)?)
+    }
+}
+
+// for fields holding a list of actions, as #[serde(with = "action_refs")]
+pub mod action_refs {
+    use serde::ser::SerializeSeq;
+
+    use super::*;
+
+    struct Registered(&'static (dyn Action + Send + Sync));
+
+    impl Serialize for Registered {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize_action(self.0, serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Registered {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Ok(Registered(deserialize_action(deserializer)?))
+        }
+    }
+
+    pub fn serialize<S: Serializer>(
+        actions: &[&'static (dyn Action + Send + Sync)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(actions.len()))?;
+        for &action in actions {
+            seq.serialize_element(&Registered(action))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<&'static (dyn Action + Send + Sync)>, D::Error> {
+        let actions = Vec::<Registered>::deserialize(deserializer)?;
+        Ok(actions.into_iter().map(|a| a.0).collect())
+    }
+}
@@ -0,0 +1,797 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::{LazyLock, OnceLock};
+
+use rhai::{AST, Array, CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope};
+use tyche::Expr;
+
+use crate::actions::action_template::{Action, ActionRole, TargetingSchema};
+use crate::actions::registry::ACTION_REGISTRY;
+use crate::actors::creatures::creature_template_by_name;
+use crate::engine::{
+    action_overrides::ActionOverride,
+    combat::{footprint_gap, roll_attack, roll_damage, roll_healing, roll_saving_throw, tile_gap},
+    encounter::EncounterInstance,
+    errors::{EngineError, ScriptError},
+    events::EncounterEvent,
+    side_effects::{
+        ApplicableSideEffect, ConsumeResource, DealDamage, GiveResource, Heal, KnockProne,
+        MoveActor, Resource, StandUp,
+    },
+    types::{AbilityScoreType, Coordinate, DamageType},
+    visibility::{Cover, cover_between},
+};
+
+// Actions written in Rhai, one per .rhai file, for homebrew abilities that
+// don't warrant a Rust struct. A script defines
+//
+//   info()         #{ name, aliases, targeting, creatures, role }, where
+//                  targeting is one of no_args, single_point, single_actor,
+//                  single_actor_or_point or custom, and the optional role
+//                  one of attack, ranged_attack or heal for the AI to use
+//                  the action as
+//   cost(view)     optional; "action", "bonus_action", "reaction",
+//                  "object_interaction", feet of movement, or () for free
+//   validate(view) optional; true if the action can be taken
+//   effects(view)  an array of effects, see apply_effects
+//
+// view is a map of the caster, targets, points, actors and round. Scripts
+// only ever see copies of the encounter and describe what should happen;
+// the dice are rolled and the effects applied here, so a script can't reach
+// anything the view and the effects don't cover.
+
+// a runaway script is cut off rather than hanging the game
+const MAX_OPERATIONS: u64 = 100_000;
+
+static SCRIPT_ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(10_000)
+        .set_max_array_size(1_000)
+        .set_max_map_size(1_000)
+        .on_print(|_| {})
+        .on_debug(|_, _, _| {})
+        .disable_symbol("eval");
+    engine
+});
+
+static SCRIPTED_ACTIONS: OnceLock<Vec<&'static ScriptedAction>> = OnceLock::new();
+
+pub struct ScriptedAction {
+    // what saved encounters call it by, kept apart from the built in ids
+    id: String,
+    name: String,
+    aliases: Vec<String>,
+    targeting: String,
+    // templates whose creatures get the action
+    creatures: Vec<String>,
+    role: ActionRole,
+    has_cost: bool,
+    has_validate: bool,
+    ast: AST,
+}
+
+fn targeting_schema(name: &str) -> Option<TargetingSchema> {
+    match name {
+        "no_args" => Some(TargetingSchema::NoArgs),
+        "single_point" => Some(TargetingSchema::SinglePoint),
+        "single_actor" => Some(TargetingSchema::SingleActor),
+        "single_actor_or_point" => Some(TargetingSchema::SingleActorOrPoint),
+        "custom" => Some(TargetingSchema::Custom),
+        _ => None,
+    }
+}
+
+fn parse_role(name: &str) -> Option<ActionRole> {
+    match name {
+        "attack" => Some(ActionRole::Attack { ranged: false }),
+        "ranged_attack" => Some(ActionRole::Attack { ranged: true }),
+        "heal" => Some(ActionRole::Heal),
+        _ => None,
+    }
+}
+
+fn parse_damage_type(name: &str) -> Option<DamageType> {
+    match name.to_ascii_lowercase().as_str() {
+        "acid" => Some(DamageType::Acid),
+        "bludgeoning" => Some(DamageType::Bludgeoning),
+        "cold" => Some(DamageType::Cold),
+        "fire" => Some(DamageType::Fire),
+        "force" => Some(DamageType::Force),
+        "lightning" => Some(DamageType::Lightning),
+        "necrotic" => Some(DamageType::Necrotic),
+        "piercing" => Some(DamageType::Piercing),
+        "poison" => Some(DamageType::Poison),
+        "psychic" => Some(DamageType::Psychic),
+        "radiant" => Some(DamageType::Radiant),
+        "slashing" => Some(DamageType::Slashing),
+        "thunder" => Some(DamageType::Thunder),
+        _ => None,
+    }
+}
+
+fn parse_ability(name: &str) -> Option<AbilityScoreType> {
+    match name.to_ascii_lowercase().as_str() {
+        "str" | "strength" => Some(AbilityScoreType::Strength),
+        "dex" | "dexterity" => Some(AbilityScoreType::Dexterity),
+        "con" | "constitution" => Some(AbilityScoreType::Constitution),
+        "int" | "intelligence" => Some(AbilityScoreType::Intelligence),
+        "wis" | "wisdom" => Some(AbilityScoreType::Wisdom),
+        "cha" | "charisma" => Some(AbilityScoreType::Charisma),
+        _ => None,
+    }
+}
+
+fn cover_name(cover: Cover) -> &'static str {
+    match cover {
+        Cover::None => "none",
+        Cover::Half => "half",
+        Cover::ThreeQuarters => "three_quarters",
+        Cover::Total => "total",
+    }
+}
+
+// an actor as scripts see it; distance is in feet from the caster, 0 when
+// adjacent, and cover is what the actor has against the caster
+fn actor_view(encounter: &EncounterInstance, caster_id: usize, actor_id: usize) -> Dynamic {
+    let Some(actor) = encounter.actors.get(&actor_id) else {
+        return Dynamic::UNIT;
+    };
+    let mut map = Map::new();
+    map.insert("id".into(), (actor_id as i64).into());
+    map.insert("name".into(), actor.name().into());
+    map.insert("template".into(), actor.template_name().into());
+    map.insert("team".into(), (actor.team() as i64).into());
+    map.insert("x".into(), (actor.location().x as i64).into());
+    map.insert("y".into(), (actor.location().y as i64).into());
+    map.insert("hitpoints".into(), (actor.hitpoints() as i64).into());
+    map.insert(
+        "max_hitpoints".into(),
+        (actor.max_hitpoints() as i64).into(),
+    );
+    map.insert("ac".into(), (actor.armor_class() as i64).into());
+    map.insert("speed".into(), (actor.speed() as f64).into());
+    map.insert(
+        "movement".into(),
+        (actor.remaining_movement() as f64).into(),
+    );
+    map.insert("altitude".into(), (actor.altitude() as f64).into());
+    map.insert("prone".into(), actor.is_prone().into());
+    map.insert("down".into(), actor.is_down().into());
+    let distance = match actor_id == caster_id {
+        true => 0.,
+        false => footprint_gap(encounter, caster_id, actor_id),
+    };
+    map.insert("distance".into(), (distance as f64).into());
+    let cover = match actor_id == caster_id {
+        true => Cover::None,
+        false => cover_between(encounter, caster_id, actor_id),
+    };
+    map.insert("cover".into(), cover_name(cover).into());
+    map.into()
+}
+
+// a targeted tile; actor is the id of whoever stands there, or ()
+fn point_view(encounter: &EncounterInstance, caster_id: usize, coord: Coordinate) -> Dynamic {
+    let mut map = Map::new();
+    map.insert("x".into(), (coord.x as i64).into());
+    map.insert("y".into(), (coord.y as i64).into());
+    let terrain = encounter.terrain_at(coord);
+    map.insert("in_bounds".into(), terrain.is_some().into());
+    if let Some(terrain) = terrain {
+        map.insert("passable".into(), terrain.terrain_type.is_passable().into());
+        map.insert(
+            "blocks_sight".into(),
+            terrain.terrain_type.blocks_sight().into(),
+        );
+        map.insert(
+            "distance".into(),
+            (tile_gap(encounter, caster_id, coord) as f64).into(),
+        );
+    }
+    let occupant = encounter
+        .actors
+        .keys()
+        .find(|&&id| encounter.footprint(id).contains(&coord))
+        .map_or(Dynamic::UNIT, |&id| (id as i64).into());
+    map.insert("actor".into(), occupant);
+    map.into()
+}
+
+fn script_view(
+    encounter: &EncounterInstance,
+    caster_id: usize,
+    target_ids: Option<&Vec<usize>>,
+    target_locations: Option<&Vec<Coordinate>>,
+) -> Map {
+    let targets: Array = target_ids
+        .into_iter()
+        .flatten()
+        .map(|&id| actor_view(encounter, caster_id, id))
+        .collect();
+    let points: Array = target_locations
+        .into_iter()
+        .flatten()
+        .map(|&coord| point_view(encounter, caster_id, coord))
+        .collect();
+    let actors: Array = encounter
+        .actors
+        .keys()
+        .map(|&id| actor_view(encounter, caster_id, id))
+        .collect();
+    let mut view = Map::new();
+    view.insert("caster".into(), actor_view(encounter, caster_id, caster_id));
+    view.insert("targets".into(), targets.into());
+    view.insert("points".into(), points.into());
+    view.insert("actors".into(), actors.into());
+    view.insert("round".into(), (encounter.round() as i64).into());
+    view
+}
+
+fn field<'a>(effect: &'a Map, key: &str) -> Result<&'a Dynamic, String> {
+    effect
+        .get(key)
+        .ok_or_else(|| format!("effect is missing {:?}", key))
+}
+
+fn int_field(effect: &Map, key: &str) -> Result<i64, String> {
+    field(effect, key)?
+        .as_int()
+        .map_err(|t| format!("{:?} should be an integer, not {}", key, t))
+}
+
+fn string_field(effect: &Map, key: &str) -> Result<String, String> {
+    field(effect, key)?
+        .clone()
+        .into_string()
+        .map_err(|t| format!("{:?} should be a string, not {}", key, t))
+}
+
+// the nested effects under key, if there are any
+fn effects_field(effect: &Map, key: &str) -> Result<Array, String> {
+    match effect.get(key) {
+        Some(value) => value
+            .clone()
+            .into_array()
+            .map_err(|t| format!("{:?} should be an array, not {}", key, t)),
+        None => Ok(Array::new()),
+    }
+}
+
+fn actor_field(encounter: &EncounterInstance, effect: &Map, key: &str) -> Result<usize, String> {
+    let actor_id = int_field(effect, key)?;
+    usize::try_from(actor_id)
+        .ok()
+        .filter(|id| encounter.actors.contains_key(id))
+        .ok_or_else(|| format!("no actor with id {}", actor_id))
+}
+
+fn dice_field(effect: &Map, key: &str) -> Result<Expr, String> {
+    let dice = string_field(effect, key)?;
+    dice.parse()
+        .map_err(|_| format!("{:?} is not a dice expression", dice))
+}
+
+fn damage_type_field(effect: &Map) -> Result<DamageType, String> {
+    let name = string_field(effect, "damage_type")?;
+    parse_damage_type(&name).ok_or_else(|| format!("unknown damage type {:?}", name))
+}
+
+// Rolls and queues the effects in order. Each is a map with a kind:
+//
+//   attack  target, bonus, damage (dice), damage_type, on_hit, on_miss
+//   save    target, ability, dc, on_fail, on_success; made against the caster
+//   damage  target, damage (dice), damage_type; no roll to hit
+//   heal    target, healing (dice)
+//   prone   target
+//   stand   target
+//   move    x, y; the caster walks there
+//   dash    feet of extra movement for the caster
+//   note    text for the log
+fn apply_effects(
+    encounter: &mut EncounterInstance,
+    caster_id: usize,
+    effects: Array,
+    side_effects: &mut Vec<Box<dyn ApplicableSideEffect>>,
+) -> Result<(), String> {
+    for effect in effects {
+        let effect: Map = effect
+            .try_cast()
+            .ok_or_else(|| "effects should be maps".to_string())?;
+        let kind = string_field(&effect, "kind")?;
+        match kind.as_str() {
+            "attack" => {
+                let target_id = actor_field(encounter, &effect, "target")?;
+                let bonus = int_field(&effect, "bonus")? as i32;
+                let damage = dice_field(&effect, "damage")?;
+                let damage_type = damage_type_field(&effect)?;
+                let hits =
+                    roll_attack(encounter, caster_id, target_id, bonus, &damage, damage_type);
+                let follow_up = match hits.is_empty() {
+                    true => effects_field(&effect, "on_miss")?,
+                    false => effects_field(&effect, "on_hit")?,
+                };
+                side_effects.extend(hits);
+                apply_effects(encounter, caster_id, follow_up, side_effects)?;
+            }
+            "save" => {
+                let target_id = actor_field(encounter, &effect, "target")?;
+                let name = string_field(&effect, "ability")?;
+                let ability =
+                    parse_ability(&name).ok_or_else(|| format!("unknown ability {:?}", name))?;
+                let dc = int_field(&effect, "dc")? as i32;
+                let origin = encounter
+                    .actors
+                    .get(&caster_id)
+                    .map(|a| a.location())
+                    .ok_or_else(|| format!("no actor with id {}", caster_id))?;
+                let saved = roll_saving_throw(encounter, Some(origin), target_id, ability, dc);
+                let follow_up = match saved {
+                    true => effects_field(&effect, "on_success")?,
+                    false => effects_field(&effect, "on_fail")?,
+                };
+                apply_effects(encounter, caster_id, follow_up, side_effects)?;
+            }
+            "damage" => {
+                let target_id = actor_field(encounter, &effect, "target")?;
+                let damage = dice_field(&effect, "damage")?;
+                let damage_type = damage_type_field(&effect)?;
+                let amount =
+                    roll_damage(encounter, caster_id, &damage, format!("{:?}", damage_type));
+                side_effects.push(Box::new(DealDamage {
+                    actor_id: target_id,
+                    amount,
+                    damage_type,
+                }));
+            }
+            "heal" => {
+                let target_id = actor_field(encounter, &effect, "target")?;
+                let healing = dice_field(&effect, "healing")?;
+                let target = encounter.actor_name(target_id);
+                let amount = roll_healing(encounter, caster_id, &healing, target);
+                side_effects.push(Box::new(Heal {
+                    actor_id: target_id,
+                    amount,
+                }));
+            }
+            "prone" => {
+                let actor_id = actor_field(encounter, &effect, "target")?;
+                side_effects.push(Box::new(KnockProne { actor_id }));
+            }
+            "stand" => {
+                let actor_id = actor_field(encounter, &effect, "target")?;
+                side_effects.push(Box::new(StandUp { actor_id }));
+            }
+            "move" => {
+                let target = Coordinate::new(
+                    int_field(&effect, "x")? as isize,
+                    int_field(&effect, "y")? as isize,
+                );
+                let path = encounter
+                    .find_path(caster_id, target)
+                    .ok_or_else(|| format!("no path to {}", target))?;
+                side_effects.push(Box::new(MoveActor {
+                    actor_id: caster_id,
+                    target,
+                    path: path.tiles,
+                    movement_paid: 0.,
+                }));
+            }
+            "dash" => {
+                let feet = int_field(&effect, "feet")?;
+                side_effects.push(Box::new(GiveResource {
+                    actor_id: caster_id,
+                    resource: Resource::Movement(feet.max(0) as f32),
+                }));
+            }
+            "note" => {
+                let text = string_field(&effect, "text")?;
+                encounter.log(EncounterEvent::Note {
+                    text,
+                    actor_id: Some(caster_id),
+                    location: None,
+                });
+            }
+            _ => return Err(format!("unknown effect kind {:?}", kind)),
+        }
+    }
+    Ok(())
+}
+
+fn parse_cost(cost: Dynamic) -> Result<Option<Resource>, String> {
+    if cost.is_unit() {
+        return Ok(None);
+    }
+    if let Ok(feet) = cost.as_int() {
+        return Ok(Some(Resource::Movement(feet as f32)));
+    }
+    if let Ok(feet) = cost.as_float() {
+        return Ok(Some(Resource::Movement(feet as f32)));
+    }
+    let name = cost
+        .into_string()
+        .map_err(|t| format!("cost should be a string or feet, not {}", t))?;
+    match name.as_str() {
+        "action" => Ok(Some(Resource::Action)),
+        "bonus_action" => Ok(Some(Resource::BonusAction)),
+        "reaction" => Ok(Some(Resource::Reaction)),
+        "object_interaction" => Ok(Some(Resource::ObjectInteraction)),
+        _ => Err(format!("unknown cost {:?}", name)),
+    }
+}
+
+fn string_list(info: &Map, key: &str) -> Result<Vec<String>, String> {
+    let Some(value) = info.get(key) else {
+        return Ok(Vec::new());
+    };
+    let items = value
+        .clone()
+        .into_array()
+        .map_err(|t| format!("{:?} should be an array, not {}", key, t))?;
+    items
+        .into_iter()
+        .map(|item| {
+            item.into_string()
+                .map_err(|t| format!("{:?} should hold strings, not {}", key, t))
+        })
+        .collect()
+}
+
+// true if any action, built in or scripted, is typed in as word
+fn name_taken(word: &str) -> bool {
+    let builtin = ACTION_REGISTRY.iter().map(|(_, a)| *a);
+    let scripted = scripted_actions()
+        .iter()
+        .map(|a| *a as &(dyn Action + Send + Sync));
+    builtin
+        .chain(scripted)
+        .any(|a| a.name() == word || a.aliases().contains(&word))
+}
+
+impl ScriptedAction {
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let ast = SCRIPT_ENGINE.compile(source).map_err(|e| e.to_string())?;
+        let has_fn = |name: &str, n_params: usize| {
+            ast.iter_functions()
+                .any(|f| f.name == name && f.params.len() == n_params)
+        };
+        if !has_fn("info", 0) {
+            return Err("the script has no info() function".to_string());
+        }
+        if !has_fn("effects", 1) {
+            return Err("the script has no effects(view) function".to_string());
+        }
+        let has_cost = has_fn("cost", 1);
+        let has_validate = has_fn("validate", 1);
+
+        let info: Map = call(&ast, "info", ())?
+            .try_cast()
+            .ok_or_else(|| "info() should return a map".to_string())?;
+        let name = string_field(&info, "name")?;
+        let targeting = string_field(&info, "targeting")?;
+        if targeting_schema(&targeting).is_none() {
+            return Err(format!("unknown targeting {:?}", targeting));
+        }
+        let aliases = string_list(&info, "aliases")?;
+        let creatures = string_list(&info, "creatures")?;
+        for creature in creatures.iter() {
+            creature_template_by_name(creature).map_err(|e| e.to_string())?;
+        }
+        let role = match info.get("role") {
+            Some(_) => {
+                let role = string_field(&info, "role")?;
+                parse_role(&role).ok_or_else(|| format!("unknown role {:?}", role))?
+            }
+            None => ActionRole::Other,
+        };
+        Ok(Self {
+            id: format!("script:{}", name),
+            name,
+            aliases,
+            targeting,
+            creatures,
+            role,
+            has_cost,
+            has_validate,
+            ast,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_for(&self, template_name: &str) -> bool {
+        self.creatures
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(template_name))
+    }
+
+    fn script_cost(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+    ) -> Result<Option<Resource>, String> {
+        if !self.has_cost {
+            return Ok(None);
+        }
+        let view = script_view(encounter, caster_id, target_ids, target_locations);
+        parse_cost(call(&self.ast, "cost", (view,))?)
+    }
+
+    fn script_effects(
+        &self,
+        encounter: &mut EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+    ) -> Result<Vec<Box<dyn ApplicableSideEffect>>, EngineError> {
+        let script_error = |message: String| EngineError::Script {
+            action: self.name.clone(),
+            message,
+        };
+        let view = script_view(encounter, caster_id, target_ids, target_locations);
+        let effects = call(&self.ast, "effects", (view,))
+            .map_err(script_error)?
+            .into_array()
+            .map_err(|t| script_error(format!("effects should be an array, not {}", t)))?;
+        let mut side_effects = Vec::new();
+        apply_effects(encounter, caster_id, effects, &mut side_effects).map_err(script_error)?;
+        Ok(side_effects)
+    }
+}
+
+fn call(ast: &AST, function: &str, args: impl FuncArgs) -> Result<Dynamic, String> {
+    // only the functions are run, never statements at the top of the script
+    let options = CallFnOptions::new().eval_ast(false);
+    SCRIPT_ENGINE
+        .call_fn_with_options(options, &mut Scope::new(), ast, function, args)
+        .map_err(|e| format!("{}(): {}", function, e))
+}
+
+impl Action for ScriptedAction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        self.aliases.iter().map(|a| a.as_str()).collect()
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        targeting_schema(&self.targeting).unwrap_or(TargetingSchema::Custom)
+    }
+
+    fn role(&self) -> ActionRole {
+        self.role
+    }
+
+    fn cost(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        // a failing cost script makes the action invalid, see below
+        self.script_cost(encounter, caster_id, target_ids, target_locations)
+            .ok()
+            .flatten()
+    }
+
+    fn custom_validate_input(
+        &self,
+        encounter: &EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> bool {
+        if self
+            .script_cost(encounter, caster_id, target_ids, target_locations)
+            .is_err()
+        {
+            return false;
+        }
+        if !self.has_validate {
+            return true;
+        }
+        let view = script_view(encounter, caster_id, target_ids, target_locations);
+        call(&self.ast, "validate", (view,))
+            .ok()
+            .and_then(|valid| valid.as_bool().ok())
+            .unwrap_or(false)
+    }
+
+    fn side_effects(
+        &self,
+        encounter: &mut EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        match self.script_effects(encounter, caster_id, target_ids, target_locations) {
+            Ok(side_effects) => side_effects,
+            Err(e) => {
+                encounter.log(EncounterEvent::Note {
+                    text: e.to_string(),
+                    actor_id: Some(caster_id),
+                    location: None,
+                });
+                Vec::new()
+            }
+        }
+    }
+
+    // as the default, but a failing script is reported rather than logged
+    fn execute(
+        &self,
+        encounter: &mut EncounterInstance,
+        caster_id: usize,
+        target_ids: Option<&Vec<usize>>,
+        target_locations: Option<&Vec<Coordinate>>,
+        overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Result<Vec<Box<dyn ApplicableSideEffect>>, EngineError> {
+        if !self.validate_input(
+            encounter,
+            caster_id,
+            target_ids,
+            target_locations,
+            overrides,
+        ) {
+            return Err(EngineError::IllegalAction {
+                action: self.name.clone(),
+                caster_id,
+            });
+        }
+        let mut side_effects =
+            self.script_effects(encounter, caster_id, target_ids, target_locations)?;
+        if let Some(cost) = self.cost(
+            encounter,
+            caster_id,
+            target_ids,
+            target_locations,
+            overrides,
+        ) {
+            side_effects.push(Box::new(ConsumeResource {
+                actor_id: caster_id,
+                resource: cost,
+            }));
+        }
+        Ok(side_effects)
+    }
+}
+
+// every loaded script, empty until load_scripts is called
+pub fn scripted_actions() -> &'static [&'static ScriptedAction] {
+    SCRIPTED_ACTIONS
+        .get()
+        .map_or(&[], |actions| actions.as_slice())
+}
+
+// the scripted actions creatures of the template get on top of its own
+pub fn scripted_actions_for(
+    template_name: &str,
+) -> impl Iterator<Item = &'static (dyn Action + Send + Sync)> {
+    scripted_actions()
+        .iter()
+        .filter(move |a| a.is_for(template_name))
+        .map(|&a| a as &'static (dyn Action + Send + Sync))
+}
+
+// Compiles every .rhai file in the directory, in name order. Scripts are
+// loaded once, before any encounter is set up; names and aliases may not
+// clash with other actions.
+pub fn load_scripts(dir: &str) -> Result<usize, Box<dyn Error>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+        .collect();
+    paths.sort();
+
+    let mut actions: Vec<&'static ScriptedAction> = Vec::new();
+    for path in paths {
+        let file = path.display().to_string();
+        let source = std::fs::read_to_string(&path)?;
+        let action = ScriptedAction::compile(&source).map_err(|e| ScriptError::new(&file, &e))?;
+        let words = std::iter::once(action.name()).chain(action.aliases());
+        for word in words {
+            let clashes = name_taken(word)
+                || actions
+                    .iter()
+                    .any(|a| a.name() == word || a.aliases().contains(&word));
+            if clashes {
+                let message = format!("{:?} is already the name of an action", word);
+                return Err(Box::new(ScriptError::new(&file, &message)));
+            }
+        }
+        // actions live as long as the program, like the built in statics
+        actions.push(Box::leak(Box::new(action)));
+    }
+    let n_actions = actions.len();
+    SCRIPTED_ACTIONS
+        .set(actions)
+        .map_err(|_| ScriptError::new(dir, "scripts have already been loaded"))?;
+    Ok(n_actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_util::{FACING_ZOMBIES, encounter_from};
+
+    // a free no_args action whose effects() runs body, next to any other
+    // functions it needs
+    fn script(functions: &str, body: &str) -> String {
+        format!(
+            "fn info() {{ #{{ name: \"test\", aliases: [], targeting: \"no_args\", creatures: [] }} }}\n\
+             {}\nfn effects(view) {{ {} }}\n",
+            functions, body
+        )
+    }
+
+    // the error running the script's effects gives, if any
+    fn effects_error(functions: &str, body: &str) -> Option<String> {
+        let action = ScriptedAction::compile(&script(functions, body)).unwrap();
+        let mut encounter = encounter_from(FACING_ZOMBIES, 0);
+        match action.execute(&mut encounter, 0, None, None, None) {
+            Ok(_) => None,
+            Err(EngineError::Script { message, .. }) => Some(message),
+            Err(e) => panic!("expected a script error, got {}", e),
+        }
+    }
+
+    #[test]
+    fn the_bundled_scripts_compile() {
+        for source in [
+            include_str!("../../scripts/shove.rhai"),
+            include_str!("../../scripts/rotting_bite.rhai"),
+        ] {
+            ScriptedAction::compile(source).unwrap();
+        }
+    }
+
+    #[test]
+    fn a_well_behaved_script_runs() {
+        assert_eq!(
+            effects_error("", "[#{ kind: \"note\", text: \"hi\" }]"),
+            None
+        );
+    }
+
+    #[test]
+    fn runaway_scripts_are_cut_off() {
+        let deeper = "fn deeper(n) { deeper(n + 1) }";
+        for (functions, body) in [
+            ("", "loop {}"),
+            ("", "let n = 0; while true { n += 1; } []"),
+            (deeper, "deeper(0)"),
+            ("", "let s = \"x\"; loop { s += s; }"),
+            ("", "let a = []; loop { a.push(0); }"),
+            (
+                "",
+                "let m = #{}; let n = 0; loop { m[`${n}`] = n; n += 1; }",
+            ),
+        ] {
+            let stopped = effects_error(functions, body).is_some();
+            assert!(stopped, "{} was not stopped", body);
+        }
+
+        // nor can info() hang loading the scripts
+        let source = "fn info() { loop {} }\nfn effects(view) { [] }\n";
+        assert!(ScriptedAction::compile(source).is_err());
+    }
+
+    #[test]
+    fn scripts_cannot_eval() {
+        assert!(ScriptedAction::compile(&script("", "eval(\"[]\")")).is_err());
+    }
+}
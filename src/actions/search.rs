@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::action_template::{Action, TargetingSchema},
+    engine::{
+        action_overrides::ActionOverride,
+        combat::{roll_skill_check, tile_gap},
+        encounter::EncounterInstance,
+        errors::EngineError,
+        events::EncounterEvent,
+        hazards::reveal_hazard,
+        side_effects::{ApplicableSideEffect, Resource, SavedSideEffect},
+        types::{Coordinate, Skill},
+    },
+};
+
+const SEARCH_RADIUS: f32 = 15.;
+
+// one check against every hidden hazard nearby
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RevealHazards {
+    pub actor_id: usize,
+    pub skill: Skill,
+}
+
+impl ApplicableSideEffect for RevealHazards {
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::RevealHazards(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        let total = roll_skill_check(ei, self.actor_id, self.skill.clone());
+        let mut found: Vec<Coordinate> = Vec::new();
+        for x in 0..ei.width {
+            for y in 0..ei.height {
+                let coord = Coordinate::new(x as isize, y as isize);
+                if let Some(dc) = ei
+                    .terrain_at(coord)
+                    .and_then(|ti| ti.hazard.as_ref())
+                    .and_then(|h| h.hidden_dc)
+                    && total >= dc
+                    && tile_gap(ei, self.actor_id, coord) <= SEARCH_RADIUS
+                {
+                    found.push(coord);
+                }
+            }
+        }
+        // tiles of one hazard are revealed together, so count each once
+        let mut n_found: usize = 0;
+        for coord in found {
+            if ei
+                .terrain_at(coord)
+                .and_then(|ti| ti.hazard.as_ref())
+                .is_some_and(|h| h.is_hidden())
+            {
+                n_found += 1;
+                reveal_hazard(ei, coord);
+            }
+        }
+        ei.log(EncounterEvent::Note {
+            text: if n_found > 0 {
+                format!("found {} hidden hazard(s)", n_found)
+            } else {
+                "found nothing".to_string()
+            },
+            actor_id: Some(self.actor_id),
+            location: None,
+        });
+        Ok(())
+    }
+}
+
+// looks for traps within SEARCH_RADIUS using the given skill
+pub struct Search {
+    name: &'static str,
+    aliases: Vec<&'static str>,
+    skill: Skill,
+}
+
+impl Action for Search {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        self.aliases.clone()
+    }
+
+    fn targeting_schema(&self) -> TargetingSchema {
+        TargetingSchema::NoArgs
+    }
+
+    fn cost(
+        &self,
+        _encounter: &EncounterInstance,
+        _caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Option<Resource> {
+        Some(Resource::Action)
+    }
+
+    fn side_effects(
+        &self,
+        _encounter: &mut EncounterInstance,
+        caster_id: usize,
+        _target_ids: Option<&Vec<usize>>,
+        _target_locations: Option<&Vec<Coordinate>>,
+        _overrides: Option<&HashSet<ActionOverride>>,
+    ) -> Vec<Box<dyn ApplicableSideEffect>> {
+        vec![Box::new(RevealHazards {
+            actor_id: caster_id,
+            skill: self.skill.clone(),
+        })]
+    }
+}
+
+pub static SEARCH: LazyLock<Search> = LazyLock::new(|| Search {
+    name: "search",
+    aliases: vec!["srch"],
+    skill: Skill::Perception,
+});
+
+pub static INVESTIGATE: LazyLock<Search> = LazyLock::new(|| Search {
+    name: "investigate",
+    aliases: vec!["inv"],
+    skill: Skill::Investigation,
+});
@@ -1,3 +1,7 @@
+use crate::actions::registry::action_refs;
+use crate::actions::scripted::scripted_actions_for;
+use crate::actors::creatures::template_ref;
+use crate::engine::errors::EngineError;
 use crate::engine::side_effects::Resource;
 use crate::engine::types::Coordinate;
 use crate::items::item_template::Item;
@@ -5,15 +9,14 @@ use crate::{
     actions::action_template::Action,
     engine::{
         types::{AbilityScoreType, Language, Size, Skill, SpecialSense},
-        util::modifier_from_score,
+        util::{get_tiles_from_size, modifier_from_score, squeezed_tiles_from_size},
     },
 };
 use std::collections::HashSet;
 
+use serde::{Deserialize, Serialize};
+use tyche::Expr;
 use tyche::dice::roller::Roller;
-use tyche::{Dice, Expr};
-
-use std::error::Error;
 
 pub struct CreatureTemplate {
     pub name: &'static str,
@@ -21,6 +24,9 @@ pub struct CreatureTemplate {
     pub ac: u32,
     pub hitpoints: Expr,
     pub speed: f32,
+    pub climb_speed: f32,
+    pub swim_speed: f32,
+    pub fly_speed: f32,
     pub strength: u32,
     pub intelligence: u32,
     pub dexterity: u32,
@@ -34,15 +40,17 @@ pub struct CreatureTemplate {
     pub cr: f32,
     pub size: Size,
     pub actions: Vec<&'static (dyn Action + Send + Sync)>,
+    // name of the AI profile computer controlled creatures of this kind use
+    pub ai_profile: &'static str,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct SpellSlotInfo {
     pub max_spell_slots: u32,
     pub spell_slots: u32,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct SpellSlotManager {
     ssi_by_lvl: Vec<SpellSlotInfo>,
     warlock_ssi: SpellSlotInfo,
@@ -141,14 +149,26 @@ impl SpellSlotManager {
     }
 }
 
-#[derive(Clone)]
+// TODO: some template fields are carried but not yet read (items, senses, etc)
+#[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ActorInstance {
     name: String,
+    #[serde(with = "template_ref")]
+    template: &'static CreatureTemplate,
     location: Coordinate,
     team_id: usize,
     base_ac: u32,
     base_hipoints: u32,
     base_speed: f32,
+    base_climb_speed: f32,
+    base_swim_speed: f32,
+    base_fly_speed: f32,
+    // feet above the ground beneath the actor, for flyers and climbers
+    altitude: f32,
+    prone: bool,
+    // in a space meant for a creature one size smaller
+    squeezing: bool,
     base_size: Size,
     initiative: Option<i32>,
     strength: u32,
@@ -168,8 +188,10 @@ pub struct ActorInstance {
     bonus_action_slots: u32,
     reaction_slots: u32,
     legendary_action_slots: u32,
+    object_interaction_slots: u32,
     size: Size,
     pub spell_slot_manager: SpellSlotManager,
+    #[serde(with = "action_refs")]
     pub actions: Vec<&'static (dyn Action + Send + Sync)>,
 }
 
@@ -180,22 +202,35 @@ impl ActorInstance {
         team_id: usize,
         roller: &mut impl Roller,
         instance_n: usize,
-    ) -> Result<ActorInstance, Box<dyn Error>> {
-        let hp_roll_result = ct.hitpoints.eval(roller)?;
-        let hp_roll_val = hp_roll_result.calc()? as u32;
+    ) -> Result<ActorInstance, EngineError> {
+        let hp_error = |e: &dyn std::fmt::Display| {
+            EngineError::ActorGeneration(format!(
+                "could not roll hit points for {}: {}",
+                ct.name, e
+            ))
+        };
+        let hp_roll_result = ct.hitpoints.eval(roller).map_err(|e| hp_error(&e))?;
+        let hp_roll_val = hp_roll_result.calc().map_err(|e| hp_error(&e))? as u32;
 
         let name: String = format!("{} {}", ct.name, instance_n);
 
         // variable stats should derive from below calls(such as max_hitpoints())
         // as they can be affected by item, effects, etc
         Result::Ok(ActorInstance {
-            name: name,
-            location: location,
-            team_id: team_id,
+            name,
+            template: ct,
+            location,
+            team_id,
             base_ac: ct.ac,
             base_hipoints: hp_roll_val,
             base_speed: ct.speed,
-            base_size: ct.size.clone(),
+            base_climb_speed: ct.climb_speed,
+            base_swim_speed: ct.swim_speed,
+            base_fly_speed: ct.fly_speed,
+            altitude: 0.,
+            prone: false,
+            squeezing: false,
+            base_size: ct.size,
             initiative: None,
             strength: ct.strength,
             intelligence: ct.intelligence,
@@ -214,7 +249,8 @@ impl ActorInstance {
             bonus_action_slots: 0,
             reaction_slots: 0,
             legendary_action_slots: 0,
-            size: ct.size.clone(), // TODO: should derive from function call
+            object_interaction_slots: 0,
+            size: ct.size, // TODO: should derive from function call
             spell_slot_manager: SpellSlotManager {
                 ssi_by_lvl: Vec::new(),
                 warlock_ssi: SpellSlotInfo {
@@ -223,7 +259,12 @@ impl ActorInstance {
                 },
                 warlock_spell_slot_lvl: 0,
             },
-            actions: ct.actions.clone(),
+            actions: ct
+                .actions
+                .iter()
+                .copied()
+                .chain(scripted_actions_for(ct.name))
+                .collect(),
         })
     }
 
@@ -231,6 +272,14 @@ impl ActorInstance {
         self.name.clone()
     }
 
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn template_name(&self) -> &'static str {
+        self.template.name
+    }
+
     pub fn team(&self) -> usize {
         self.team_id
     }
@@ -257,12 +306,16 @@ impl ActorInstance {
             Resource::BonusAction => self.bonus_action_slots >= 1,
             Resource::Reaction => self.reaction_slots >= 1,
             Resource::LegendaryAction => self.legendary_action_slots >= 1,
+            Resource::ObjectInteraction => self.object_interaction_slots >= 1,
         }
     }
 
-    pub fn consume_resource(&mut self, resource: Resource) {
+    pub fn consume_resource(&mut self, resource: Resource) -> Result<(), EngineError> {
         if !self.can_consume_resource(resource) {
-            panic!("illegal resource consumption")
+            return Err(EngineError::IllegalResourceConsumption {
+                actor: self.name(),
+                resource,
+            });
         }
         match resource {
             Resource::Movement(movement_amt) => {
@@ -283,7 +336,11 @@ impl ActorInstance {
             Resource::LegendaryAction => {
                 self.legendary_action_slots -= 1;
             }
+            Resource::ObjectInteraction => {
+                self.object_interaction_slots -= 1;
+            }
         }
+        Ok(())
     }
 
     pub fn give_resource(&mut self, resource: Resource) {
@@ -306,6 +363,9 @@ impl ActorInstance {
             Resource::LegendaryAction => {
                 self.legendary_action_slots += 1;
             }
+            Resource::ObjectInteraction => {
+                self.object_interaction_slots += 1;
+            }
         }
     }
 
@@ -325,9 +385,74 @@ impl ActorInstance {
     }
     // TODO: bonus hitpoints?
 
+    // at 0 hit points; out of the fight but still lying where it fell
+    pub fn is_down(&self) -> bool {
+        self.hitpoints == 0
+    }
+
+    pub fn take_damage(&mut self, amount: u32) {
+        // TODO: resistances, temporary hitpoints
+        self.hitpoints = self.hitpoints.saturating_sub(amount);
+    }
+
+    // never past max_hitpoints
+    pub fn heal(&mut self, amount: u32) {
+        self.hitpoints = (self.hitpoints + amount).min(self.max_hitpoints());
+    }
+
+    // a creature that is down can't move at all, whatever its speeds are
+    fn effective_speed(&self, base: f32) -> f32 {
+        if self.is_down() { 0. } else { base }
+    }
+
     pub fn speed(&self) -> f32 {
         // TODO: apply modifiers to ability scores (such as temporary buffs)
-        return self.base_speed;
+        self.effective_speed(self.base_speed)
+    }
+
+    pub fn climb_speed(&self) -> f32 {
+        self.effective_speed(self.base_climb_speed)
+    }
+
+    pub fn swim_speed(&self) -> f32 {
+        self.effective_speed(self.base_swim_speed)
+    }
+
+    pub fn fly_speed(&self) -> f32 {
+        self.effective_speed(self.base_fly_speed)
+    }
+
+    pub fn altitude(&self) -> f32 {
+        self.altitude
+    }
+
+    pub fn set_altitude(&mut self, altitude: f32) {
+        self.altitude = altitude.max(0.);
+    }
+
+    pub fn is_prone(&self) -> bool {
+        self.prone
+    }
+
+    pub fn set_prone(&mut self, prone: bool) {
+        self.prone = prone;
+    }
+
+    pub fn is_squeezing(&self) -> bool {
+        self.squeezing
+    }
+
+    pub fn set_squeezing(&mut self, squeezing: bool) {
+        self.squeezing = squeezing;
+    }
+
+    // tiles per side of the space the actor takes up where it stands
+    pub fn footprint_width(&self) -> usize {
+        if self.squeezing {
+            squeezed_tiles_from_size(self.size)
+        } else {
+            get_tiles_from_size(self.size)
+        }
     }
 
     pub fn remaining_movement(&self) -> f32 {
@@ -335,7 +460,7 @@ impl ActorInstance {
     }
 
     pub fn size(&self) -> Size {
-        self.size.clone()
+        self.size
     }
 
     pub fn set_location(&mut self, target: Coordinate) {
@@ -356,23 +481,18 @@ impl ActorInstance {
     }
 
     pub fn roll_initiative(&mut self, roller: &mut impl Roller) {
-        let dice = Dice::new(1, 6);
-        let rolled = roller
-            .roll(&dice, true)
-            .expect("somehow roll failed")
-            .total()
-            .expect("roll conversion failed");
-
+        let rolled = roller.roll_die(6).val;
         self.initiative = Some(rolled as i32 + self.initiative_mod());
     }
 
     pub fn reset_for_new_round(&mut self) {
-        self.movement = self.speed();
+        self.movement = self.speed().max(self.fly_speed());
 
         // TODO: pull from function
         self.action_slots = 1;
         self.bonus_action_slots = 1;
         self.reaction_slots = 1;
+        self.object_interaction_slots = 1;
         // TODO: legendary actions
     }
 
@@ -0,0 +1,34 @@
+use crate::actions::default_actions::DEFAULT_ACTIONS;
+use crate::actions::healing::CURE_WOUNDS;
+use crate::actions::monster_attacks::ACOLYTE_CLUB;
+use crate::actors::actor_template::CreatureTemplate;
+use crate::engine::types::{Language, Size};
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+pub static ACOLYTE_TEMPLATE: LazyLock<CreatureTemplate> = LazyLock::new(|| {
+    CreatureTemplate {
+        name: "Acolyte",
+        n_instances: 0,
+        ac: 10,
+        hitpoints: "2d8".parse().unwrap(),
+        speed: 30.,
+        climb_speed: 0.,
+        swim_speed: 0.,
+        fly_speed: 0.,
+        strength: 10,
+        intelligence: 10,
+        dexterity: 10,
+        wisdom: 14,
+        constitution: 10,
+        charisma: 11,
+        skills: HashSet::new(), // TODO: medicine, religion
+        items: Vec::new(),
+        senses: HashSet::new(),
+        languages: HashSet::from([Language::Common]),
+        cr: 0.25,
+        size: Size::Medium,
+        actions: [DEFAULT_ACTIONS.clone(), vec![&*ACOLYTE_CLUB, &*CURE_WOUNDS]].concat(),
+        ai_profile: "support",
+    }
+});
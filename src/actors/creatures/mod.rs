@@ -1 +1,45 @@
+pub mod acolytes;
+pub mod skeletons;
 pub mod zombies;
+
+use crate::actors::actor_template::CreatureTemplate;
+use crate::actors::creatures::acolytes::ACOLYTE_TEMPLATE;
+use crate::actors::creatures::skeletons::SKELETON_TEMPLATE;
+use crate::actors::creatures::zombies::ZOMBIE_TEMPLATE;
+use crate::engine::errors::EngineError;
+
+// every creature that can be generated or placed by name
+pub fn creature_templates() -> Vec<&'static CreatureTemplate> {
+    vec![&ZOMBIE_TEMPLATE, &SKELETON_TEMPLATE, &ACOLYTE_TEMPLATE]
+}
+
+pub fn creature_template_by_name(name: &str) -> Result<&'static CreatureTemplate, EngineError> {
+    creature_templates()
+        .into_iter()
+        .find(|ct| ct.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| EngineError::UnknownTemplate(name.to_string()))
+}
+
+// for fields holding a creature template, saved by its name, as
+// #[serde(with = "template_ref")]
+pub mod template_ref {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::creature_template_by_name;
+    use crate::actors::actor_template::CreatureTemplate;
+
+    pub fn serialize<S: Serializer>(
+        template: &&'static CreatureTemplate,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(template.name)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<&'static CreatureTemplate, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        creature_template_by_name(&name).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,37 @@
+use crate::actions::default_actions::DEFAULT_ACTIONS;
+use crate::actions::monster_attacks::{SKELETON_SHORTBOW, SKELETON_SHORTSWORD};
+use crate::actors::actor_template::CreatureTemplate;
+use crate::engine::types::{Language, Size, SpecialSense};
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+pub static SKELETON_TEMPLATE: LazyLock<CreatureTemplate> = LazyLock::new(|| {
+    CreatureTemplate {
+        name: "Skeleton",
+        n_instances: 0,
+        ac: 13,
+        hitpoints: "2d8+4".parse().unwrap(),
+        speed: 30.,
+        climb_speed: 0.,
+        swim_speed: 0.,
+        fly_speed: 0.,
+        strength: 10,
+        intelligence: 6,
+        dexterity: 14,
+        wisdom: 8,
+        constitution: 15,
+        charisma: 5,
+        skills: HashSet::new(),
+        items: Vec::new(), // TODO: shortsword, shortbow, armor scraps
+        senses: HashSet::from([SpecialSense::Darkvision(60)]),
+        languages: HashSet::from([Language::Common]), // understands but can't speak
+        cr: 0.25,
+        size: Size::Medium,
+        actions: [
+            DEFAULT_ACTIONS.clone(),
+            vec![&*SKELETON_SHORTSWORD, &*SKELETON_SHORTBOW],
+        ]
+        .concat(),
+        ai_profile: "artillery",
+    }
+});
@@ -1,4 +1,5 @@
 use crate::actions::default_actions::DEFAULT_ACTIONS;
+use crate::actions::monster_attacks::ZOMBIE_SLAM;
 use crate::actors::actor_template::CreatureTemplate;
 use crate::engine::types::{Language, Size, SpecialSense};
 use std::collections::HashSet;
@@ -6,11 +7,14 @@ use std::sync::LazyLock;
 
 pub static ZOMBIE_TEMPLATE: LazyLock<CreatureTemplate> = LazyLock::new(|| {
     CreatureTemplate {
-        name: &"Zombie",
+        name: "Zombie",
         n_instances: 0,
         ac: 8,
         hitpoints: "2d8+6".parse().unwrap(),
         speed: 20.,
+        climb_speed: 0.,
+        swim_speed: 0.,
+        fly_speed: 0.,
         strength: 13,
         intelligence: 3,
         dexterity: 6,
@@ -23,6 +27,7 @@ pub static ZOMBIE_TEMPLATE: LazyLock<CreatureTemplate> = LazyLock::new(|| {
         languages: HashSet::from([Language::Common]), // plus one other
         cr: 0.25,
         size: Size::Medium,
-        actions: DEFAULT_ACTIONS.clone(), // TODO
+        actions: [DEFAULT_ACTIONS.clone(), vec![&*ZOMBIE_SLAM]].concat(),
+        ai_profile: "brute",
     }
 });
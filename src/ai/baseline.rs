@@ -0,0 +1,267 @@
+use std::cmp::Ordering;
+
+use crate::actions::action_template::{Action, ActionExecutionInfo, ActionRole};
+use crate::engine::combat::{footprint_gap, position_gap, square_gap};
+use crate::engine::controller::{Controller, pass_action};
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::prompt::Prompt;
+use crate::engine::terrain::{DoorState, TerrainType};
+use crate::engine::types::Coordinate;
+use crate::engine::visibility::{Cover, cover_between};
+
+pub fn find_action(prompt: &Prompt, name: &str) -> Option<&'static (dyn Action + Send + Sync)> {
+    prompt.actions().iter().find(|a| a.name() == name).copied()
+}
+
+// creatures on other teams that are still standing
+pub fn enemies(encounter: &EncounterInstance, actor_id: usize) -> Vec<usize> {
+    let Some(actor) = encounter.actors.get(&actor_id) else {
+        return Vec::new();
+    };
+    encounter
+        .actors
+        .iter()
+        .filter(|(_, other)| other.team() != actor.team() && !other.is_down())
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+// enemies the actor can see, i.e. that are not behind total cover
+pub fn visible_enemies(encounter: &EncounterInstance, actor_id: usize) -> Vec<usize> {
+    enemies(encounter, actor_id)
+        .into_iter()
+        .filter(|&id| cover_between(encounter, actor_id, id) != Cover::Total)
+        .collect()
+}
+
+fn nearest(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    candidates: Vec<usize>,
+) -> Option<usize> {
+    candidates.into_iter().min_by(|&a, &b| {
+        footprint_gap(encounter, actor_id, a).total_cmp(&footprint_gap(encounter, actor_id, b))
+    })
+}
+
+// the nearest enemy in sight, or failing that the nearest one anywhere, which
+// the actor goes looking for
+pub fn choose_target(encounter: &EncounterInstance, actor_id: usize) -> Option<usize> {
+    nearest(encounter, actor_id, visible_enemies(encounter, actor_id))
+        .or_else(|| nearest(encounter, actor_id, enemies(encounter, actor_id)))
+}
+
+// an attack from the prompt's actions that can be made on the target right
+// now; ranged ones first when prefer_ranged is set, melee ones first otherwise
+pub fn attack_on(
+    prompt: &Prompt,
+    encounter: &EncounterInstance,
+    target_id: usize,
+    prefer_ranged: bool,
+) -> Option<ActionExecutionInfo> {
+    let mut attacks: Vec<_> = prompt
+        .actions()
+        .iter()
+        .filter_map(|&a| match a.role() {
+            ActionRole::Attack { ranged } => Some((ranged != prefer_ranged, a)),
+            _ => None,
+        })
+        .collect();
+    // stable, so actions keep the order the creature lists them in
+    attacks.sort_by_key(|&(not_preferred, _)| not_preferred);
+    attacks
+        .into_iter()
+        .map(|(_, a)| {
+            ActionExecutionInfo::new(a, prompt.actor_id(), Some(vec![target_id]), None, None)
+        })
+        .find(|aei| aei.validate(encounter))
+}
+
+// where the actor can end a move of at most budget feet, best first by the
+// given score and then by cost; ties go to the lowest coordinate so that
+// decisions are reproducible
+pub fn best_position<F: Fn(Coordinate, usize) -> f32>(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    budget: f32,
+    score: F,
+) -> Option<(Coordinate, f32)> {
+    let mut candidates: Vec<(Coordinate, f32, f32)> = encounter
+        .reachable(actor_id, budget)
+        .into_iter()
+        .filter_map(|(coord, cost)| {
+            let width = encounter.fit_width(actor_id, coord)?;
+            Some((coord, cost, score(coord, width)))
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        a.2.total_cmp(&b.2)
+            .then(a.1.total_cmp(&b.1))
+            .then(a.0.y.cmp(&b.0.y))
+            .then(a.0.x.cmp(&b.0.x))
+    });
+    candidates.first().map(|&(coord, _, score)| (coord, score))
+}
+
+// the furthest point within budget feet along the shortest route to dest
+pub fn walk_towards(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    dest: Coordinate,
+    budget: f32,
+) -> Option<Coordinate> {
+    let path = encounter.find_path(actor_id, dest)?;
+    let within_budget = encounter.reachable(actor_id, budget);
+    path.tiles
+        .into_iter()
+        .skip(1)
+        .rev()
+        .find(|coord| within_budget.contains_key(coord) && encounter.can_move_to(actor_id, *coord))
+}
+
+// where to head for within budget feet to get closer to the target, if
+// walking gets the actor any closer
+pub fn approach(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    target_id: usize,
+    budget: f32,
+) -> Option<Coordinate> {
+    let current = footprint_gap(encounter, actor_id, target_id);
+    let (dest, gap) = best_position(encounter, actor_id, f32::INFINITY, |coord, width| {
+        position_gap(encounter, actor_id, coord, width, target_id)
+    })?;
+    if gap.partial_cmp(&current) != Some(Ordering::Less) {
+        return None;
+    }
+    walk_towards(encounter, actor_id, dest, budget)
+}
+
+// the shut door the actor can get to that lies nearest the target, and
+// where to stand to open it; for when walking alone gets no closer
+pub fn door_towards(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    target_id: usize,
+) -> Option<(Coordinate, Coordinate)> {
+    let target = encounter.actors.get(&target_id)?;
+    let mut best: Option<(f32, f32, Coordinate, Coordinate)> = None;
+    let mut positions: Vec<(Coordinate, f32)> = encounter
+        .reachable(actor_id, f32::INFINITY)
+        .into_iter()
+        .collect();
+    positions.sort_by(|a, b| {
+        a.1.total_cmp(&b.1)
+            .then(a.0.y.cmp(&b.0.y))
+            .then(a.0.x.cmp(&b.0.x))
+    });
+    for (position, cost) in positions {
+        let Some(width) = encounter.fit_width(actor_id, position) else {
+            continue;
+        };
+        let width = width as isize;
+        for x_off in -1..=width {
+            for y_off in -1..=width {
+                let tile = position + Coordinate::new(x_off, y_off);
+                if !encounter.terrain_at(tile).is_some_and(|ti| {
+                    matches!(
+                        ti.terrain_type,
+                        TerrainType::Door(DoorState::Closed | DoorState::Locked)
+                    )
+                }) {
+                    continue;
+                }
+                let gap = square_gap(
+                    tile,
+                    1,
+                    target.location(),
+                    target.footprint_width() as isize,
+                );
+                if best.is_none_or(|(g, c, _, _)| (gap, cost) < (g, c)) {
+                    best = Some((gap, cost, tile, position));
+                }
+            }
+        }
+    }
+    best.map(|(_, _, door, position)| (door, position))
+}
+
+pub fn move_to(
+    prompt: &Prompt,
+    encounter: &EncounterInstance,
+    dest: Coordinate,
+) -> Option<ActionExecutionInfo> {
+    let action = find_action(prompt, "move")?;
+    let aei = ActionExecutionInfo::new(action, prompt.actor_id(), None, Some(vec![dest]), None);
+    aei.validate(encounter).then_some(aei)
+}
+
+// the next step towards the target when it is out of reach: walking, opening
+// doors on the way, and dashing when it is too far to reach this turn; passes
+// when none of them gets any closer
+pub fn close_in(
+    prompt: &Prompt,
+    encounter: &EncounterInstance,
+    target_id: usize,
+) -> Option<ActionExecutionInfo> {
+    let actor_id = prompt.actor_id();
+    let actor = encounter.actors.get(&actor_id)?;
+    if let Some(dest) = approach(encounter, actor_id, target_id, actor.remaining_movement())
+        && let Some(step) = move_to(prompt, encounter, dest)
+    {
+        return Some(step);
+    }
+    // walled in; make for the door that leads closest to the target
+    let mut can_get_closer = approach(encounter, actor_id, target_id, actor.speed()).is_some();
+    if !can_get_closer && let Some((door, position)) = door_towards(encounter, actor_id, target_id)
+    {
+        // locked doors get picked, or forced if it cannot pick them
+        for name in ["open", "picklock", "force"] {
+            if let Some(action) = find_action(prompt, name) {
+                let aei = ActionExecutionInfo::new(action, actor_id, None, Some(vec![door]), None);
+                if aei.validate(encounter) {
+                    return Some(aei);
+                }
+            }
+        }
+        if let Some(dest) = walk_towards(encounter, actor_id, position, actor.remaining_movement())
+            && let Some(step) = move_to(prompt, encounter, dest)
+        {
+            return Some(step);
+        }
+        can_get_closer = position != actor.location();
+    }
+    // only worth it if a full move would get any closer
+    if can_get_closer && let Some(dash) = find_action(prompt, "dash") {
+        let aei = ActionExecutionInfo::new(dash, actor_id, None, None, None);
+        if aei.validate(encounter) {
+            return Some(aei);
+        }
+    }
+    pass_action(prompt)
+}
+
+// closes in on the nearest enemy it can see and attacks it
+pub struct BaselineAi {}
+
+impl Controller for BaselineAi {
+    fn decide(
+        &mut self,
+        prompt: &Prompt,
+        encounter: &EncounterInstance,
+    ) -> Option<ActionExecutionInfo> {
+        let actor_id = prompt.actor_id();
+        let actor = encounter.actors.get(&actor_id)?;
+        if actor.is_down() {
+            return pass_action(prompt);
+        }
+        let Some(target_id) = choose_target(encounter, actor_id) else {
+            return pass_action(prompt);
+        };
+
+        if let Some(attack) = attack_on(prompt, encounter, target_id, false) {
+            return Some(attack);
+        }
+        close_in(prompt, encounter, target_id)
+    }
+}
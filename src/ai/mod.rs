@@ -0,0 +1,4 @@
+pub mod baseline;
+pub mod profiles;
+pub mod search;
+pub mod tactical;
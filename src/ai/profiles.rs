@@ -0,0 +1,335 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+use crate::actors::creatures::creature_template_by_name;
+use crate::engine::errors::ProfileParseError;
+
+// A profile file is split into sections by header lines:
+//
+//   [<profile>]  one "<key> = <value>" per line, e.g. "preferred_range = 30";
+//                a "base = <profile>" first line starts from that profile,
+//                otherwise a built in one of the same name or brute
+//   [templates]  one "<template> = <profile>" per line, overriding the
+//                profile a creature template picks for itself
+//
+// Fractions take either 0.25 or 25%. Blank lines and lines starting with '#'
+// are ignored.
+
+// how a computer controlled creature fights
+#[derive(Clone, Debug, PartialEq)]
+pub struct AiProfile {
+    // feet to keep between itself and its target; 0 closes to melee
+    pub preferred_range: f32,
+    // feet to fall back from enemies after attacking; 0 holds its ground
+    pub retreat_after_attack: f32,
+    // share of its hit points below which it runs for it
+    pub flee_below: f32,
+    // goes for the visible enemy with the fewest hit points, not the nearest
+    pub focus_weakest: bool,
+    // never walks out of an adjacent enemy's reach
+    pub avoid_opportunity_attacks: bool,
+    // prefers spots that fewer enemies can see
+    pub use_cover: bool,
+    // shoots rather than strikes when it has both to hand
+    pub prefer_ranged: bool,
+    // actions it heals with, used on allies below heal_below of their hit points
+    pub heal_actions: Vec<String>,
+    pub heal_below: f32,
+    // playouts of each choice before making it; 0 decides by the rules above
+    // straight away
+    pub lookahead_rollouts: usize,
+    // milliseconds the playouts may take at most when playing in the
+    // terminal, where a slow machine shouldn't stall the game; 0 for no cap.
+    // Never applied in simulations, which have to come out the same each run
+    pub lookahead_ms: u64,
+    // rounds each playout looks ahead
+    pub lookahead_rounds: usize,
+}
+
+impl Default for AiProfile {
+    fn default() -> Self {
+        Self::brute()
+    }
+}
+
+impl AiProfile {
+    // charges the nearest enemy
+    pub fn brute() -> Self {
+        Self {
+            preferred_range: 0.,
+            retreat_after_attack: 0.,
+            flee_below: 0.,
+            focus_weakest: false,
+            avoid_opportunity_attacks: false,
+            use_cover: false,
+            prefer_ranged: false,
+            heal_actions: Vec::new(),
+            heal_below: 0.,
+            lookahead_rollouts: 0,
+            lookahead_ms: 0,
+            lookahead_rounds: 2,
+        }
+    }
+
+    // darts in to attack and back out of reach
+    pub fn skirmisher() -> Self {
+        Self {
+            retreat_after_attack: 15.,
+            focus_weakest: true,
+            use_cover: true,
+            ..Self::brute()
+        }
+    }
+
+    // hangs back and attacks from range
+    pub fn artillery() -> Self {
+        Self {
+            preferred_range: 30.,
+            focus_weakest: true,
+            avoid_opportunity_attacks: true,
+            use_cover: true,
+            prefer_ranged: true,
+            ..Self::brute()
+        }
+    }
+
+    // keeps allies standing, fighting from a distance otherwise
+    pub fn support() -> Self {
+        Self {
+            preferred_range: 15.,
+            avoid_opportunity_attacks: true,
+            use_cover: true,
+            heal_actions: vec!["cure".to_string()],
+            heal_below: 0.5,
+            ..Self::brute()
+        }
+    }
+
+    // weighs up its options by playing them out; meant for a few big monsters
+    // as it is far slower than the rest
+    pub fn boss() -> Self {
+        Self {
+            focus_weakest: true,
+            lookahead_rollouts: 32,
+            ..Self::brute()
+        }
+    }
+
+    // fights until hurt, then runs
+    pub fn coward() -> Self {
+        Self {
+            flee_below: 0.25,
+            focus_weakest: true,
+            use_cover: true,
+            ..Self::brute()
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let number = |value: &str| -> Result<f32, String> {
+            value
+                .parse::<f32>()
+                .map_err(|_| format!("expected a number for {}, got {:?}", key, value))
+        };
+        let fraction = |value: &str| -> Result<f32, String> {
+            match value.strip_suffix('%') {
+                Some(percent) => Ok(number(percent.trim())? / 100.),
+                None => number(value),
+            }
+        };
+        let count = |value: &str| -> Result<usize, String> {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("expected a whole number for {}, got {:?}", key, value))
+        };
+        let flag = |value: &str| -> Result<bool, String> {
+            value
+                .parse::<bool>()
+                .map_err(|_| format!("expected true or false for {}, got {:?}", key, value))
+        };
+        match key {
+            "preferred_range" => self.preferred_range = number(value)?,
+            "retreat_after_attack" => self.retreat_after_attack = number(value)?,
+            "flee_below" => self.flee_below = fraction(value)?,
+            "focus_weakest" => self.focus_weakest = flag(value)?,
+            "avoid_opportunity_attacks" => self.avoid_opportunity_attacks = flag(value)?,
+            "use_cover" => self.use_cover = flag(value)?,
+            "prefer_ranged" => self.prefer_ranged = flag(value)?,
+            "heal_actions" => {
+                self.heal_actions = value
+                    .split(',')
+                    .map(|a| a.trim().to_string())
+                    .filter(|a| !a.is_empty())
+                    .collect()
+            }
+            "heal_below" => self.heal_below = fraction(value)?,
+            "lookahead_ms" => self.lookahead_ms = count(value)? as u64,
+            "lookahead_rollouts" => self.lookahead_rollouts = count(value)?,
+            "lookahead_rounds" => self.lookahead_rounds = count(value)?,
+            _ => return Err(format!("unknown key {:?}", key)),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct AiProfiles {
+    profiles: BTreeMap<String, AiProfile>,
+    // template name -> profile name, ahead of the template's own choice
+    templates: HashMap<String, String>,
+}
+
+impl Default for AiProfiles {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl AiProfiles {
+    pub fn builtin() -> Self {
+        let profiles: BTreeMap<String, AiProfile> = [
+            ("brute", AiProfile::brute()),
+            ("skirmisher", AiProfile::skirmisher()),
+            ("artillery", AiProfile::artillery()),
+            ("support", AiProfile::support()),
+            ("coward", AiProfile::coward()),
+            ("boss", AiProfile::boss()),
+        ]
+        .into_iter()
+        .map(|(name, profile)| (name.to_string(), profile))
+        .collect();
+        Self {
+            profiles,
+            templates: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AiProfile> {
+        self.profiles.get(&name.to_ascii_lowercase())
+    }
+
+    // the profile creatures of a template fight with; brute when neither the
+    // file nor the template names one that exists
+    pub fn for_template(&self, template_name: &str) -> AiProfile {
+        let name = self
+            .templates
+            .get(&template_name.to_ascii_lowercase())
+            .map(|s| s.as_str())
+            .or_else(|| {
+                creature_template_by_name(template_name)
+                    .ok()
+                    .map(|ct| ct.ai_profile)
+            });
+        name.and_then(|n| self.get(n)).cloned().unwrap_or_default()
+    }
+}
+
+enum Section {
+    Preamble,
+    Profile(String),
+    Templates,
+}
+
+// profiles from a file, on top of the built in ones
+pub fn parse_profiles(text: &str) -> Result<AiProfiles, ProfileParseError> {
+    let mut profiles = AiProfiles::builtin();
+    let mut section = Section::Preamble;
+    // whether the current profile section has had a key yet, since base has
+    // to come first
+    let mut section_started = false;
+    let mut template_lines: Vec<(usize, String, String)> = Vec::new();
+
+    for (i, raw_line) in text.lines().enumerate() {
+        let line_n = i + 1;
+        let trimmed = raw_line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            let name = name.trim().to_ascii_lowercase();
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(ProfileParseError::new(
+                    line_n,
+                    &format!("invalid section name {:?}", name),
+                ));
+            }
+            section = if name == "templates" {
+                Section::Templates
+            } else {
+                let profile = profiles.get(&name).cloned().unwrap_or_default();
+                profiles.profiles.insert(name.clone(), profile);
+                Section::Profile(name)
+            };
+            section_started = false;
+            continue;
+        }
+
+        let Some((key, value)) = trimmed.split_once('=') else {
+            return Err(ProfileParseError::new(
+                line_n,
+                &format!("expected <key> = <value>, got {:?}", trimmed),
+            ));
+        };
+        let (key, value) = (key.trim(), value.trim());
+        match &section {
+            Section::Preamble => {
+                return Err(ProfileParseError::new(
+                    line_n,
+                    "expected a [<profile>] or [templates] section",
+                ));
+            }
+            Section::Profile(name) => {
+                if key == "base" {
+                    if section_started {
+                        return Err(ProfileParseError::new(
+                            line_n,
+                            "base has to be the first key of a profile",
+                        ));
+                    }
+                    let Some(base) = profiles.get(value).cloned() else {
+                        return Err(ProfileParseError::new(
+                            line_n,
+                            &format!("unknown base profile {:?}", value),
+                        ));
+                    };
+                    profiles.profiles.insert(name.clone(), base);
+                } else {
+                    let profile = profiles
+                        .profiles
+                        .get_mut(name)
+                        .expect("inserted with its section header");
+                    profile
+                        .set(key, value)
+                        .map_err(|message| ProfileParseError::new(line_n, &message))?;
+                }
+                section_started = true;
+            }
+            Section::Templates => {
+                if let Err(e) = creature_template_by_name(key) {
+                    return Err(ProfileParseError::new(line_n, &e.to_string()));
+                }
+                template_lines.push((line_n, key.to_ascii_lowercase(), value.to_ascii_lowercase()));
+            }
+        }
+    }
+
+    // profiles can be defined after the templates that use them
+    for (line_n, template, profile) in template_lines {
+        if profiles.get(&profile).is_none() {
+            return Err(ProfileParseError::new(
+                line_n,
+                &format!("unknown profile {:?}", profile),
+            ));
+        }
+        profiles.templates.insert(template, profile);
+    }
+    Ok(profiles)
+}
+
+pub fn load_profiles(path: &str) -> Result<AiProfiles, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(parse_profiles(&text)?)
+}
@@ -0,0 +1,264 @@
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::actions::action_template::ActionExecutionInfo;
+use crate::ai::baseline::{approach, attack_on, best_position, enemies, find_action, move_to};
+use crate::ai::profiles::{AiProfile, AiProfiles};
+use crate::ai::tactical::ProfiledAi;
+use crate::engine::combat::{footprint_gap, position_gap};
+use crate::engine::controller::{Controller, TeamControllers, pass_action};
+use crate::engine::dice_roller::DiceRoller;
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::prompt::Prompt;
+
+// Monte Carlo lookahead: every candidate action is played out a number of
+// times on a copy of the encounter with fresh dice each time, and the one
+// whose playouts leave the actor's side best off is taken. Playouts run
+// everyone on a light policy, as the full profiles cost too much to play
+// hundreds of turns per decision.
+
+fn teams_standing(encounter: &EncounterInstance) -> BTreeSet<usize> {
+    encounter
+        .actors
+        .values()
+        .filter(|a| !a.is_down())
+        .map(|a| a.team())
+        .collect()
+}
+
+// what everyone does in a playout: hit the nearest enemy, or get as close
+// to it as this turn's movement allows
+struct PlayoutPolicy {}
+
+impl Controller for PlayoutPolicy {
+    fn decide(
+        &mut self,
+        prompt: &Prompt,
+        encounter: &EncounterInstance,
+    ) -> Option<ActionExecutionInfo> {
+        let actor_id = prompt.actor_id();
+        let actor = encounter.actors.get(&actor_id)?;
+        if actor.is_down() {
+            return pass_action(prompt);
+        }
+        let Some(target_id) = enemies(encounter, actor_id).into_iter().min_by(|&a, &b| {
+            footprint_gap(encounter, actor_id, a).total_cmp(&footprint_gap(encounter, actor_id, b))
+        }) else {
+            return pass_action(prompt);
+        };
+        if let Some(attack) = attack_on(prompt, encounter, target_id, false) {
+            return Some(attack);
+        }
+        let current = footprint_gap(encounter, actor_id, target_id);
+        if let Some((dest, gap)) = best_position(
+            encounter,
+            actor_id,
+            actor.remaining_movement(),
+            |coord, width| position_gap(encounter, actor_id, coord, width, target_id),
+        ) && gap < current
+            && let Some(step) = move_to(prompt, encounter, dest)
+        {
+            return Some(step);
+        }
+        pass_action(prompt)
+    }
+}
+
+// the team's share of hit points left against everyone else's, counted per
+// creature so that dropping a foe counts for as much as a whole one
+fn evaluate(encounter: &EncounterInstance, team_id: usize) -> f32 {
+    encounter
+        .actors
+        .values()
+        .map(|actor| {
+            let fraction = actor.hitpoints() as f32 / actor.max_hitpoints().max(1) as f32;
+            match actor.team() == team_id {
+                true => fraction,
+                false => -fraction,
+            }
+        })
+        .sum()
+}
+
+// what is worth trying: whatever the profile would do, a melee and a ranged
+// attack on each enemy in reach, a step towards each enemy, backing off,
+// dashing and passing
+fn candidates(
+    prompt: &Prompt,
+    encounter: &EncounterInstance,
+    profiles: &AiProfiles,
+) -> Vec<ActionExecutionInfo> {
+    let actor_id = prompt.actor_id();
+    let mut candidates: Vec<ActionExecutionInfo> = Vec::new();
+    let mut policy = ProfiledAi::without_lookahead(profiles.clone());
+    candidates.extend(policy.decide(prompt, encounter));
+
+    let foes = enemies(encounter, actor_id);
+    for &enemy_id in foes.iter() {
+        candidates.extend(attack_on(prompt, encounter, enemy_id, false));
+        candidates.extend(attack_on(prompt, encounter, enemy_id, true));
+    }
+    if let Some(actor) = encounter.actors.get(&actor_id) {
+        let budget = actor.remaining_movement();
+        for &enemy_id in foes.iter() {
+            if let Some(dest) = approach(encounter, actor_id, enemy_id, budget) {
+                candidates.extend(move_to(prompt, encounter, dest));
+            }
+        }
+        let away = best_position(encounter, actor_id, budget, |coord, width| {
+            -foes
+                .iter()
+                .map(|&id| position_gap(encounter, actor_id, coord, width, id))
+                .fold(f32::INFINITY, f32::min)
+        });
+        if let Some((dest, _)) = away
+            && dest != actor.location()
+        {
+            candidates.extend(move_to(prompt, encounter, dest));
+        }
+    }
+    if let Some(dash) = find_action(prompt, "dash") {
+        let aei = ActionExecutionInfo::new(dash, actor_id, None, None, None);
+        if aei.validate(encounter) {
+            candidates.push(aei);
+        }
+    }
+    candidates.extend(pass_action(prompt));
+
+    let mut unique: Vec<ActionExecutionInfo> = Vec::new();
+    for candidate in candidates {
+        if !unique.contains(&candidate) {
+            unique.push(candidate);
+        }
+    }
+    unique
+}
+
+// plays the candidate out from a copy of the encounter and scores the result
+// for the actor's team
+fn rollout(
+    sim: &mut EncounterInstance,
+    candidate: &ActionExecutionInfo,
+    team_id: usize,
+    rounds: usize,
+    seed: u64,
+) -> f32 {
+    // the real dice are not for peeking at
+    sim.roller = DiceRoller::with_seed(seed);
+    sim.pop_prompt();
+    sim.push_action(candidate.clone());
+
+    let mut controllers = TeamControllers::new();
+    let teams: BTreeSet<usize> = sim.actors.values().map(|a| a.team()).collect();
+    for team in teams {
+        controllers.assign(team, Box::new(PlayoutPolicy {}));
+    }
+    let last_round = sim.round() + rounds;
+    // a playout the engine gives up on counts as the worst there is
+    while sim.round() < last_round && teams_standing(sim).len() > 1 {
+        if sim.process_stack().is_err() {
+            return f32::NEG_INFINITY;
+        }
+        if !controllers.answer_prompt(sim) {
+            break;
+        }
+    }
+    // whatever was decided last still has to happen
+    if sim.process_stack().is_err() {
+        return f32::NEG_INFINITY;
+    }
+    evaluate(sim, team_id)
+}
+
+// the candidate whose playouts came out best on average, after the
+// profile's number of playouts each; with time_limits the profile's
+// lookahead_ms can stop it sooner, but every candidate gets played out at
+// least once
+pub fn lookahead(
+    prompt: &Prompt,
+    encounter: &EncounterInstance,
+    profiles: &AiProfiles,
+    profile: &AiProfile,
+    rng: &mut StdRng,
+    time_limits: bool,
+) -> Option<ActionExecutionInfo> {
+    let deadline = (time_limits && profile.lookahead_ms > 0)
+        .then(|| Instant::now() + Duration::from_millis(profile.lookahead_ms));
+    let team_id = encounter.actors.get(&prompt.actor_id())?.team();
+    let candidates = candidates(prompt, encounter, profiles);
+    if candidates.len() <= 1 {
+        return candidates.into_iter().next();
+    }
+
+    let mut sim = encounter.clone_without_log();
+    let start = sim.snapshot();
+    let mut totals: Vec<(f32, usize)> = vec![(0., 0); candidates.len()];
+    'playouts: loop {
+        let mut played = false;
+        for (i, candidate) in candidates.iter().enumerate() {
+            let (_, n) = totals[i];
+            if n >= profile.lookahead_rollouts.max(1) {
+                continue;
+            }
+            if n > 0 && deadline.is_some_and(|d| Instant::now() >= d) {
+                break 'playouts;
+            }
+            sim.restore(&start);
+            let score = rollout(
+                &mut sim,
+                candidate,
+                team_id,
+                profile.lookahead_rounds.max(1),
+                rng.random(),
+            );
+            totals[i].0 += score;
+            totals[i].1 += 1;
+            played = true;
+        }
+        if !played {
+            break;
+        }
+    }
+
+    // ties go to the earlier candidate, which puts the profile's own choice first
+    let mut best: Option<(f32, usize)> = None;
+    for (i, &(total, n)) in totals.iter().enumerate() {
+        let mean = total / n.max(1) as f32;
+        if best.is_none_or(|(best_mean, _)| mean > best_mean) {
+            best = Some((mean, i));
+        }
+    }
+    best.map(|(_, i)| candidates[i].clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::profiles::parse_profiles;
+    use crate::engine::test_util::encounter_from;
+    use rand::SeedableRng;
+
+    const MAP: &str = "[map]\n............\n............\n[creatures]\nZombie 0,0 0\nZombie 2,0 1\nZombie 8,0 1\n";
+
+    #[test]
+    fn lookahead_decides_the_same_every_time() {
+        let profiles = parse_profiles("[templates]\nZombie = boss\n").unwrap();
+        let mut encounter = encounter_from(MAP, 0);
+        encounter.process_stack().unwrap();
+        let prompt = encounter.peek_prompt().unwrap().clone();
+        let profile = profiles.for_template("Zombie");
+        assert!(candidates(&prompt, &encounter, &profiles).len() > 1);
+
+        let decide = || {
+            let mut rng = StdRng::seed_from_u64(7);
+            lookahead(&prompt, &encounter, &profiles, &profile, &mut rng, false).unwrap()
+        };
+        let first = decide();
+        for _ in 0..3 {
+            assert!(decide() == first);
+        }
+    }
+}
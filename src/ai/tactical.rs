@@ -0,0 +1,449 @@
+use std::collections::HashSet;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::actions::action_template::ActionExecutionInfo;
+use crate::ai::baseline::{
+    attack_on, best_position, choose_target, close_in, enemies, find_action, move_to,
+    visible_enemies,
+};
+use crate::ai::profiles::{AiProfile, AiProfiles};
+use crate::ai::search::lookahead;
+use crate::engine::combat::{footprint_gap, position_gap};
+use crate::engine::controller::{Controller, pass_action};
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::prompt::Prompt;
+use crate::engine::types::Coordinate;
+use crate::engine::visibility::has_line_of_sight;
+
+// enemies closer than this get a swing at anyone walking away from them
+const OPPORTUNITY_REACH: f32 = 5.;
+// how many feet of extra distance a spot in view of one more enemy is worth
+const EXPOSURE_PENALTY: f32 = 5.;
+// for spots the target cannot be seen from
+const NO_SIGHT_PENALTY: f32 = 1000.;
+
+fn hp_fraction(encounter: &EncounterInstance, actor_id: usize) -> f32 {
+    match encounter.actors.get(&actor_id) {
+        Some(actor) => actor.hitpoints() as f32 / actor.max_hitpoints().max(1) as f32,
+        None => 0.,
+    }
+}
+
+// enemies the actor is within reach of, who would get an opportunity attack
+// if it walked off
+fn adjacent_enemies(encounter: &EncounterInstance, actor_id: usize) -> Vec<usize> {
+    enemies(encounter, actor_id)
+        .into_iter()
+        .filter(|&id| footprint_gap(encounter, actor_id, id) < OPPORTUNITY_REACH)
+        .collect()
+}
+
+// whether ending a move at coord leaves an adjacent enemy behind; only the end
+// of the move is checked, so a route that ducks out of reach and back in slips by
+fn provokes(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    coord: Coordinate,
+    width: usize,
+) -> bool {
+    adjacent_enemies(encounter, actor_id)
+        .into_iter()
+        .any(|id| position_gap(encounter, actor_id, coord, width, id) >= OPPORTUNITY_REACH)
+}
+
+// how many enemies could see the actor standing at coord
+fn exposure(encounter: &EncounterInstance, actor_id: usize, coord: Coordinate) -> usize {
+    enemies(encounter, actor_id)
+        .into_iter()
+        .filter_map(|id| encounter.actors.get(&id))
+        .filter(|enemy| has_line_of_sight(encounter, enemy.location(), coord))
+        .count()
+}
+
+// the gap at coord to the nearest enemy
+fn nearest_enemy_gap(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    coord: Coordinate,
+    width: usize,
+) -> f32 {
+    enemies(encounter, actor_id)
+        .into_iter()
+        .map(|id| position_gap(encounter, actor_id, coord, width, id))
+        .fold(f32::INFINITY, f32::min)
+}
+
+// whether the actor can get further from every enemy than they could follow
+// next turn, dashing included
+fn can_outrun(encounter: &EncounterInstance, actor_id: usize) -> bool {
+    let Some(actor) = encounter.actors.get(&actor_id) else {
+        return false;
+    };
+    let dash = if actor.action_slots() > 0 {
+        actor.speed()
+    } else {
+        0.
+    };
+    let pursuit = enemies(encounter, actor_id)
+        .into_iter()
+        .filter_map(|id| encounter.actors.get(&id))
+        .map(|enemy| enemy.speed() * 2.)
+        .fold(0., f32::max);
+    encounter
+        .reachable(actor_id, actor.remaining_movement() + dash)
+        .into_keys()
+        .filter_map(|coord| {
+            let width = encounter.fit_width(actor_id, coord)?;
+            Some(nearest_enemy_gap(encounter, actor_id, coord, width))
+        })
+        .any(|gap| gap > pursuit)
+}
+
+// moves within budget feet to the spot scoring lowest, if it beats where the
+// actor stands now
+fn reposition<F: Fn(Coordinate, usize) -> f32>(
+    prompt: &Prompt,
+    encounter: &EncounterInstance,
+    budget: f32,
+    score: F,
+) -> Option<ActionExecutionInfo> {
+    let actor = encounter.actors.get(&prompt.actor_id())?;
+    let current = score(actor.location(), actor.footprint_width());
+    let (dest, best) = best_position(encounter, prompt.actor_id(), budget, &score)?;
+    if best >= current || dest == actor.location() {
+        return None;
+    }
+    move_to(prompt, encounter, dest)
+}
+
+// the spot furthest from every enemy, out of view where the profile cares
+fn away_from_enemies(
+    profile: &AiProfile,
+    encounter: &EncounterInstance,
+    actor_id: usize,
+) -> impl Fn(Coordinate, usize) -> f32 {
+    move |coord, width| {
+        let gap = nearest_enemy_gap(encounter, actor_id, coord, width);
+        // distance stops mattering once nothing could catch up in one turn
+        let mut score = -gap.min(120.);
+        if profile.use_cover {
+            score += exposure(encounter, actor_id, coord) as f32 * EXPOSURE_PENALTY;
+        }
+        score
+    }
+}
+
+// fights according to the profile of each creature's template
+pub struct ProfiledAi {
+    pub profiles: AiProfiles,
+    // actors that have spent this turn's action on an attack
+    attacked: HashSet<usize>,
+    // whether profiles that ask for lookahead get it; off inside the playouts
+    // themselves
+    lookahead: bool,
+    // whether lookahead_ms cuts the playouts short; only for play in the
+    // terminal, as it makes decisions depend on how fast the machine is
+    time_limits: bool,
+    // dice for the playouts
+    rng: StdRng,
+}
+
+impl ProfiledAi {
+    pub fn new(profiles: AiProfiles) -> Self {
+        Self {
+            profiles,
+            attacked: HashSet::new(),
+            lookahead: true,
+            time_limits: false,
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    pub fn with_time_limits(self) -> Self {
+        Self {
+            time_limits: true,
+            ..self
+        }
+    }
+
+    pub fn without_lookahead(profiles: AiProfiles) -> Self {
+        Self {
+            lookahead: false,
+            ..Self::new(profiles)
+        }
+    }
+
+    // runs out of reach, dashing when walking gets it no further
+    fn flee(
+        &self,
+        prompt: &Prompt,
+        encounter: &EncounterInstance,
+        profile: &AiProfile,
+    ) -> Option<ActionExecutionInfo> {
+        let actor_id = prompt.actor_id();
+        let actor = encounter.actors.get(&actor_id)?;
+        let score = away_from_enemies(profile, encounter, actor_id);
+        if let Some(step) = reposition(prompt, encounter, actor.remaining_movement(), &score) {
+            return Some(step);
+        }
+        if let Some(dash) = find_action(prompt, "dash") {
+            let aei = ActionExecutionInfo::new(dash, actor_id, None, None, None);
+            if aei.validate(encounter) {
+                return Some(aei);
+            }
+        }
+        pass_action(prompt)
+    }
+
+    // heals the ally worst off, walking over to them if none is in reach
+    fn heal(
+        &self,
+        prompt: &Prompt,
+        encounter: &EncounterInstance,
+        profile: &AiProfile,
+    ) -> Option<ActionExecutionInfo> {
+        let actor_id = prompt.actor_id();
+        let actor = encounter.actors.get(&actor_id)?;
+        let actions: Vec<_> = profile
+            .heal_actions
+            .iter()
+            .filter_map(|name| find_action(prompt, name))
+            .collect();
+        if actions.is_empty() {
+            return None;
+        }
+        let mut wounded: Vec<(usize, f32)> = encounter
+            .actors
+            .iter()
+            .filter(|(_, ally)| ally.team() == actor.team() && !ally.is_down())
+            .map(|(&id, _)| (id, hp_fraction(encounter, id)))
+            .filter(|&(_, fraction)| fraction < profile.heal_below)
+            .collect();
+        wounded.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        for &(ally_id, _) in wounded.iter() {
+            for &action in actions.iter() {
+                let aei =
+                    ActionExecutionInfo::new(action, actor_id, Some(vec![ally_id]), None, None);
+                if aei.validate(encounter) {
+                    return Some(aei);
+                }
+            }
+        }
+        // out of reach; only worth walking over with an action left to heal with
+        if actor.action_slots() == 0 {
+            return None;
+        }
+        let &(ally_id, _) = wounded.iter().find(|&&(id, _)| id != actor_id)?;
+        reposition(
+            prompt,
+            encounter,
+            actor.remaining_movement(),
+            |coord, width| position_gap(encounter, actor_id, coord, width, ally_id),
+        )
+    }
+
+    // who to go after: the weakest or nearest enemy in sight, and only those
+    // already in reach when walking off would draw an opportunity attack
+    fn target(
+        &self,
+        encounter: &EncounterInstance,
+        actor_id: usize,
+        profile: &AiProfile,
+    ) -> Option<usize> {
+        let adjacent = adjacent_enemies(encounter, actor_id);
+        let candidates = if profile.avoid_opportunity_attacks && !adjacent.is_empty() {
+            adjacent
+        } else {
+            visible_enemies(encounter, actor_id)
+        };
+        let key = |id: usize| {
+            let hp = match profile.focus_weakest {
+                true => encounter.actors.get(&id).map_or(0, |a| a.hitpoints()),
+                false => 0,
+            };
+            (hp, footprint_gap(encounter, actor_id, id))
+        };
+        candidates
+            .into_iter()
+            .min_by(|&a, &b| {
+                let (a_hp, a_gap) = key(a);
+                let (b_hp, b_gap) = key(b);
+                a_hp.cmp(&b_hp)
+                    .then(a_gap.total_cmp(&b_gap))
+                    .then(a.cmp(&b))
+            })
+            .or_else(|| choose_target(encounter, actor_id))
+    }
+
+    // keeps preferred_range from the target with it in sight
+    fn keep_range(
+        &self,
+        prompt: &Prompt,
+        encounter: &EncounterInstance,
+        profile: &AiProfile,
+        target_id: usize,
+    ) -> Option<ActionExecutionInfo> {
+        let actor_id = prompt.actor_id();
+        let actor = encounter.actors.get(&actor_id)?;
+        let target_location = encounter.actors.get(&target_id)?.location();
+        let score = |coord: Coordinate, width: usize| {
+            let gap = position_gap(encounter, actor_id, coord, width, target_id);
+            let mut score = (gap - profile.preferred_range).abs();
+            if !has_line_of_sight(encounter, coord, target_location) {
+                score += NO_SIGHT_PENALTY;
+            }
+            if profile.use_cover {
+                score += exposure(encounter, actor_id, coord) as f32 * EXPOSURE_PENALTY;
+            }
+            if profile.avoid_opportunity_attacks && provokes(encounter, actor_id, coord, width) {
+                score = f32::INFINITY;
+            }
+            score
+        };
+        if let Some(step) = reposition(prompt, encounter, actor.remaining_movement(), score) {
+            return Some(step);
+        }
+        // holding here is only worth it with an attack that reaches from here,
+        // and it has just found none; otherwise go the long way round
+        if actor.action_slots() > 0
+            || footprint_gap(encounter, actor_id, target_id) > profile.preferred_range
+            || !has_line_of_sight(encounter, actor.location(), target_location)
+        {
+            return close_in(prompt, encounter, target_id);
+        }
+        pass_action(prompt)
+    }
+}
+
+impl Controller for ProfiledAi {
+    fn decide(
+        &mut self,
+        prompt: &Prompt,
+        encounter: &EncounterInstance,
+    ) -> Option<ActionExecutionInfo> {
+        let actor_id = prompt.actor_id();
+        let actor = encounter.actors.get(&actor_id)?;
+        if actor.is_down() {
+            return pass_action(prompt);
+        }
+        let profile = self.profiles.for_template(actor.template_name());
+        // an action to spend means a new turn
+        if actor.action_slots() > 0 {
+            self.attacked.remove(&actor_id);
+        }
+        if self.lookahead && profile.lookahead_rollouts > 0 {
+            return lookahead(
+                prompt,
+                encounter,
+                &self.profiles,
+                &profile,
+                &mut self.rng,
+                self.time_limits,
+            );
+        }
+
+        if hp_fraction(encounter, actor_id) < profile.flee_below
+            && !enemies(encounter, actor_id).is_empty()
+        {
+            // cornered, it turns on whoever is in reach
+            if !can_outrun(encounter, actor_id)
+                && let Some(attack) = adjacent_enemies(encounter, actor_id)
+                    .into_iter()
+                    .find_map(|id| attack_on(prompt, encounter, id, profile.prefer_ranged))
+            {
+                return Some(attack);
+            }
+            return self.flee(prompt, encounter, &profile);
+        }
+        if let Some(heal) = self.heal(prompt, encounter, &profile) {
+            return Some(heal);
+        }
+        let Some(target_id) = self.target(encounter, actor_id, &profile) else {
+            return pass_action(prompt);
+        };
+
+        if let Some(attack) = attack_on(prompt, encounter, target_id, profile.prefer_ranged) {
+            self.attacked.insert(actor_id);
+            return Some(attack);
+        }
+        if self.attacked.contains(&actor_id) && profile.retreat_after_attack > 0. {
+            let budget = actor.remaining_movement().min(profile.retreat_after_attack);
+            let score = away_from_enemies(&profile, encounter, actor_id);
+            return reposition(prompt, encounter, budget, score).or_else(|| pass_action(prompt));
+        }
+        if profile.preferred_range > 0. {
+            return self.keep_range(prompt, encounter, &profile, target_id);
+        }
+        // in reach of someone it cannot hit; walking off would only draw an attack
+        if profile.avoid_opportunity_attacks && !adjacent_enemies(encounter, actor_id).is_empty() {
+            return pass_action(prompt);
+        }
+        close_in(prompt, encounter, target_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_util::encounter_from;
+
+    // an acolyte beside a zombie on its side, with a skeleton further off
+    // that way and another zombie of theirs the other way
+    const MAP: &str = "[map]\n....................\n....................\n[creatures]\nAcolyte 8,0 0\nZombie 10,0 0\nSkeleton 16,0 1\nZombie 0,0 0\n";
+
+    fn encounter() -> EncounterInstance {
+        let mut encounter = encounter_from(MAP, 0);
+        for actor in encounter.actors.values_mut() {
+            actor.reset_for_new_round();
+        }
+        encounter
+    }
+
+    fn decide(encounter: &EncounterInstance, actor_id: usize) -> ActionExecutionInfo {
+        let prompt = Prompt::new(actor_id, encounter.actors[&actor_id].actions.clone());
+        ProfiledAi::new(AiProfiles::builtin())
+            .decide(&prompt, encounter)
+            .unwrap()
+    }
+
+    #[test]
+    fn support_heals_a_wounded_ally() {
+        let mut encounter = encounter();
+        let zombie = encounter.actors.get_mut(&1).unwrap();
+        zombie.take_damage(zombie.hitpoints() - 1);
+
+        let choice = decide(&encounter, 0);
+        assert_eq!(choice.action_name(), "cure");
+        assert_eq!(choice.target_ids(), &[1]);
+
+        // one that is down is past curing, so it goes to the one further off
+        let zombie = encounter.actors.get_mut(&1).unwrap();
+        zombie.take_damage(zombie.hitpoints());
+        let zombie = encounter.actors.get_mut(&3).unwrap();
+        zombie.take_damage(zombie.hitpoints() - 1);
+        let choice = decide(&encounter, 0);
+        assert_eq!(choice.action_name(), "move");
+        assert!(choice.target_locations()[0].x < 8);
+    }
+
+    #[test]
+    fn artillery_shoots_rather_than_strikes() {
+        let mut encounter = encounter();
+        let choice = decide(&encounter, 2);
+        assert_eq!(choice.action_name(), "shortbow");
+
+        // still the bow with an enemy right beside it
+        let zombie_location = encounter.actors[&1].location();
+        encounter
+            .actors
+            .get_mut(&2)
+            .unwrap()
+            .set_location(zombie_location + Coordinate::new(2, 0));
+        encounter
+            .set_actor_map(2, zombie_location + Coordinate::new(2, 0))
+            .unwrap();
+        assert_eq!(decide(&encounter, 2).action_name(), "shortbow");
+    }
+}
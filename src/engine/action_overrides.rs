@@ -1,4 +1,6 @@
-#[derive(Clone, PartialEq, Hash, Eq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub enum ActionOverride {
     IncreaseTargets(usize),
 }
@@ -1,43 +1,138 @@
-use std::error::Error;
+use std::collections::BTreeMap;
 
 use crate::actors::actor_template::CreatureTemplate;
+use crate::engine::difficulty::{Difficulty, adjusted_xp, party_ceiling, party_threshold};
 use crate::engine::encounter::EncounterInstance;
-use crate::engine::errors::RngTryError;
+use crate::engine::errors::{EngineError, RngTryError};
+use crate::engine::types::Coordinate;
 
 use rand::Rng;
 
 const MAX_TRIES: usize = 512;
 
+// each team's zone reaches this fraction of the way across the map
+const EDGE_ZONE_DEPTH: f32 = 0.25;
+
+// where teams start when the map does not define spawn zones itself
+#[derive(Clone, Copy, PartialEq)]
+pub enum SpawnLayout {
+    Scattered,
+    // first two teams on opposite short edges, any further ones on the long edges
+    OppositeEdges,
+}
+
+#[derive(Clone)]
 pub struct ActorGenParams {
-    pub cr_target: f32,
+    // each team is made up to be a fight of this difficulty for a party of
+    // characters of these levels
+    pub party_levels: Vec<u32>,
+    pub difficulty: Difficulty,
     pub n_teams: usize,
+    pub spawn_layout: SpawnLayout,
+    // feet between the footprints of creatures on different teams
+    pub min_team_distance: f32,
+}
+
+fn edge_spawn_zones(
+    width: usize,
+    height: usize,
+    n_teams: usize,
+) -> BTreeMap<usize, Vec<Coordinate>> {
+    let depth_x = ((width as f32 * EDGE_ZONE_DEPTH) as usize).max(1);
+    let depth_y = ((height as f32 * EDGE_ZONE_DEPTH) as usize).max(1);
+    let (short_depth, long_depth) = if width >= height {
+        (depth_x, depth_y)
+    } else {
+        (depth_y, depth_x)
+    };
+    let mut zones = BTreeMap::new();
+    for team_id in 0..n_teams {
+        let mut zone = Vec::new();
+        for x in 0..width {
+            for y in 0..height {
+                // distance from the far edge along and across the long axis
+                let (along, along_len, across, across_len) = if width >= height {
+                    (x, width, y, height)
+                } else {
+                    (y, height, x, width)
+                };
+                let in_zone = match team_id % 4 {
+                    0 => along < short_depth,
+                    1 => along >= along_len - short_depth,
+                    2 => across < long_depth,
+                    _ => across >= across_len - long_depth,
+                };
+                if in_zone {
+                    zone.push(Coordinate::new(x as isize, y as isize));
+                }
+            }
+        }
+        zones.insert(team_id, zone);
+    }
+    zones
 }
 
 pub fn generate_actors(
     ei: &mut EncounterInstance,
     params: &ActorGenParams,
-    template_pool: &Vec<&'static CreatureTemplate>,
-) -> Result<(), Box<dyn Error>> {
-    let mut id_by_template: Vec<usize> = vec![0; template_pool.len()];
-    let mut rng = rand::rng();
+    template_pool: &[&'static CreatureTemplate],
+) -> Result<(), EngineError> {
+    // hand-authored maps bring their own zones
+    if ei.spawn_zones.is_empty() && params.spawn_layout == SpawnLayout::OppositeEdges {
+        ei.spawn_zones = edge_spawn_zones(ei.width, ei.height, params.n_teams);
+    }
+
+    // continue numbering after any creatures already placed, e.g. from a map file
+    let mut id_by_template: Vec<usize> = template_pool
+        .iter()
+        .map(|ct| {
+            ei.actors
+                .values()
+                .filter(|a| a.template_name() == ct.name)
+                .count()
+        })
+        .collect();
+    let party_size = params.party_levels.len();
+    let target = party_threshold(&params.party_levels, params.difficulty);
+    let ceiling = party_ceiling(&params.party_levels, params.difficulty);
     for team_id in 0..params.n_teams {
-        let mut cr_total: f32 = 0.0;
+        let mut crs: Vec<f32> = Vec::new();
 
         let mut tries: usize = 0;
-        while cr_total < params.cr_target {
+        while adjusted_xp(&crs, party_size) < target || crs.is_empty() {
+            // only creatures that keep the group short of the next difficulty
+            // up, though a team gets its weakest creature at the very least
+            let xp_with = |idx: usize| {
+                let mut with = crs.clone();
+                with.push(template_pool[idx].cr);
+                adjusted_xp(&with, party_size)
+            };
+            let mut fitting: Vec<usize> = (0..template_pool.len())
+                .filter(|&idx| xp_with(idx) < ceiling)
+                .collect();
+            if fitting.is_empty() && crs.is_empty() {
+                fitting = (0..template_pool.len())
+                    .min_by_key(|&idx| xp_with(idx))
+                    .into_iter()
+                    .collect();
+            }
+            if fitting.is_empty() {
+                break;
+            }
             if tries >= MAX_TRIES {
-                return Err(Box::new(RngTryError));
+                return Err(EngineError::ActorGeneration(RngTryError.to_string()));
             }
             tries += 1;
-            let idx = rng.random_range(0..template_pool.len());
+            let idx = fitting[ei.rng.random_range(0..fitting.len())];
             let creature_template = &template_pool[idx];
-            let location_result = ei.get_random_spawn(creature_template.size);
+            let location_result =
+                ei.get_random_spawn(creature_template.size, team_id, params.min_team_distance);
             let instance_n = id_by_template[idx];
             id_by_template[idx] += 1;
             match location_result {
                 Ok(location) => {
-                    ei.instantiate_creature(&creature_template, location, team_id, instance_n)?;
-                    cr_total += creature_template.cr;
+                    ei.instantiate_creature(creature_template, location, team_id, instance_n)?;
+                    crs.push(creature_template.cr);
                 }
                 Err(_) => continue,
             }
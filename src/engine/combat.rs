@@ -0,0 +1,394 @@
+use tyche::Dice;
+use tyche::Expr;
+use tyche::dice::roller::Roller;
+use tyche::expr::Evaled;
+
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::events::{EncounterEvent, RollKind};
+use crate::engine::side_effects::{ApplicableSideEffect, DamageTerrain, DealDamage};
+use crate::engine::types::{AbilityScoreType, Coordinate, DamageType, Skill};
+use crate::engine::util::{FEET_PER_TILE, get_tiles_from_size, modifier_from_score};
+use crate::engine::visibility::{Cover, cover_between, cover_from_point};
+
+// the kept dice of an evaluated expression, in the order they were rolled
+fn kept_dice(evaled: &Evaled, dice: &mut Vec<i32>) {
+    match evaled {
+        Evaled::Num(_) => (),
+        Evaled::Dice(rolled) => dice.extend(
+            rolled
+                .rolls
+                .iter()
+                .filter(|r| r.is_kept())
+                .map(|r| r.val as i32),
+        ),
+        Evaled::Neg(x) => kept_dice(x, dice),
+        Evaled::Add(a, b)
+        | Evaled::Sub(a, b)
+        | Evaled::Mul(a, b)
+        | Evaled::DivDown(a, b)
+        | Evaled::DivUp(a, b) => {
+            kept_dice(a, dice);
+            kept_dice(b, dice);
+        }
+        _ => (),
+    }
+}
+
+// rolls damage for the actor dealing or taking it and logs the dice
+pub fn roll_damage(
+    encounter: &mut EncounterInstance,
+    actor_id: usize,
+    damage: &Expr,
+    detail: String,
+) -> u32 {
+    roll_amount(encounter, actor_id, damage, RollKind::Damage, detail)
+}
+
+// rolls hit points regained for the actor healing and logs the dice; detail
+// names who they go to
+pub fn roll_healing(
+    encounter: &mut EncounterInstance,
+    actor_id: usize,
+    healing: &Expr,
+    detail: String,
+) -> u32 {
+    roll_amount(encounter, actor_id, healing, RollKind::Healing, detail)
+}
+
+fn roll_amount(
+    encounter: &mut EncounterInstance,
+    actor_id: usize,
+    amount: &Expr,
+    kind: RollKind,
+    detail: String,
+) -> u32 {
+    // an expression that overflows rolls nothing rather than taking the game down
+    let rolled = amount
+        .eval(&mut encounter.roller)
+        .map_err(|e| e.to_string())
+        .and_then(|evaled| {
+            let total = evaled.calc().map_err(|e| e.to_string())?;
+            Ok((evaled, total))
+        });
+    let (evaled, total) = match rolled {
+        Ok(rolled) => rolled,
+        Err(e) => {
+            encounter.log(EncounterEvent::Note {
+                text: format!("could not roll {}: {}", amount, e),
+                actor_id: Some(actor_id),
+                location: None,
+            });
+            return 0;
+        }
+    };
+    let mut dice: Vec<i32> = Vec::new();
+    kept_dice(&evaled, &mut dice);
+    encounter.log(EncounterEvent::Roll {
+        actor_id,
+        actor: encounter.actor_name(actor_id),
+        kind,
+        detail,
+        modifier: total - dice.iter().sum::<i32>(),
+        dice,
+        total,
+        target: None,
+        success: None,
+    });
+    total.max(0) as u32
+}
+
+// damage from count dice of the given sides, with nothing added
+pub fn roll_damage_dice(
+    encounter: &mut EncounterInstance,
+    actor_id: usize,
+    count: u8,
+    sides: u8,
+    detail: String,
+) -> u32 {
+    roll_damage(
+        encounter,
+        actor_id,
+        &Expr::Dice(Dice::new(count, sides)),
+        detail,
+    )
+}
+
+fn roll_d20(roller: &mut impl Roller) -> i32 {
+    roller.roll_die(20).val as i32
+}
+
+// distance in feet between the nearest edges of two square footprints
+pub fn square_gap(a: Coordinate, a_w: isize, b: Coordinate, b_w: isize) -> f32 {
+    let gap_1d = |a: isize, b: isize| -> isize { (b - (a + a_w)).max(a - (b + b_w)).max(0) };
+    let gap = gap_1d(a.x, b.x).max(gap_1d(a.y, b.y));
+    gap as f32 * FEET_PER_TILE
+}
+
+// distance in feet between two vertical spans, 0 if they overlap
+fn vertical_gap(a_low: f32, a_high: f32, b_low: f32, b_high: f32) -> f32 {
+    (b_low - a_high).max(a_low - b_high).max(0.)
+}
+
+// vertical extent in feet of an actor, standing or airborne
+pub fn actor_span(encounter: &EncounterInstance, actor_id: usize) -> (f32, f32) {
+    let Some(actor) = encounter.actors.get(&actor_id) else {
+        return (0., 0.);
+    };
+    let low = encounter.actor_elevation(actor_id);
+    (
+        low,
+        low + get_tiles_from_size(actor.size()) as f32 * FEET_PER_TILE,
+    )
+}
+
+// gaps are measured like movement, so the larger of horizontal and vertical
+pub fn footprint_gap(encounter: &EncounterInstance, actor_id: usize, target_id: usize) -> f32 {
+    let (Some(actor), Some(target)) = (
+        encounter.actors.get(&actor_id),
+        encounter.actors.get(&target_id),
+    ) else {
+        return f32::INFINITY;
+    };
+    let horizontal = square_gap(
+        actor.location(),
+        actor.footprint_width() as isize,
+        target.location(),
+        target.footprint_width() as isize,
+    );
+    let (a_low, a_high) = actor_span(encounter, actor_id);
+    let (t_low, t_high) = actor_span(encounter, target_id);
+    horizontal.max(vertical_gap(a_low, a_high, t_low, t_high))
+}
+
+// footprint_gap as it would be with the actor standing at coord, taking up
+// width tiles
+pub fn position_gap(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    coord: Coordinate,
+    width: usize,
+    target_id: usize,
+) -> f32 {
+    let (Some(actor), Some(target)) = (
+        encounter.actors.get(&actor_id),
+        encounter.actors.get(&target_id),
+    ) else {
+        return f32::INFINITY;
+    };
+    let horizontal = square_gap(
+        coord,
+        width as isize,
+        target.location(),
+        target.footprint_width() as isize,
+    );
+    let a_low = encounter.ground_elevation(coord, width) + actor.altitude();
+    let a_high = a_low + get_tiles_from_size(actor.size()) as f32 * FEET_PER_TILE;
+    let (t_low, t_high) = actor_span(encounter, target_id);
+    horizontal.max(vertical_gap(a_low, a_high, t_low, t_high))
+}
+
+pub fn tile_gap(encounter: &EncounterInstance, actor_id: usize, coord: Coordinate) -> f32 {
+    let Some(actor) = encounter.actors.get(&actor_id) else {
+        return f32::INFINITY;
+    };
+    let horizontal = square_gap(actor.location(), actor.footprint_width() as isize, coord, 1);
+    let (a_low, a_high) = actor_span(encounter, actor_id);
+    let (t_low, t_high) = encounter
+        .terrain_at(coord)
+        .map_or((0., 0.), |ti| (ti.elevation, ti.top()));
+    horizontal.max(vertical_gap(a_low, a_high, t_low, t_high))
+}
+
+pub fn in_reach(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    target_id: usize,
+    reach: f32,
+) -> bool {
+    footprint_gap(encounter, actor_id, target_id) < reach
+}
+
+// rolls to hit against the target's AC plus cover and returns the damage to apply
+pub fn roll_attack(
+    encounter: &mut EncounterInstance,
+    attacker_id: usize,
+    target_id: usize,
+    attack_bonus: i32,
+    damage: &Expr,
+    damage_type: DamageType,
+) -> Vec<Box<dyn ApplicableSideEffect>> {
+    let cover = cover_between(encounter, attacker_id, target_id);
+    let (Some(attacker), Some(target)) = (
+        encounter.actors.get(&attacker_id),
+        encounter.actors.get(&target_id),
+    ) else {
+        return Vec::new();
+    };
+    let attacker_name = attacker.name();
+    let target_name = target.name();
+    let target_ac = target.armor_class() as i32 + cover.ac_bonus();
+
+    if cover == Cover::Total {
+        encounter.log(EncounterEvent::Note {
+            text: format!("{} has no line of effect to {}", attacker_name, target_name),
+            actor_id: Some(attacker_id),
+            location: None,
+        });
+        return Vec::new();
+    }
+
+    let roll = roll_d20(&mut encounter.roller);
+    let hit = roll == 20 || (roll != 1 && roll + attack_bonus >= target_ac);
+    encounter.log(EncounterEvent::Roll {
+        actor_id: attacker_id,
+        actor: attacker_name,
+        kind: RollKind::Attack,
+        detail: format!("{} ({:?} cover)", target_name, cover),
+        dice: vec![roll],
+        modifier: attack_bonus,
+        total: roll + attack_bonus,
+        target: Some(target_ac),
+        success: Some(hit),
+    });
+    if !hit {
+        return Vec::new();
+    }
+
+    let amount = roll_damage(encounter, attacker_id, damage, format!("{:?}", damage_type));
+    vec![Box::new(DealDamage {
+        actor_id: target_id,
+        amount,
+        damage_type,
+    })]
+}
+
+// attacks against walls and doors; a hit damages the object directly
+pub fn roll_attack_terrain(
+    encounter: &mut EncounterInstance,
+    attacker_id: usize,
+    target: Coordinate,
+    attack_bonus: i32,
+    damage: &Expr,
+) -> Vec<Box<dyn ApplicableSideEffect>> {
+    let Some(attacker) = encounter.actors.get(&attacker_id) else {
+        return Vec::new();
+    };
+    let attacker_name = attacker.name();
+    let Some(target_ac) = encounter
+        .terrain_at(target)
+        .and_then(|ti| ti.terrain_type.armor_class())
+    else {
+        return Vec::new();
+    };
+
+    let roll = roll_d20(&mut encounter.roller);
+    let hit = roll == 20 || (roll != 1 && roll + attack_bonus >= target_ac as i32);
+    encounter.log(EncounterEvent::Roll {
+        actor_id: attacker_id,
+        actor: attacker_name,
+        kind: RollKind::Attack,
+        detail: target.to_string(),
+        dice: vec![roll],
+        modifier: attack_bonus,
+        total: roll + attack_bonus,
+        target: Some(target_ac as i32),
+        success: Some(hit),
+    });
+    if !hit {
+        return Vec::new();
+    }
+
+    let amount = roll_damage(encounter, attacker_id, damage, "object".to_string());
+    vec![Box::new(DamageTerrain { target, amount })]
+}
+
+// true if the check succeeds
+pub fn roll_ability_check(
+    encounter: &mut EncounterInstance,
+    actor_id: usize,
+    ability: AbilityScoreType,
+    dc: i32,
+) -> bool {
+    let Some(actor) = encounter.actors.get(&actor_id) else {
+        return false;
+    };
+    let actor_name = actor.name();
+    // TODO: skill proficiencies
+    let modifier = modifier_from_score(actor.ability_score(ability.clone()));
+
+    let roll = roll_d20(&mut encounter.roller);
+    let success = roll + modifier >= dc;
+    encounter.log(EncounterEvent::Roll {
+        actor_id,
+        actor: actor_name,
+        kind: RollKind::AbilityCheck,
+        detail: format!("{:?}", ability),
+        dice: vec![roll],
+        modifier,
+        total: roll + modifier,
+        target: Some(dc),
+        success: Some(success),
+    });
+    success
+}
+
+// total of a skill check, for comparing against several DCs at once
+pub fn roll_skill_check(encounter: &mut EncounterInstance, actor_id: usize, skill: Skill) -> i32 {
+    let Some(actor) = encounter.actors.get(&actor_id) else {
+        return 0;
+    };
+    let actor_name = actor.name();
+    // TODO: skill proficiencies
+    let modifier = modifier_from_score(actor.ability_score(skill.ability()));
+
+    let roll = roll_d20(&mut encounter.roller);
+    encounter.log(EncounterEvent::Roll {
+        actor_id,
+        actor: actor_name,
+        kind: RollKind::SkillCheck,
+        detail: format!("{:?}", skill),
+        dice: vec![roll],
+        modifier,
+        total: roll + modifier,
+        target: None,
+        success: None,
+    });
+    roll + modifier
+}
+
+// true if the save succeeds; cover from the origin applies to Dexterity
+// saves, and effects with no origin (such as hazards underfoot) get none
+pub fn roll_saving_throw(
+    encounter: &mut EncounterInstance,
+    origin: Option<Coordinate>,
+    target_id: usize,
+    ability: AbilityScoreType,
+    dc: i32,
+) -> bool {
+    let cover_bonus = match origin {
+        Some(origin) if ability == AbilityScoreType::Dexterity => {
+            cover_from_point(encounter, origin, target_id).dex_save_bonus()
+        }
+        _ => 0,
+    };
+    let Some(target) = encounter.actors.get(&target_id) else {
+        return false;
+    };
+    let target_name = target.name();
+    let modifier = modifier_from_score(target.ability_score(ability.clone())) + cover_bonus;
+
+    let roll = roll_d20(&mut encounter.roller);
+    let success = roll + modifier >= dc;
+    encounter.log(EncounterEvent::Roll {
+        actor_id: target_id,
+        actor: target_name,
+        kind: RollKind::SavingThrow,
+        detail: format!("{:?}", ability),
+        dice: vec![roll],
+        modifier,
+        total: roll + modifier,
+        target: Some(dc),
+        success: Some(success),
+    });
+    success
+}
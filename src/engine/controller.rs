@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use crate::actions::action_template::{ActionExecutionInfo, TargetingSchema};
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::prompt::Prompt;
+
+// decides what an actor does when prompted
+pub trait Controller {
+    // None passes the rest of the turn
+    fn decide(
+        &mut self,
+        prompt: &Prompt,
+        encounter: &EncounterInstance,
+    ) -> Option<ActionExecutionInfo>;
+}
+
+// the prompt's way of giving up the rest of the turn: skip, or keep the
+// current position while deploying
+pub fn pass_action(prompt: &Prompt) -> Option<ActionExecutionInfo> {
+    prompt
+        .actions()
+        .iter()
+        .find(|a| {
+            matches!(a.targeting_schema(), TargetingSchema::NoArgs)
+                && matches!(a.name(), "skip" | "keep")
+        })
+        .map(|&a| ActionExecutionInfo::new(a, prompt.actor_id(), None, None, None))
+}
+
+// who answers each team's prompts; teams without a controller are played
+// from the keyboard
+#[derive(Default)]
+pub struct TeamControllers {
+    controllers: BTreeMap<usize, Box<dyn Controller>>,
+}
+
+impl TeamControllers {
+    pub fn new() -> Self {
+        Self {
+            controllers: BTreeMap::new(),
+        }
+    }
+
+    pub fn assign(&mut self, team_id: usize, controller: Box<dyn Controller>) {
+        self.controllers.insert(team_id, controller);
+    }
+
+    pub fn is_automated(&self, team_id: usize) -> bool {
+        self.controllers.contains_key(&team_id)
+    }
+
+    // whether the prompt on top of the stack is one a controller answers
+    pub fn has_prompt(&self, encounter: &EncounterInstance) -> bool {
+        encounter
+            .peek_prompt()
+            .and_then(|prompt| encounter.actors.get(&prompt.actor_id()))
+            .is_some_and(|actor| self.is_automated(actor.team()))
+    }
+
+    // answers the prompt on top of the stack if its team has a controller;
+    // returns whether an action was pushed
+    pub fn answer_prompt(&mut self, encounter: &mut EncounterInstance) -> bool {
+        let Some(prompt) = encounter.peek_prompt() else {
+            return false;
+        };
+        let Some(team_id) = encounter.actors.get(&prompt.actor_id()).map(|a| a.team()) else {
+            return false;
+        };
+        let Some(controller) = self.controllers.get_mut(&team_id) else {
+            return false;
+        };
+        let aei = match controller.decide(prompt, encounter) {
+            Some(aei) if aei.validate(encounter) => aei,
+            // an illegal choice would stall the encounter, so it passes too
+            _ => match pass_action(prompt) {
+                Some(aei) => aei,
+                None => return false,
+            },
+        };
+        encounter.pop_prompt();
+        encounter.push_action(aei);
+        true
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tyche::dice::DieRoll;
+use tyche::dice::roller::Roller;
+
+// rolls the same dice as tyche's fastrand roller for a given seed, but keeps
+// its state where it can be saved with the encounter and picked up again
+#[derive(Clone, Debug)]
+pub struct DiceRoller(fastrand::Rng);
+
+impl DiceRoller {
+    pub fn with_seed(seed: u64) -> Self {
+        Self(fastrand::Rng::with_seed(seed))
+    }
+}
+
+impl Roller for DiceRoller {
+    fn roll_die(&mut self, sides: u8) -> DieRoll {
+        if sides > 0 {
+            DieRoll::new(self.0.u8(1..=sides))
+        } else {
+            DieRoll::new(0)
+        }
+    }
+}
+
+impl Serialize for DiceRoller {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.get_seed().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DiceRoller {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::with_seed(u64::deserialize(deserializer)?))
+    }
+}
@@ -0,0 +1,229 @@
+// Encounter difficulty the DMG way: monsters are worth XP by challenge
+// rating, their total is multiplied up for fighting in numbers, and the
+// result is held against the XP thresholds of the party's characters.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Deadly,
+}
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "medium" => Some(Difficulty::Medium),
+            "hard" => Some(Difficulty::Hard),
+            "deadly" => Some(Difficulty::Deadly),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Deadly => "deadly",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Difficulty::Easy => 0,
+            Difficulty::Medium => 1,
+            Difficulty::Hard => 2,
+            Difficulty::Deadly => 3,
+        }
+    }
+
+    fn next(&self) -> Option<Self> {
+        match self {
+            Difficulty::Easy => Some(Difficulty::Medium),
+            Difficulty::Medium => Some(Difficulty::Hard),
+            Difficulty::Hard => Some(Difficulty::Deadly),
+            Difficulty::Deadly => None,
+        }
+    }
+}
+
+// (challenge rating, XP), in rising order
+const XP_BY_CR: [(f32, u32); 34] = [
+    (0., 10),
+    (0.125, 25),
+    (0.25, 50),
+    (0.5, 100),
+    (1., 200),
+    (2., 450),
+    (3., 700),
+    (4., 1100),
+    (5., 1800),
+    (6., 2300),
+    (7., 2900),
+    (8., 3900),
+    (9., 5000),
+    (10., 5900),
+    (11., 7200),
+    (12., 8400),
+    (13., 10000),
+    (14., 11500),
+    (15., 13000),
+    (16., 15000),
+    (17., 18000),
+    (18., 20000),
+    (19., 22000),
+    (20., 25000),
+    (21., 33000),
+    (22., 41000),
+    (23., 50000),
+    (24., 62000),
+    (25., 75000),
+    (26., 90000),
+    (27., 105000),
+    (28., 120000),
+    (29., 135000),
+    (30., 155000),
+];
+
+// easy, medium, hard and deadly thresholds for one character, by level
+const XP_THRESHOLDS: [[u32; 4]; 20] = [
+    [25, 50, 75, 100],
+    [50, 100, 150, 200],
+    [75, 150, 225, 400],
+    [125, 250, 375, 500],
+    [250, 500, 750, 1100],
+    [300, 600, 900, 1400],
+    [350, 750, 1100, 1700],
+    [450, 900, 1400, 2100],
+    [550, 1100, 1600, 2400],
+    [600, 1200, 1900, 2800],
+    [800, 1600, 2400, 3600],
+    [1000, 2000, 3000, 4500],
+    [1100, 2200, 3400, 5100],
+    [1250, 2500, 3800, 5700],
+    [1400, 2800, 4300, 6400],
+    [1600, 3200, 4800, 7200],
+    [2000, 3900, 5900, 8800],
+    [2100, 4200, 6300, 9500],
+    [2400, 4900, 7300, 10900],
+    [2800, 5700, 8500, 12700],
+];
+
+// the group multipliers, with one step either side for small and large parties
+const MULTIPLIERS: [f32; 8] = [0.5, 1., 1.5, 2., 2.5, 3., 4., 5.];
+
+// XP for a challenge rating; ratings between table entries count as the one below
+pub fn xp_for_cr(cr: f32) -> u32 {
+    XP_BY_CR
+        .iter()
+        .rev()
+        .find(|&&(table_cr, _)| table_cr <= cr)
+        .map_or(0, |&(_, xp)| xp)
+}
+
+// how much harder than the sum of their XP a number of monsters fight; a
+// party of fewer than three has it harder again, one of six or more easier
+pub fn group_multiplier(n_monsters: usize, party_size: usize) -> f32 {
+    let step: usize = match n_monsters {
+        0 | 1 => 1,
+        2 => 2,
+        3..=6 => 3,
+        7..=10 => 4,
+        11..=14 => 5,
+        _ => 6,
+    };
+    let step = match party_size {
+        0..=2 => step + 1,
+        3..=5 => step,
+        _ => step - 1,
+    };
+    MULTIPLIERS[step]
+}
+
+// the XP a group of monsters of these challenge ratings counts as for a
+// party of party_size
+pub fn adjusted_xp(crs: &[f32], party_size: usize) -> u32 {
+    if crs.is_empty() {
+        return 0;
+    }
+    let total: u32 = crs.iter().map(|&cr| xp_for_cr(cr)).sum();
+    (total as f32 * group_multiplier(crs.len(), party_size)).round() as u32
+}
+
+// the party's threshold for a difficulty, summed over its characters;
+// levels past 20 count as 20
+pub fn party_threshold(party_levels: &[u32], difficulty: Difficulty) -> u32 {
+    party_levels
+        .iter()
+        .map(|&level| XP_THRESHOLDS[level.clamp(1, 20) as usize - 1][difficulty.index()])
+        .sum()
+}
+
+// the adjusted XP a group should stay under to be no harder than the
+// difficulty; deadly has no ceiling, so twice its threshold stands in
+pub fn party_ceiling(party_levels: &[u32], difficulty: Difficulty) -> u32 {
+    match difficulty.next() {
+        Some(next) => party_threshold(party_levels, next),
+        None => party_threshold(party_levels, Difficulty::Deadly) * 2,
+    }
+}
+
+// how hard monsters of these challenge ratings are for the party; None when
+// they are not even an easy fight
+pub fn rate_encounter(crs: &[f32], party_levels: &[u32]) -> Option<Difficulty> {
+    let xp = adjusted_xp(crs, party_levels.len());
+    [
+        Difficulty::Deadly,
+        Difficulty::Hard,
+        Difficulty::Medium,
+        Difficulty::Easy,
+    ]
+    .into_iter()
+    .find(|&d| xp >= party_threshold(party_levels, d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xp_comes_from_the_table() {
+        assert_eq!(xp_for_cr(0.), 10);
+        assert_eq!(xp_for_cr(0.25), 50);
+        assert_eq!(xp_for_cr(1.), 200);
+        assert_eq!(xp_for_cr(30.), 155000);
+        // in between and off the end count as the rating below
+        assert_eq!(xp_for_cr(0.3), 50);
+        assert_eq!(xp_for_cr(40.), 155000);
+    }
+
+    #[test]
+    fn multipliers_grow_with_numbers_and_shift_with_party_size() {
+        assert_eq!(group_multiplier(1, 4), 1.);
+        assert_eq!(group_multiplier(2, 4), 1.5);
+        assert_eq!(group_multiplier(6, 4), 2.);
+        assert_eq!(group_multiplier(7, 4), 2.5);
+        assert_eq!(group_multiplier(11, 4), 3.);
+        assert_eq!(group_multiplier(15, 4), 4.);
+        assert_eq!(group_multiplier(1, 2), 1.5);
+        assert_eq!(group_multiplier(15, 2), 5.);
+        assert_eq!(group_multiplier(1, 6), 0.5);
+    }
+
+    #[test]
+    fn encounters_are_rated_against_the_party() {
+        let party = [3, 3, 3, 3];
+        // the DMG's bugbear and three hobgoblins: 500 XP, doubled for four
+        assert_eq!(
+            rate_encounter(&[1., 0.5, 0.5, 0.5], &party),
+            Some(Difficulty::Hard)
+        );
+        assert_eq!(rate_encounter(&[2.], &party), Some(Difficulty::Easy));
+        assert_eq!(rate_encounter(&[1., 1.], &party), Some(Difficulty::Medium));
+        assert_eq!(rate_encounter(&[2.; 4], &party), Some(Difficulty::Deadly));
+        assert_eq!(rate_encounter(&[0.], &party), None);
+        assert_eq!(rate_encounter(&[], &party), None);
+    }
+}
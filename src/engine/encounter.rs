@@ -1,44 +1,50 @@
-use crate::actors::creatures::zombies::ZOMBIE_TEMPLATE;
-use std::collections::{HashMap, LinkedList};
-use std::error::Error;
+use std::collections::{BTreeMap, HashMap, LinkedList};
+use std::sync::OnceLock;
+use std::sync::mpsc::Receiver;
 
-use crate::actions::action_template::ActionExecutionInfo;
+use crate::actions::action_template::{Action, ActionExecutionInfo};
+use crate::actions::deployment::DEPLOYMENT_ACTIONS;
 use crate::actors::actor_template::{ActorInstance, CreatureTemplate};
+use crate::actors::creatures::creature_templates;
 use crate::engine::actor_gen::{ActorGenParams, generate_actors};
-use crate::engine::errors::{NegativeAbsCoord, NoLegalPosition};
+use crate::engine::combat::square_gap;
+use crate::engine::dice_roller::DiceRoller;
+use crate::engine::errors::{EngineError, NegativeAbsCoord, NoLegalPosition};
+use crate::engine::events::{EncounterEvent, EventLog, LoggedEvent, RollKind, Subscribers};
+use crate::engine::hazards::{hazards_at_turn_start, hazards_on_path};
+use crate::engine::map_loader::MapDefinition;
+use crate::engine::pathfinding::{DiagonalRule, Path, find_path, reachable, walk_cost};
 use crate::engine::prompt::Prompt;
-use crate::engine::side_effects::ApplicableSideEffect;
+use crate::engine::side_effects::{ApplicableSideEffect, Fall};
+use crate::engine::spatial_index::{SpatialIndex, TINY_PER_TILE};
 use crate::engine::terrain::{TerrainInfo, TerrainType};
+use crate::engine::terrain_gen::connectivity::main_region;
 use crate::engine::terrain_gen::{TerrainGenParams, generate_terrain};
-use crate::engine::triggers::TriggerEventType;
+use crate::engine::triggers::{TriggerEvent, TriggerEventType};
 use crate::engine::types::{Coordinate, Size};
-use crate::engine::util::{get_colored_span, get_tiles_from_size};
+use crate::engine::util::{get_tiles_from_size, square_footprint, squeezed_tiles_from_size};
+use crate::engine::visibility::witnesses;
+use rand::SeedableRng;
 use rand::seq::SliceRandom;
-use ratatui::Frame;
-use ratatui::layout::Rect;
-use ratatui::style::{Color, Style};
-use ratatui::text::Line;
-use ratatui::{
-    layout::{Constraint, Direction, Layout},
-    text::Span,
-    widgets::{Block, Borders, Paragraph},
-};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use tyche::dice::roller::FastRand as FastRandRoller;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum StackElementEntry {
     SideEffect(Box<dyn ApplicableSideEffect>),
     Action(Box<ActionExecutionInfo>),
     Prompt(Prompt),
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StackElement {
     pub entry: StackElementEntry,
     pub id: usize,
     pub success_dependencies: Option<Vec<usize>>,
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
 struct InitiativeElement {
     pub actor_id: usize,
     pub initiative: i32,
@@ -46,7 +52,10 @@ struct InitiativeElement {
 
 impl Ord for InitiativeElement {
     fn cmp(&self, other: &Self) -> Ordering {
-        other.initiative.cmp(&self.initiative)
+        other
+            .initiative
+            .cmp(&self.initiative)
+            .then_with(|| self.actor_id.cmp(&other.actor_id))
     }
 }
 
@@ -56,10 +65,13 @@ impl PartialOrd for InitiativeElement {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 struct InitiativeTracker {
     // this is probably slightly more efficient as a linked list
     initiatives: Vec<InitiativeElement>,
     curr_index: usize,
+    // counts from 1, going up each time the order comes back round
+    round: usize,
 }
 
 impl InitiativeTracker {
@@ -67,20 +79,18 @@ impl InitiativeTracker {
         InitiativeTracker {
             initiatives: Vec::new(),
             curr_index: 0,
+            round: 1,
         }
     }
 
     pub fn current_player(&self) -> Option<usize> {
-        if let Some(ie) = self.initiatives.get(self.curr_index) {
-            Some(ie.actor_id)
-        } else {
-            None
-        }
+        self.initiatives.get(self.curr_index).map(|ie| ie.actor_id)
     }
 
     pub fn advance(&mut self) {
         if self.curr_index >= self.initiatives.len() - 1 {
             self.curr_index = 0;
+            self.round += 1;
         } else {
             self.curr_index += 1;
         }
@@ -97,8 +107,8 @@ impl InitiativeTracker {
         self.initiatives.insert(
             idx,
             InitiativeElement {
-                actor_id: actor_id,
-                initiative: initiative,
+                actor_id,
+                initiative,
             },
         );
         if idx <= self.curr_index {
@@ -106,17 +116,18 @@ impl InitiativeTracker {
         }
     }
 
-    pub fn initialize_actors(&mut self, actors: &HashMap<usize, Box<ActorInstance>>) {
+    pub fn initialize_actors(&mut self, actors: &BTreeMap<usize, Box<ActorInstance>>) {
         for (id, actor) in actors.iter() {
             self.initiatives.push(InitiativeElement {
                 actor_id: *id,
-                initiative: actor.initiative().expect("Expected initiative"),
+                initiative: actor.initiative().unwrap_or(actor.initiative_mod()),
             });
         }
         self.initiatives.sort();
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 struct OutcomeTracker {
     next_id: usize,
     successes: HashMap<usize, bool>,
@@ -141,11 +152,7 @@ impl OutcomeTracker {
     }
 
     pub fn get_outcome(&self, id: usize) -> Option<bool> {
-        if let Some(s) = self.successes.get(&id) {
-            Some(*s)
-        } else {
-            None
-        }
+        self.successes.get(&id).copied()
     }
 
     pub fn reset(&mut self) {
@@ -154,27 +161,157 @@ impl OutcomeTracker {
     }
 }
 
-// TODO: having all pub is not very good
+// fields are open to the engine; everything outside the crate goes through
+// the methods below
+#[derive(Clone, Serialize, Deserialize)]
 pub struct EncounterInstance {
     initialized: bool,
-    pub width: usize,
-    pub height: usize,
-    pub terrain: Vec<TerrainInfo>,
-    pub actor_id_next: usize,
-    pub actor_map: Vec<Option<usize>>,
-    pub actors: HashMap<usize, Box<ActorInstance>>,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) terrain: Vec<TerrainInfo>,
+    pub(crate) actor_id_next: usize,
+    pub(crate) spatial_index: SpatialIndex,
+    // ordered so that iterating over actors is reproducible for a given seed
+    pub(crate) actors: BTreeMap<usize, Box<ActorInstance>>,
     initiative_tracker: InitiativeTracker,
-    pub encounter_stack: Vec<StackElement>,
-    pub temp_encounter_queue: LinkedList<StackElement>, // for handling multiple reactions
-    pub roller: FastRandRoller,
-    messages: Vec<String>,
+    pub(crate) encounter_stack: Vec<StackElement>,
+    pub(crate) temp_encounter_queue: LinkedList<StackElement>, // for handling multiple reactions
+    pub(crate) roller: DiceRoller,
+    // drives map and spawn generation; dice go through roller
+    pub(crate) rng: ChaCha12Rng,
+    seed: u64,
+    pub(crate) diagonal_rule: DiagonalRule,
+    // team -> tiles its creatures may spawn on; teams without one spawn anywhere
+    pub(crate) spawn_zones: BTreeMap<usize, Vec<Coordinate>>,
+    events: EventLog,
+    #[serde(skip)]
+    subscribers: Subscribers,
+    #[serde(skip)]
     tmp_message: String,
     outcome_tracker: OutcomeTracker,
+    // worked out from the terrain on first use, as spawning and deployment
+    // ask for it over and over
+    #[serde(skip)]
+    main_region: OnceLock<Vec<bool>>,
+}
+
+// the whole state of an encounter at one moment, to go back to after trying
+// things out; restoring leaves the snapshot as it was, so it can be reused.
+// The log is not copied, only how long it was, since it only ever grows
+#[derive(Clone)]
+pub struct EncounterSnapshot {
+    state: EncounterInstance,
+    events_len: usize,
 }
 
 impl EncounterInstance {
-    pub fn messages(&self) -> &Vec<String> {
-        &self.messages
+    // a copy to play ahead on, e.g. for the AI's playouts: it starts with an
+    // empty log and no subscribers, so nothing it does is sent anywhere
+    pub fn clone_without_log(&self) -> EncounterInstance {
+        EncounterInstance {
+            initialized: self.initialized,
+            width: self.width,
+            height: self.height,
+            terrain: self.terrain.clone(),
+            actor_id_next: self.actor_id_next,
+            spatial_index: self.spatial_index.clone(),
+            actors: self.actors.clone(),
+            initiative_tracker: self.initiative_tracker.clone(),
+            encounter_stack: self.encounter_stack.clone(),
+            temp_encounter_queue: self.temp_encounter_queue.clone(),
+            roller: self.roller.clone(),
+            rng: self.rng.clone(),
+            seed: self.seed,
+            diagonal_rule: self.diagonal_rule,
+            spawn_zones: self.spawn_zones.clone(),
+            events: EventLog::default(),
+            subscribers: Subscribers::default(),
+            tmp_message: self.tmp_message.clone(),
+            outcome_tracker: self.outcome_tracker.clone(),
+            main_region: self.main_region.clone(),
+        }
+    }
+
+    pub fn snapshot(&self) -> EncounterSnapshot {
+        EncounterSnapshot {
+            state: self.clone_without_log(),
+            events_len: self.events.events().len(),
+        }
+    }
+
+    // subscribers stay subscribed; they are not told that anything went back
+    pub fn restore(&mut self, snapshot: &EncounterSnapshot) {
+        let subscribers = std::mem::take(&mut self.subscribers);
+        let mut events = std::mem::take(&mut self.events);
+        *self = snapshot.state.clone_without_log();
+        self.subscribers = subscribers;
+        events.truncate(snapshot.events_len);
+        self.events = events;
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn actors(&self) -> &BTreeMap<usize, Box<ActorInstance>> {
+        &self.actors
+    }
+
+    pub fn actor(&self, actor_id: usize) -> Option<&ActorInstance> {
+        self.actors.get(&actor_id).map(|a| a.as_ref())
+    }
+
+    pub fn diagonal_rule(&self) -> DiagonalRule {
+        self.diagonal_rule
+    }
+
+    // how diagonal steps are charged from now on
+    pub fn set_diagonal_rule(&mut self, rule: DiagonalRule) {
+        self.diagonal_rule = rule;
+    }
+
+    pub fn events(&self) -> &[LoggedEvent] {
+        self.events.events()
+    }
+
+    // every event logged from now on is sent to the receiver
+    pub fn subscribe(&mut self) -> Receiver<LoggedEvent> {
+        self.subscribers.add()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // from now on each event notes the teams that saw it happen, as things
+    // stood when it was logged
+    pub fn track_sight(&mut self) {
+        self.events.track_sight();
+    }
+
+    pub fn log(&mut self, event: EncounterEvent) {
+        let seen_by = self
+            .events
+            .tracks_sight()
+            .then(|| witnesses(self, &event.subject()));
+        let logged = LoggedEvent {
+            round: self.round(),
+            seen_by,
+            event,
+        };
+        self.subscribers.send(&logged);
+        self.events.push(logged);
+    }
+
+    // the actor's name as it appears in the log
+    pub fn actor_name(&self, actor_id: usize) -> String {
+        self.actors
+            .get(&actor_id)
+            .map_or_else(|| format!("#{}", actor_id), |a| a.name())
     }
 
     pub fn tmp_message(&self) -> &String {
@@ -189,9 +326,9 @@ impl EncounterInstance {
 
     pub fn get_actor(&mut self, actor_id: usize) -> Option<&mut ActorInstance> {
         if let Some(a) = self.actors.get_mut(&actor_id) {
-            return Some(a);
+            Some(a)
         } else {
-            return None;
+            None
         }
     }
 
@@ -211,15 +348,12 @@ impl EncounterInstance {
             return false;
         }
 
-        if let Some(_) = self.actor_id_at(coord) {
+        if self.actor_id_at(coord).is_some() {
             return false;
         }
 
-        let ti = self.terrain_at(coord);
-        match ti.terrain_type {
-            TerrainType::Floor => true,
-            _ => false,
-        }
+        self.terrain_at(coord)
+            .is_some_and(|ti| matches!(ti.terrain_type, TerrainType::Floor) && ti.hazard.is_none())
     }
 
     fn can_move_to_subtile(&self, coord: Coordinate, actor_id: usize) -> bool {
@@ -231,20 +365,63 @@ impl EncounterInstance {
             return false;
         }
 
-        if let Some(other_id) = self.actor_id_at(coord) {
-            if other_id != actor_id {
-                return false;
-            }
+        let others: Vec<usize> = self
+            .actors_at(coord)
+            .iter()
+            .copied()
+            .filter(|&id| id != actor_id)
+            .collect();
+        if !others.is_empty() && !self.can_share_tile(actor_id, &others) {
+            return false;
+        }
+
+        self.terrain_at(coord)
+            .is_some_and(|ti| ti.terrain_type.is_passable())
+    }
+
+    // Tiny allies can crowd onto one tile, nobody else ends a move in
+    // another creature's space
+    fn can_share_tile(&self, actor_id: usize, others: &[usize]) -> bool {
+        let Some(actor) = self.actors.get(&actor_id) else {
+            return false;
+        };
+        actor.size() == Size::Tiny
+            && others.len() < TINY_PER_TILE
+            && others.iter().all(|id| {
+                self.actors
+                    .get(id)
+                    .is_some_and(|o| o.size() == Size::Tiny && o.team() == actor.team())
+            })
+    }
+
+    // allies can be passed through but not ended on; hostiles block unless
+    // they are at least two sizes larger or smaller
+    fn can_pass_through_subtile(&self, coord: Coordinate, actor_id: usize, team: usize) -> bool {
+        if coord.x < 0 || coord.y < 0 {
+            return false;
+        }
+
+        if coord.x as usize >= self.width || coord.y as usize >= self.height {
+            return false;
         }
 
-        let ti = self.terrain_at(coord);
-        match ti.terrain_type {
-            TerrainType::Floor => true,
-            _ => false,
+        let size = self.actors.get(&actor_id).map(|a| a.size());
+        let blocked = self.actors_at(coord).iter().any(|&other_id| {
+            other_id != actor_id
+                && self.actors.get(&other_id).is_some_and(|o| {
+                    o.team() != team
+                        && size.is_none_or(|s| (s as isize - o.size() as isize).abs() < 2)
+                })
+        });
+        if blocked {
+            return false;
         }
+
+        self.terrain_at(coord)
+            .is_some_and(|ti| ti.terrain_type.is_passable())
     }
 
-    fn get_random_coord_list(&self) -> Vec<Coordinate> {
+    fn get_random_coord_list(&mut self) -> Vec<Coordinate> {
         // TODO: probably try random order of one axis first
         let mut all_coords: Vec<Coordinate> = Vec::new();
         for x in 0..self.width {
@@ -252,261 +429,489 @@ impl EncounterInstance {
                 all_coords.push(Coordinate::new(x as isize, y as isize));
             }
         }
-        all_coords.shuffle(&mut rand::rng());
+        all_coords.shuffle(&mut self.rng);
         all_coords
     }
 
-    pub fn get_random_spawn(&mut self, size: Size) -> Result<Coordinate, NoLegalPosition> {
+    pub fn get_random_spawn(
+        &mut self,
+        size: Size,
+        team_id: usize,
+        min_enemy_distance: f32,
+    ) -> Result<Coordinate, NoLegalPosition> {
         let actor_width: usize = get_tiles_from_size(size);
-        let coords = self.get_random_coord_list();
+        let mut coords = self.get_random_coord_list();
+        if let Some(zone) = self.spawn_zones.get(&team_id) {
+            coords.retain(|coord| zone.contains(coord));
+        }
+        // creatures cut off from the main region could never reach a fight
+        let in_main = self.main_region();
 
         'coord_loop: for &coord in coords.iter() {
             for x_off in 0..actor_width {
                 for y_off in 0..actor_width {
                     let offset = Coordinate::new(x_off as isize, y_off as isize);
-                    if !self.is_spawnable(coord + offset) {
+                    if !self.is_spawnable(coord + offset)
+                        || !self.idx(coord + offset).is_ok_and(|i| in_main[i])
+                    {
                         continue 'coord_loop;
                     }
                 }
             }
+            let too_close = self.actors.values().any(|other| {
+                other.team() != team_id
+                    && square_gap(
+                        coord,
+                        actor_width as isize,
+                        other.location(),
+                        other.footprint_width() as isize,
+                    ) < min_enemy_distance
+            });
+            if too_close {
+                continue;
+            }
             return Ok(coord);
         }
         Err(NoLegalPosition)
     }
 
-    pub fn actor_id_at(&self, coord: Coordinate) -> Option<usize> {
-        if let Some(r) = self.actor_map.get(self.idx(coord).unwrap()) {
-            *r
-        } else {
-            None
+    // the tiles creatures can reach the fight from
+    fn main_region(&self) -> &[bool] {
+        self.main_region
+            .get_or_init(|| main_region(&self.terrain, self.width, self.height))
+    }
+
+    // where an actor may be placed during deployment: floor in its team's
+    // spawn zone, if it has one, reachable from the main region
+    pub fn can_deploy_to(&self, actor_id: usize, coord: Coordinate) -> bool {
+        let Some(actor) = self.actors.get(&actor_id) else {
+            return false;
+        };
+        if let Some(zone) = self.spawn_zones.get(&actor.team())
+            && !zone.contains(&coord)
+        {
+            return false;
         }
+        let in_main = self.main_region();
+        let actor_width = get_tiles_from_size(actor.size());
+        for x_off in 0..actor_width {
+            for y_off in 0..actor_width {
+                let tile = coord + Coordinate::new(x_off as isize, y_off as isize);
+                if self.actors_at(tile).iter().any(|&id| id != actor_id) {
+                    return false;
+                }
+                let (Some(ti), Ok(i)) = (self.terrain_at(tile), self.idx(tile)) else {
+                    return false;
+                };
+                if ti.terrain_type != TerrainType::Floor || ti.hazard.is_some() || !in_main[i] {
+                    return false;
+                }
+            }
+        }
+        true
     }
 
-    fn set_actor_id_at(&mut self, actor_id: Option<usize>, coord: Coordinate) {
-        let idx = self.idx(coord).unwrap();
-        self.actor_map[idx] = actor_id;
+    // prompts every creature of the given teams to pick its starting
+    // position; only possible before initiative is rolled, i.e. before the
+    // first process_stack
+    pub fn begin_deployment(&mut self, teams: &[usize]) -> Result<(), EngineError> {
+        if self.initialized {
+            return Err(EngineError::AlreadyInitialized);
+        }
+        let deploying: Vec<usize> = self
+            .actors
+            .iter()
+            .filter(|(_, a)| teams.contains(&a.team()))
+            .map(|(id, _)| *id)
+            .collect();
+        if deploying.is_empty() {
+            return Ok(());
+        }
+        self.log(EncounterEvent::Note {
+            text: "Deployment: place each creature with deploy <x,y>, or keep".to_string(),
+            actor_id: None,
+            location: None,
+        });
+        // the stack is last in first out, so the first actor is prompted first
+        for actor_id in deploying.into_iter().rev() {
+            let prompt = Prompt::new(actor_id, DEPLOYMENT_ACTIONS.clone());
+            self.enqueue_event(StackElementEntry::Prompt(prompt), None);
+        }
+        Ok(())
     }
 
-    pub fn terrain_at(&self, coord: Coordinate) -> &TerrainInfo {
-        &self.terrain[self.idx(coord).unwrap()]
+    // one of the actors on a tile, the first to arrive if Tiny ones share it
+    pub fn actor_id_at(&self, coord: Coordinate) -> Option<usize> {
+        self.actors_at(coord).first().copied()
     }
 
-    pub fn can_move_to(&self, actor_id: usize, coord: Coordinate) -> bool {
-        if let Some(actor) = self.actors.get(&actor_id) {
-            let actor_width = get_tiles_from_size(actor.size());
-            for x_off in 0..actor_width {
-                for y_off in 0..actor_width {
-                    let offset: Coordinate = Coordinate::new(x_off as isize, y_off as isize);
-                    if !self.can_move_to_subtile(coord + offset, actor_id) {
-                        return false;
-                    }
+    pub fn actors_at(&self, coord: Coordinate) -> &[usize] {
+        self.spatial_index.occupants(coord)
+    }
+
+    // tiles the actor covers where it stands
+    pub fn footprint(&self, actor_id: usize) -> &[Coordinate] {
+        self.spatial_index.footprint(actor_id)
+    }
+
+    // None off the map
+    pub fn terrain_at(&self, coord: Coordinate) -> Option<&TerrainInfo> {
+        if !self.in_bounds(coord) {
+            return None;
+        }
+        self.terrain.get(self.idx(coord).ok()?)
+    }
+
+    // the main region is worked out again after any change, since e.g. a
+    // broken wall can open up a pocket
+    pub fn terrain_at_mut(&mut self, coord: Coordinate) -> Option<&mut TerrainInfo> {
+        if !self.in_bounds(coord) {
+            return None;
+        }
+        let idx = self.idx(coord).ok()?;
+        self.main_region.take();
+        self.terrain.get_mut(idx)
+    }
+
+    // a door can span several tiles; returns every door tile connected to coord
+    pub fn door_tiles(&self, coord: Coordinate) -> Vec<Coordinate> {
+        let is_door = |c: Coordinate| {
+            self.terrain_at(c)
+                .is_some_and(|ti| matches!(ti.terrain_type, TerrainType::Door(_)))
+        };
+        if !is_door(coord) {
+            return Vec::new();
+        }
+        let mut tiles: Vec<Coordinate> = vec![coord];
+        let mut i: usize = 0;
+        while i < tiles.len() {
+            let curr = tiles[i];
+            for offset in [
+                Coordinate::new(1, 0),
+                Coordinate::new(-1, 0),
+                Coordinate::new(0, 1),
+                Coordinate::new(0, -1),
+            ] {
+                let next = curr + offset;
+                if is_door(next) && !tiles.contains(&next) {
+                    tiles.push(next);
                 }
             }
-            return true;
+            i += 1;
         }
-        false
+        tiles
+    }
+
+    pub fn in_bounds(&self, coord: Coordinate) -> bool {
+        coord.x >= 0
+            && coord.y >= 0
+            && (coord.x as usize) < self.width
+            && (coord.y as usize) < self.height
     }
 
-    pub fn render_map(&self, frame: &mut Frame, area: Rect) {
-        let mut text: Vec<Line> = Vec::new();
+    // anything off the map is treated as opaque
+    pub fn blocks_sight(&self, coord: Coordinate) -> bool {
+        self.terrain_at(coord)
+            .is_none_or(|ti| ti.terrain_type.blocks_sight())
+    }
 
-        for y in (0..self.height).rev() {
-            let mut row: Vec<Span> = Vec::new();
-            for x in 0..self.width {
-                let coord = Coordinate::new(x as isize, y as isize);
-                if let Some(actor_id) = self.actor_id_at(coord) {
-                    match self.actors.get(&actor_id) {
-                        Some(actor) => {
-                            let (s, c, bg): (String, Color, Color) =
-                                get_colored_span(actor_id, actor.team());
-                            row.push(Span::styled(s, Style::default().fg(c).bg(bg)));
-                        }
-                        None => {
-                            panic!("Actor not found");
-                        }
-                    }
-                } else {
-                    let s = Span::from(
-                        match self.terrain_at(coord).terrain_type {
-                            TerrainType::Empty => ' ',
-                            TerrainType::Floor => '░',
-                            TerrainType::Wall => '█',
-                        }
-                        .to_string(),
-                    );
-                    row.push(s);
+    // tiles per side of the space the actor would take up ending a move at
+    // coord: its own, or a squeezed one if only that fits
+    pub fn fit_width(&self, actor_id: usize, coord: Coordinate) -> Option<usize> {
+        let actor = self.actors.get(&actor_id)?;
+        [
+            get_tiles_from_size(actor.size()),
+            squeezed_tiles_from_size(actor.size()),
+        ]
+        .into_iter()
+        .find(|&width| {
+            square_footprint(coord, width)
+                .into_iter()
+                .all(|tile| self.can_move_to_subtile(tile, actor_id))
+        })
+    }
+
+    // as fit_width, for passing through coord on the way somewhere else
+    pub fn pass_width(&self, actor_id: usize, coord: Coordinate) -> Option<usize> {
+        let actor = self.actors.get(&actor_id)?;
+        [
+            get_tiles_from_size(actor.size()),
+            squeezed_tiles_from_size(actor.size()),
+        ]
+        .into_iter()
+        .find(|&width| {
+            square_footprint(coord, width)
+                .into_iter()
+                .all(|tile| self.can_pass_through_subtile(tile, actor_id, actor.team()))
+        })
+    }
+
+    pub fn can_move_to(&self, actor_id: usize, coord: Coordinate) -> bool {
+        self.fit_width(actor_id, coord).is_some()
+    }
+
+    pub fn can_pass_through(&self, actor_id: usize, coord: Coordinate) -> bool {
+        self.pass_width(actor_id, coord).is_some()
+    }
+
+    // cost multiplier for moving the actor's footprint onto coord;
+    // difficult terrain, climbing, swimming and squeezing each double the cost
+    pub fn movement_multiplier(&self, actor_id: usize, coord: Coordinate) -> u32 {
+        let Some(actor) = self.actors.get(&actor_id) else {
+            return 1;
+        };
+        // flyers in the air pass over whatever is on the ground
+        if actor.altitude() > 0. && actor.fly_speed() > 0. {
+            return 1;
+        }
+        let full_width = get_tiles_from_size(actor.size());
+        let actor_width = self.pass_width(actor_id, coord).unwrap_or(full_width);
+        let mut multiplier: u32 = 1;
+        for x_off in 0..actor_width {
+            for y_off in 0..actor_width {
+                let offset: Coordinate = Coordinate::new(x_off as isize, y_off as isize);
+                let Some(ti) = self.terrain_at(coord + offset) else {
+                    continue;
+                };
+                let tt = &ti.terrain_type;
+                let mut tile_multiplier: u32 = 1;
+                if tt.is_difficult() {
+                    tile_multiplier += 1;
+                }
+                if tt.needs_climbing() && actor.climb_speed() <= 0.0 {
+                    tile_multiplier += 1;
                 }
+                if tt.needs_swimming() && actor.swim_speed() <= 0.0 {
+                    tile_multiplier += 1;
+                }
+                multiplier = multiplier.max(tile_multiplier);
             }
-            text.push(Line::from(row));
         }
-        frame.render_widget(
-            Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Map")),
-            area,
-        );
+        if actor_width < full_width {
+            multiplier += 1;
+        }
+        multiplier
     }
 
-    pub fn render_sideinfo(&mut self, frame: &mut Frame, area: Rect) {
-        let area_split = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Min(1), Constraint::Min(1), Constraint::Min(1)])
-            .split(area);
-
-        if let Some(se) = self.encounter_stack.last() {
-            if let StackElementEntry::Prompt(prmpt) = &se.entry {
-                let curr_actor_id = prmpt.actor_id();
-                let curr_actor = self.actors.get(&curr_actor_id).expect("missing actor");
-                let (s, c, bg): (String, Color, Color) =
-                    get_colored_span(curr_actor_id, curr_actor.team());
-
-                let mut initiative_bar: Vec<Span> = Vec::new();
-                initiative_bar.push(Span::from(format!("Current actor: {} ", curr_actor.name())));
-                initiative_bar.push(Span::styled(s, Style::default().fg(c).bg(bg)));
-                let mut txt: Vec<Line> = Vec::new();
-                txt.push(Line::from(initiative_bar));
-
-                let mut stats_info: String = String::new();
-                stats_info.push_str(&format!(
-                    "HP: {}/{}\n",
-                    curr_actor.hitpoints(),
-                    curr_actor.max_hitpoints()
-                ));
-                stats_info.push_str(&format!("AC: {}\n", curr_actor.armor_class()));
-                stats_info.push_str(&format!("Movement: {}\n", curr_actor.remaining_movement()));
-                stats_info.push_str(&format!(
-                    "Actions: {} Bonus Actions: {}\n",
-                    curr_actor.action_slots(),
-                    curr_actor.bonus_action_slots()
-                ));
-
-                let mut action_info: String = String::new();
-                for &action in prmpt.actions().iter() {
-                    action_info.push_str(action.name());
-                    action_info.push('\n');
+    // highest ground under a footprint of the given width in tiles
+    pub fn ground_elevation(&self, coord: Coordinate, width: usize) -> f32 {
+        let mut elevation: f32 = 0.;
+        for x_off in 0..width {
+            for y_off in 0..width {
+                let tile = coord + Coordinate::new(x_off as isize, y_off as isize);
+                if let Some(ti) = self.terrain_at(tile) {
+                    elevation = elevation.max(ti.elevation);
                 }
+            }
+        }
+        elevation
+    }
 
-                frame.render_widget(
-                    Paragraph::new(txt)
-                        .block(Block::default().borders(Borders::ALL).title("Initiative")),
-                    area_split[0],
-                );
-                frame.render_widget(
-                    Paragraph::new(stats_info)
-                        .block(Block::default().borders(Borders::ALL).title("Resources")),
-                    area_split[1],
-                );
-                frame.render_widget(
-                    Paragraph::new(action_info)
-                        .block(Block::default().borders(Borders::ALL).title("Actions")),
-                    area_split[2],
-                );
-            } else {
-                frame.render_widget(
-                    Paragraph::new("ERROR")
-                        .block(Block::default().borders(Borders::ALL).title("Initiative")),
-                    area_split[0],
-                );
-
-                self.messages.push(format!(
-                    "non prompt on top of stack {:?} {:?}",
-                    self.messages.len(),
-                    self.encounter_stack.len()
-                ));
+    // feet above the map's base level of the bottom of an actor
+    pub fn actor_elevation(&self, actor_id: usize) -> f32 {
+        let Some(actor) = self.actors.get(&actor_id) else {
+            return 0.;
+        };
+        self.ground_elevation(actor.location(), actor.footprint_width()) + actor.altitude()
+    }
+
+    // highest point an actor could climb to from where it is, from the
+    // climbable terrain next to its footprint
+    pub fn climb_limit(&self, actor_id: usize) -> Option<f32> {
+        let actor = self.actors.get(&actor_id)?;
+        let actor_width = actor.footprint_width() as isize;
+        let mut limit: Option<f32> = None;
+        for x_off in -1..=actor_width {
+            for y_off in -1..=actor_width {
+                let tile = actor.location() + Coordinate::new(x_off, y_off);
+                let Some(ti) = self.terrain_at(tile) else {
+                    continue;
+                };
+                if ti.terrain_type.is_climbable() {
+                    limit = Some(limit.unwrap_or(0.).max(ti.top()));
+                }
             }
-        } else {
-            frame.render_widget(
-                Paragraph::new("ERROR")
-                    .block(Block::default().borders(Borders::ALL).title("Initiative")),
-                area_split[0],
-            );
+        }
+        limit
+    }
 
-            self.messages.push(format!(
-                "else {:?} {:?}",
-                self.messages.len(),
-                self.encounter_stack.len()
-            ));
+    // whether an airborne actor stays up: flyers need their speed and to be
+    // upright, climbers need something to cling to, and nobody who is down
+    // or can't move stays anywhere but the ground
+    pub fn is_supported(&self, actor_id: usize) -> bool {
+        let Some(actor) = self.actors.get(&actor_id) else {
+            return true;
+        };
+        if actor.altitude() <= 0. {
+            return true;
+        }
+        if actor.is_prone() || actor.is_down() {
+            return false;
+        }
+        if actor.fly_speed() > 0. {
+            return true;
+        }
+        // anything that can't move can't hold on either
+        if actor.speed() <= 0. && actor.climb_speed() <= 0. {
+            return false;
         }
+        self.climb_limit(actor_id)
+            .is_some_and(|limit| limit >= self.actor_elevation(actor_id))
     }
 
-    pub fn from_params(
-        terrain_params: &TerrainGenParams,
-        actor_params: &ActorGenParams,
+    fn check_falling(&mut self) -> Result<(), EngineError> {
+        let falling: Vec<usize> = self
+            .actors
+            .keys()
+            .copied()
+            .filter(|&id| !self.is_supported(id))
+            .collect();
+        for actor_id in falling {
+            Fall { actor_id }.apply(self)?;
+        }
+        Ok(())
+    }
+
+    pub fn find_path(&self, actor_id: usize, dest: Coordinate) -> Option<Path> {
+        find_path(self, actor_id, dest, self.diagonal_rule)
+    }
+
+    pub fn walk_cost(&self, actor_id: usize, tiles: &[Coordinate]) -> f32 {
+        walk_cost(self, actor_id, tiles, self.diagonal_rule)
+    }
+
+    pub fn reachable(&self, actor_id: usize, max_cost: f32) -> HashMap<Coordinate, f32> {
+        reachable(self, actor_id, max_cost, self.diagonal_rule)
+    }
+
+    fn with_terrain(
+        width: usize,
+        height: usize,
+        terrain: Vec<TerrainInfo>,
+        rng: ChaCha12Rng,
+        seed: u64,
     ) -> EncounterInstance {
-        let mut ei = EncounterInstance {
+        EncounterInstance {
             initialized: false,
-            width: terrain_params.width,
-            height: terrain_params.height,
-            terrain: generate_terrain(terrain_params),
+            width,
+            height,
+            terrain,
             actor_id_next: 0,
-            actor_map: vec![None; terrain_params.width * terrain_params.height],
-            actors: HashMap::new(),
+            spatial_index: SpatialIndex::new(width, height),
+            actors: BTreeMap::new(),
             initiative_tracker: InitiativeTracker::new(),
             encounter_stack: Vec::new(),
             temp_encounter_queue: LinkedList::new(),
-            roller: FastRandRoller::default(), // TODO: seed https://docs.rs/tyche/latest/tyche/#rolling-dice
-            messages: Vec::new(),
+            roller: DiceRoller::with_seed(seed),
+            rng,
+            seed,
+            diagonal_rule: DiagonalRule::default(),
+            spawn_zones: BTreeMap::new(),
+            events: EventLog::default(),
+            subscribers: Subscribers::default(),
             tmp_message: String::new(),
             outcome_tracker: OutcomeTracker::new(),
-        };
-
-        // TODO: move pool to fn
-        let mut template_pool: Vec<&'static CreatureTemplate> = Vec::new();
-        template_pool.push(&ZOMBIE_TEMPLATE);
-
-        match generate_actors(&mut ei, actor_params, &template_pool) {
-            Ok(()) => {}
-            Err(_) => panic!("failed to generate actors"),
+            main_region: OnceLock::new(),
         }
-        ei.initialize();
-        ei
     }
 
-    pub fn skip_turn(&mut self) {
-        self.initiative_tracker.advance();
-        let curr_actor = self
-            .actors
-            .get_mut(
-                &self
-                    .initiative_tracker
-                    .current_player()
-                    .expect("empty initiative tracker"),
-            )
-            .unwrap();
-        curr_actor.reset_for_new_round();
+    pub fn from_params(
+        terrain_params: &TerrainGenParams,
+        actor_params: &ActorGenParams,
+        seed: u64,
+    ) -> Result<EncounterInstance, EngineError> {
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        let terrain = generate_terrain(terrain_params, &mut rng);
+        let mut ei = EncounterInstance::with_terrain(
+            terrain_params.width(),
+            terrain_params.height(),
+            terrain,
+            rng,
+            seed,
+        );
+
+        generate_actors(&mut ei, actor_params, &creature_templates())?;
+        Ok(ei)
     }
 
-    pub fn set_actor_map(
-        &mut self,
-        actor_id: usize,
-        coord: Coordinate,
-    ) -> Result<(), Box<dyn Error>> {
-        if let Some(actor) = self.actors.get(&actor_id) {
-            let actor_width = get_tiles_from_size(actor.size());
-
-            // assumes unit is square
-            let coord_old = actor.location();
-            for i in coord_old.x..self.width as isize {
-                let coord_tmp = Coordinate::new(i, coord_old.y);
-                if self.actor_id_at(coord_tmp) != Some(actor_id) {
-                    break;
-                }
-                for j in coord_old.y..self.height as isize {
-                    let coord_tmp = Coordinate::new(i, j);
-                    if self.actor_id_at(coord_tmp) != Some(actor_id) {
-                        break;
-                    }
-                    self.set_actor_id_at(None, coord_tmp);
-                }
+    // builds an encounter from a hand-authored map, placing its creatures first
+    // and then generating any others from actor_params
+    pub fn from_map(
+        map: MapDefinition,
+        actor_params: Option<&ActorGenParams>,
+        seed: u64,
+    ) -> Result<EncounterInstance, EngineError> {
+        let mut ei = EncounterInstance::with_terrain(
+            map.width,
+            map.height,
+            map.terrain,
+            ChaCha12Rng::seed_from_u64(seed),
+            seed,
+        );
+        ei.spawn_zones = map.spawn_zones;
+
+        let mut id_by_template: HashMap<&'static str, usize> = HashMap::new();
+        for creature in map.creatures {
+            let instance_n = id_by_template.entry(creature.template.name).or_insert(0);
+            let actor_id = ei.instantiate_creature(
+                creature.template,
+                creature.location,
+                creature.team_id,
+                *instance_n,
+            )?;
+            *instance_n += 1;
+            if let Some(name) = creature.name
+                && let Some(actor) = ei.get_actor(actor_id)
+            {
+                actor.set_name(name);
             }
+        }
 
-            for x_off in 0..actor_width {
-                for y_off in 0..actor_width {
-                    let offset = Coordinate::new(x_off as isize, y_off as isize);
-                    self.set_actor_id_at(Some(actor_id), coord + offset);
-                }
-            }
-            return Ok(());
+        if let Some(actor_params) = actor_params {
+            generate_actors(&mut ei, actor_params, &creature_templates())?;
+        }
+        Ok(ei)
+    }
+
+    pub fn round(&self) -> usize {
+        self.initiative_tracker.round
+    }
+
+    pub fn skip_turn(&mut self) -> Result<(), EngineError> {
+        if let Some(actor_id) = self.initiative_tracker.current_player() {
+            self.log(EncounterEvent::TurnEnded {
+                actor_id,
+                actor: self.actor_name(actor_id),
+            });
+        }
+        self.initiative_tracker.advance();
+        let curr_actor_id = self
+            .initiative_tracker
+            .current_player()
+            .ok_or(EngineError::EmptyInitiative)?;
+        self.actors
+            .get_mut(&curr_actor_id)
+            .ok_or(EngineError::MissingActor(curr_actor_id))?
+            .reset_for_new_round();
+
+        self.log(EncounterEvent::TurnStarted {
+            actor_id: curr_actor_id,
+            actor: self.actor_name(curr_actor_id),
+        });
+        for side_effect in hazards_at_turn_start(self, curr_actor_id) {
+            self.enqueue_event(StackElementEntry::SideEffect(side_effect), None);
         }
-        Err("Actor not found".into())
+        Ok(())
+    }
+
+    pub fn set_actor_map(&mut self, actor_id: usize, coord: Coordinate) -> Result<(), EngineError> {
+        let actor = self
+            .actors
+            .get(&actor_id)
+            .ok_or(EngineError::MissingActor(actor_id))?;
+        let tiles = square_footprint(coord, actor.footprint_width());
+        self.spatial_index.place(actor_id, tiles);
+        Ok(())
     }
 
     pub fn instantiate_creature(
@@ -515,7 +920,7 @@ impl EncounterInstance {
         location: Coordinate,
         team_id: usize,
         instance_n: usize,
-    ) -> Result<usize, Box<dyn Error>> {
+    ) -> Result<usize, EngineError> {
         let actor_id = self.next_actor_id();
 
         let mut ai_box = Box::new(ActorInstance::from_creature_template(
@@ -526,39 +931,84 @@ impl EncounterInstance {
             instance_n,
         )?);
         ai_box.reset_for_new_round();
+        self.actors.insert(actor_id, ai_box);
 
         if self.initialized {
-            ai_box.roll_initiative(&mut self.roller);
-            self.initiative_tracker
-                .add_actor(actor_id, ai_box.initiative().unwrap());
+            self.roll_initiative(actor_id);
+            if let Some(actor) = self.actors.get(&actor_id) {
+                let initiative = actor.initiative().unwrap_or(actor.initiative_mod());
+                self.initiative_tracker.add_actor(actor_id, initiative);
+            }
         }
 
-        self.actors.insert(actor_id, ai_box);
-
         self.set_actor_map(actor_id, location)?;
 
         Ok(actor_id)
     }
 
-    pub fn initialize(&mut self) {
+    pub fn initialize(&mut self) -> Result<(), EngineError> {
         if self.initialized {
-            panic!("attempted to initialize already initialized encounter")
+            return Err(EngineError::AlreadyInitialized);
         }
-        for (_, actor) in self.actors.iter_mut() {
-            actor.roll_initiative(&mut self.roller);
+        let actor_ids: Vec<usize> = self.actors.keys().copied().collect();
+        for actor_id in actor_ids {
+            self.roll_initiative(actor_id);
         }
         self.initiative_tracker.initialize_actors(&self.actors);
         self.initialized = true;
+        if let Some(actor_id) = self.initiative_tracker.current_player() {
+            self.log(EncounterEvent::TurnStarted {
+                actor_id,
+                actor: self.actor_name(actor_id),
+            });
+        }
+        Ok(())
     }
 
-    pub fn check_triggers(&mut self, event: &StackElementEntry, _event_type: TriggerEventType) {
+    fn roll_initiative(&mut self, actor_id: usize) {
+        let Some(actor) = self.actors.get_mut(&actor_id) else {
+            return;
+        };
+        actor.roll_initiative(&mut self.roller);
+        let modifier = actor.initiative_mod();
+        let total = actor.initiative().unwrap_or(modifier);
+        self.log(EncounterEvent::Roll {
+            actor_id,
+            actor: self.actor_name(actor_id),
+            kind: RollKind::Initiative,
+            detail: String::new(),
+            dice: vec![total - modifier],
+            modifier,
+            total,
+            target: None,
+            success: None,
+        });
+    }
+
+    pub fn check_triggers(&mut self, event: &StackElementEntry, event_type: TriggerEventType) {
         match event {
-            StackElementEntry::Prompt(_) => return,
+            StackElementEntry::Prompt(_) => (),
             StackElementEntry::Action(_a) => {
                 // TODO
             }
-            StackElementEntry::SideEffect(_se) => {
-                // TODO
+            StackElementEntry::SideEffect(se) => {
+                if !matches!(event_type, TriggerEventType::Execute) {
+                    return;
+                }
+                match se.trigger_event() {
+                    Some(TriggerEvent::Moved {
+                        actor_id,
+                        path,
+                        movement_paid,
+                    }) => {
+                        let triggered = hazards_on_path(self, actor_id, &path, movement_paid);
+                        // reversed so the first hazard on the path resolves first
+                        for side_effect in triggered.into_iter().rev() {
+                            self.enqueue_event(StackElementEntry::SideEffect(side_effect), None);
+                        }
+                    }
+                    None => (),
+                }
             }
         }
     }
@@ -572,7 +1022,7 @@ impl EncounterInstance {
         self.encounter_stack.push(StackElement {
             entry: se,
             id: self.outcome_tracker.next_id(),
-            success_dependencies: success_dependencies,
+            success_dependencies,
         });
     }
 
@@ -581,7 +1031,7 @@ impl EncounterInstance {
         match last {
             None => None,
             Some(se) => match &se.entry {
-                StackElementEntry::Prompt(p) => Some(&p),
+                StackElementEntry::Prompt(p) => Some(p),
                 _ => None,
             },
         }
@@ -591,86 +1041,300 @@ impl EncounterInstance {
         let last = self.encounter_stack.pop();
         match last {
             None => None,
-            Some(se) => match &se.entry {
+            Some(se) => match se.entry {
                 StackElementEntry::Prompt(p) => Some(p),
                 other => {
-                    self.encounter_stack.push(se);
+                    self.encounter_stack.push(StackElement {
+                        entry: other,
+                        id: se.id,
+                        success_dependencies: se.success_dependencies,
+                    });
                     None
                 }
             },
         }
     }
 
+    // answers the prompt on top of the stack, if the intent is the prompted
+    // actor's, one of the actions offered and legal as it stands
+    pub fn submit(&mut self, intent: ActionExecutionInfo) -> Result<(), EngineError> {
+        let prompt = self.peek_prompt().ok_or(EngineError::NoPrompt)?;
+        let offered = prompt
+            .actions()
+            .iter()
+            .any(|&a| std::ptr::addr_eq(a as *const dyn Action, intent.action()));
+        if intent.caster_id() != prompt.actor_id() || !offered || !intent.validate(self) {
+            return Err(EngineError::IllegalAction {
+                action: intent.action_name().to_string(),
+                caster_id: intent.caster_id(),
+            });
+        }
+        self.pop_prompt();
+        self.push_action(intent);
+        Ok(())
+    }
+
     pub fn push_action(&mut self, action_execution_info: ActionExecutionInfo) {
+        let caster_id = action_execution_info.caster_id();
+        self.log(EncounterEvent::ActionDeclared {
+            actor_id: caster_id,
+            actor: self.actor_name(caster_id),
+            action: action_execution_info.action_name().to_string(),
+            target_ids: action_execution_info.target_ids().to_vec(),
+            target_locations: action_execution_info.target_locations().to_vec(),
+        });
         // TODO: temp stack for reactions
-        self.encounter_stack
-            .push(StackElement::Action(Box::new(action_execution_info)));
+        self.enqueue_event(
+            StackElementEntry::Action(Box::new(action_execution_info)),
+            None,
+        );
     }
 
-    pub fn process_stack(&mut self) {
-        if !self.initialized {
-            panic!("attempted to run uninitialized encounter");
-        }
+    // resolves the stack up to the next prompt. An error stops resolution
+    // part way, with the element that failed dropped from the stack; going
+    // back to a snapshot from before the action is the way to recover.
+    // Initiative is rolled the first time the stack runs dry, so that
+    // deployment happens before it.
+    pub fn process_stack(&mut self) -> Result<(), EngineError> {
         // if we ever encounter something that prompts a user/AI input, we
         // should stop processing the stack
 
         // check if we are done processing the current batch of possible reactions
-        if let Some(_) = self.peek_prompt() {
+        if self.peek_prompt().is_some() {
             // exit on prompt
-            return;
+            return Ok(());
         }
         // transfer the temp queue to the stack
-        while !self.temp_encounter_queue.is_empty() {
-            self.encounter_stack.push(
-                self.temp_encounter_queue
-                    .pop_front()
-                    .expect("temp queue should not be empty"),
-            );
+        while let Some(se) = self.temp_encounter_queue.pop_front() {
+            self.encounter_stack.push(se);
         }
 
-        while !self.encounter_stack.is_empty() {
-            if let Some(_) = self.peek_prompt() {
-                return;
+        while let Some(se) = self.encounter_stack.pop() {
+            if let StackElementEntry::Prompt(_) = se.entry {
+                self.encounter_stack.push(se);
+                return Ok(());
             }
 
-            let se = self.encounter_stack.pop().expect("unexpected empty stack");
-            self.check_triggers(&se, TriggerEventType::Execute);
-            match se {
-                StackElement::Prompt(_) => {
-                    panic!("should be unreachable: prompt case")
-                }
-                StackElement::Action(a) => {
-                    let mut side_effects = a.execute(self);
-                    for sen in side_effects.drain(..) {
-                        self.enqueue_event(StackElement::SideEffect(sen));
+            if let Some(deps) = &se.success_dependencies
+                && deps
+                    .iter()
+                    .any(|d| self.outcome_tracker.get_outcome(*d) == Some(false))
+            {
+                self.outcome_tracker.set_outcome(se.id, false);
+                continue;
+            }
+            self.check_triggers(&se.entry, TriggerEventType::Execute);
+            match se.entry {
+                StackElementEntry::Prompt(_) => return Err(EngineError::UnexpectedPrompt),
+                StackElementEntry::Action(a) => {
+                    for sen in a.execute(self)? {
+                        self.enqueue_event(StackElementEntry::SideEffect(sen), Some(vec![se.id]));
                     }
                 }
-                StackElement::SideEffect(s) => {
-                    s.apply(self);
+                StackElementEntry::SideEffect(s) => {
+                    s.apply(self)?;
+                    self.check_falling()?;
                 }
             };
+            self.outcome_tracker.set_outcome(se.id, true);
         }
 
         // TODO: get the next prompt if necessary
         // the stack should contain a prompt at the top always
-        if let Some(StackElement::Prompt(_)) = self.encounter_stack.last() {
+        if self.peek_prompt().is_some() {
             // exit on prompt
-            return;
+            return Ok(());
         }
         if self.encounter_stack.is_empty() {
             self.outcome_tracker.reset();
         }
+        if !self.initialized {
+            self.initialize()?;
+        }
         let current_player_id = self
             .initiative_tracker
             .current_player()
-            .expect("empty initiative tracker");
+            .ok_or(EngineError::EmptyInitiative)?;
         let current_player = self
             .actors
             .get(&current_player_id)
-            .expect("missing player_id");
-        self.encounter_stack.push(StackElement::Prompt(Prompt::new(
+            .ok_or(EngineError::MissingActor(current_player_id))?;
+        let prompt = Prompt::new(
             current_player_id,
             current_player.actions.clone(), // TODO: filter for legal actions (action, bonus action; no reaction)
-        )));
+        );
+        self.enqueue_event(StackElementEntry::Prompt(prompt), None);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::actor_gen::SpawnLayout;
+    use crate::engine::difficulty::Difficulty;
+    use crate::engine::side_effects::DamageTerrain;
+    use crate::engine::terrain_gen::bsp::BspParams;
+    use crate::engine::test_util::{FACING_ZOMBIES, GIANT_BAT, encounter_from, play};
+
+    fn initiative_rolls(encounter: &EncounterInstance) -> usize {
+        encounter
+            .events()
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e.event,
+                    EncounterEvent::Roll {
+                        kind: RollKind::Initiative,
+                        ..
+                    }
+                )
+            })
+            .count()
+    }
+
+    #[test]
+    fn deployment_comes_before_initiative() {
+        let mut encounter = encounter_from(FACING_ZOMBIES, 0);
+        encounter.begin_deployment(&[0]).unwrap();
+
+        encounter.process_stack().unwrap();
+        let prompt = encounter.peek_prompt().unwrap().clone();
+        assert_eq!(prompt.actor_id(), 0);
+        assert!(prompt.actions().iter().any(|a| a.name() == "deploy"));
+        assert_eq!(initiative_rolls(&encounter), 0);
+
+        let keep = prompt.process_input("keep", &encounter).unwrap();
+        encounter.submit(keep).unwrap();
+        encounter.process_stack().unwrap();
+        assert_eq!(initiative_rolls(&encounter), 2);
+        assert!(encounter.begin_deployment(&[1]).is_err());
+    }
+
+    #[test]
+    fn restoring_cuts_the_log_back_without_copying_it() {
+        let mut encounter = encounter_from(FACING_ZOMBIES, 0);
+        let receiver = encounter.subscribe();
+        encounter.process_stack().unwrap();
+        let before = encounter.events().len();
+        let snapshot = encounter.snapshot();
+        assert!(snapshot.state.events().is_empty());
+
+        encounter.log(EncounterEvent::Note {
+            text: "gone again".to_string(),
+            actor_id: None,
+            location: None,
+        });
+        encounter.restore(&snapshot);
+        assert_eq!(encounter.events().len(), before);
+        // subscribers heard everything, including what was taken back
+        assert_eq!(receiver.try_iter().count(), before + 1);
+        encounter.log(EncounterEvent::Note {
+            text: "still subscribed".to_string(),
+            actor_id: None,
+            location: None,
+        });
+        assert_eq!(receiver.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn only_clone_without_log_leaves_the_log_behind() {
+        let mut encounter = encounter_from(FACING_ZOMBIES, 0);
+        let receiver = encounter.subscribe();
+        encounter.process_stack().unwrap();
+        let logged = encounter.events().len();
+        let _ = receiver.try_iter().count();
+
+        let mut copy = encounter.clone();
+        assert_eq!(copy.events().len(), logged);
+        copy.skip_turn().unwrap();
+        // a plain clone is a full copy, subscribers included
+        assert!(receiver.try_iter().count() > 0);
+
+        let mut playout = encounter.clone_without_log();
+        assert!(playout.events().is_empty());
+        playout.skip_turn().unwrap();
+        assert_eq!(receiver.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn breaking_a_wall_opens_up_the_pocket_behind_it() {
+        let map = "[map]\n......#...\n......#...\n......#...\n[creatures]\nZombie 0,0 0\n";
+        let mut encounter = encounter_from(map, 0);
+        let pocket = Coordinate::new(8, 0);
+        assert!(!encounter.can_deploy_to(0, pocket));
+
+        for y in 0..3 {
+            let wall = DamageTerrain {
+                target: Coordinate::new(6, y),
+                amount: 100,
+            };
+            wall.apply(&mut encounter).unwrap();
+        }
+        assert!(encounter.can_deploy_to(0, pocket));
+    }
+
+    #[test]
+    fn a_flyer_that_goes_down_falls() {
+        let mut encounter = encounter_from(FACING_ZOMBIES, 0);
+        let bat = encounter
+            .instantiate_creature(&GIANT_BAT, Coordinate::new(3, 0), 0, 0)
+            .unwrap();
+        let actor = encounter.actors.get_mut(&bat).unwrap();
+        actor.set_altitude(20.);
+        assert!(encounter.is_supported(bat));
+
+        let actor = encounter.actors.get_mut(&bat).unwrap();
+        actor.take_damage(1000);
+        assert_eq!(actor.fly_speed(), 0.);
+        assert!(!encounter.is_supported(bat));
+    }
+
+    // a generated dungeon with doors and hazards, played for a few turns
+    fn generated(seed: u64) -> EncounterInstance {
+        let terrain_params = TerrainGenParams::Bsp(BspParams {
+            width: 30,
+            height: 16,
+            branch_depth: 8,
+            branch_prob: 0.5,
+            n_features: 6,
+            door_prob: 0.5,
+            locked_door_prob: 0.25,
+            hazard_budget: 6,
+        });
+        let actor_params = ActorGenParams {
+            party_levels: vec![3, 3],
+            difficulty: Difficulty::Medium,
+            n_teams: 2,
+            spawn_layout: SpawnLayout::OppositeEdges,
+            min_team_distance: 30.,
+        };
+        let mut encounter =
+            EncounterInstance::from_params(&terrain_params, &actor_params, seed).unwrap();
+        play(&mut encounter, 30);
+        encounter
+    }
+
+    fn log_lines(encounter: &EncounterInstance) -> Vec<String> {
+        encounter.events().iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn the_same_seed_replays_the_same_encounter() {
+        let (a, b) = (generated(7), generated(7));
+        assert!(a.terrain == b.terrain);
+        assert!(a.actors.len() > 1);
+        let placed = |e: &EncounterInstance| -> Vec<(Coordinate, u32)> {
+            e.actors
+                .values()
+                .map(|a| (a.location(), a.hitpoints()))
+                .collect()
+        };
+        assert_eq!(placed(&a), placed(&b));
+        assert!(log_lines(&a).len() > a.actors.len());
+        assert_eq!(log_lines(&a), log_lines(&b));
+
+        let c = generated(8);
+        assert!(a.terrain != c.terrain);
     }
 }
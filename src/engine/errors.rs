@@ -1,5 +1,6 @@
 use std::fmt;
 
+use crate::engine::side_effects::Resource;
 use crate::engine::types::Coordinate;
 
 #[derive(Debug, Clone)]
@@ -55,7 +56,7 @@ pub struct NegativeAbsCoord {
 
 impl NegativeAbsCoord {
     pub fn new(coord: Coordinate) -> Self {
-        Self { coord: coord }
+        Self { coord }
     }
     pub fn coord(&self) -> Coordinate {
         self.coord
@@ -73,3 +74,180 @@ impl fmt::Display for NegativeAbsCoord {
 }
 
 impl std::error::Error for NegativeAbsCoord {}
+
+#[derive(Debug, Clone)]
+pub struct MapParseError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl MapParseError {
+    pub fn new(line: usize, column: usize, message: &str) -> Self {
+        Self {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl fmt::Display for MapParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for MapParseError {}
+
+#[derive(Debug, Clone)]
+pub struct ProfileParseError {
+    line: usize,
+    message: String,
+}
+
+impl ProfileParseError {
+    pub fn new(line: usize, message: &str) -> Self {
+        Self {
+            line,
+            message: message.to_string(),
+        }
+    }
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for ProfileParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ProfileParseError {}
+
+#[derive(Debug, Clone)]
+pub struct SaveVersionMismatch {
+    found: u32,
+    expected: u32,
+}
+
+impl SaveVersionMismatch {
+    pub fn new(found: u32, expected: u32) -> Self {
+        Self { found, expected }
+    }
+}
+
+impl fmt::Display for SaveVersionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "saved with format version {}, this build reads version {}",
+            self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for SaveVersionMismatch {}
+
+#[derive(Debug, Clone)]
+pub struct ScriptError {
+    file: String,
+    message: String,
+}
+
+impl ScriptError {
+    pub fn new(file: &str, message: &str) -> Self {
+        Self {
+            file: file.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.file, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+// something the engine was asked to do that the encounter cannot support;
+// the encounter is left as it was when the error came up, part way through
+// whatever was being resolved
+#[derive(Debug, Clone)]
+pub enum EngineError {
+    MissingActor(usize),
+    OffMap(Coordinate),
+    IllegalAction { action: String, caster_id: usize },
+    IllegalResourceConsumption { actor: String, resource: Resource },
+    AlreadyInitialized,
+    EmptyInitiative,
+    // an intent was submitted with no prompt waiting for one
+    NoPrompt,
+    // a prompt turned up where only actions and side effects should be
+    UnexpectedPrompt,
+    ActorGeneration(String),
+    // a map file that could not be read or parsed
+    MapLoad(String),
+    // no creature template goes by the name
+    UnknownTemplate(String),
+    // a scripted action failed while it was being resolved
+    Script { action: String, message: String },
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EngineError::MissingActor(actor_id) => write!(f, "no actor with id {}", actor_id),
+            EngineError::OffMap(coord) => write!(f, "{} is off the map", coord),
+            EngineError::IllegalAction { action, caster_id } => {
+                write!(f, "actor {} cannot use {} like that", caster_id, action)
+            }
+            EngineError::IllegalResourceConsumption { actor, resource } => {
+                write!(f, "{} has no {:?} left to use", actor, resource)
+            }
+            EngineError::AlreadyInitialized => {
+                write!(f, "the encounter has already been initialized")
+            }
+            EngineError::EmptyInitiative => write!(f, "nobody is in the initiative order"),
+            EngineError::NoPrompt => write!(f, "nobody is waiting to act"),
+            EngineError::UnexpectedPrompt => write!(f, "found a prompt while resolving actions"),
+            EngineError::ActorGeneration(message) => {
+                write!(f, "failed to generate actors: {}", message)
+            }
+            EngineError::MapLoad(message) => write!(f, "failed to load the map: {}", message),
+            EngineError::UnknownTemplate(name) => {
+                write!(f, "unknown creature template {:?}", name)
+            }
+            EngineError::Script { action, message } => {
+                write!(f, "the script for {} failed: {}", action, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+impl From<MapParseError> for EngineError {
+    fn from(e: MapParseError) -> Self {
+        EngineError::MapLoad(e.to_string())
+    }
+}
+
+impl From<NegativeAbsCoord> for EngineError {
+    fn from(e: NegativeAbsCoord) -> Self {
+        EngineError::OffMap(e.coord())
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::types::{Coordinate, DamageType};
+
+// what an event's roll was for
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollKind {
+    Initiative,
+    Attack,
+    Damage,
+    Healing,
+    AbilityCheck,
+    SkillCheck,
+    SavingThrow,
+}
+
+// everything that happens in an encounter, in the order it happens; the log
+// shown in the terminal is rendered from these
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EncounterEvent {
+    TurnStarted {
+        actor_id: usize,
+        actor: String,
+    },
+    TurnEnded {
+        actor_id: usize,
+        actor: String,
+    },
+    ActionDeclared {
+        actor_id: usize,
+        actor: String,
+        action: String,
+        target_ids: Vec<usize>,
+        target_locations: Vec<Coordinate>,
+    },
+    // detail names what was rolled against or for, e.g. the target of an
+    // attack or the ability of a save
+    Roll {
+        actor_id: usize,
+        actor: String,
+        kind: RollKind,
+        detail: String,
+        dice: Vec<i32>,
+        modifier: i32,
+        total: i32,
+        // the AC or DC to meet, if any
+        target: Option<i32>,
+        success: Option<bool>,
+    },
+    DamageApplied {
+        actor_id: usize,
+        actor: String,
+        amount: u32,
+        damage_type: DamageType,
+        hitpoints: u32,
+        max_hitpoints: u32,
+    },
+    Healed {
+        actor_id: usize,
+        actor: String,
+        amount: u32,
+        hitpoints: u32,
+        max_hitpoints: u32,
+    },
+    ConditionChanged {
+        actor_id: usize,
+        actor: String,
+        condition: String,
+        // false when the condition ends
+        applied: bool,
+    },
+    Died {
+        actor_id: usize,
+        actor: String,
+    },
+    // anything else worth telling, such as doors opening or hazards found.
+    // Only those who see the actor or the place it is about hear of it; a
+    // note about neither is for everyone
+    Note {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        actor_id: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        location: Option<Coordinate>,
+    },
+}
+
+fn format_dice(dice: &[i32]) -> String {
+    match dice {
+        [die] => die.to_string(),
+        _ => format!(
+            "[{}]",
+            dice.iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn outcome(success: Option<bool>, pass: &str, fail: &str) -> String {
+    match success {
+        Some(true) => format!(" - {}", pass),
+        Some(false) => format!(" - {}", fail),
+        None => String::new(),
+    }
+}
+
+impl fmt::Display for EncounterEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncounterEvent::TurnStarted { actor, .. } => write!(f, "{}'s turn", actor),
+            EncounterEvent::TurnEnded { actor, .. } => write!(f, "{} ends their turn", actor),
+            EncounterEvent::ActionDeclared {
+                actor,
+                action,
+                target_locations,
+                ..
+            } => match target_locations.last() {
+                Some(location) => write!(f, "{} uses {} at {}", actor, action, location),
+                None => write!(f, "{} uses {}", actor, action),
+            },
+            EncounterEvent::Roll {
+                actor,
+                kind,
+                detail,
+                dice,
+                modifier,
+                total,
+                target,
+                success,
+                ..
+            } => {
+                let rolled = match modifier {
+                    m if *m < 0 => format!("{} - {}", format_dice(dice), -m),
+                    m => format!("{} + {}", format_dice(dice), m),
+                };
+                let against = |label: &str| match target {
+                    Some(t) => format!(" vs {} {}", label, t),
+                    None => String::new(),
+                };
+                match kind {
+                    RollKind::Initiative => {
+                        write!(f, "{} rolls initiative: {} = {}", actor, rolled, total)
+                    }
+                    RollKind::Attack => write!(
+                        f,
+                        "{} attacks {}: {}{}{}",
+                        actor,
+                        detail,
+                        rolled,
+                        against("AC"),
+                        outcome(*success, "hit", "miss")
+                    ),
+                    RollKind::Damage => write!(
+                        f,
+                        "{} rolls {} damage: {} = {}",
+                        actor, detail, rolled, total
+                    ),
+                    RollKind::Healing => write!(
+                        f,
+                        "{} rolls healing for {}: {} = {}",
+                        actor, detail, rolled, total
+                    ),
+                    RollKind::AbilityCheck | RollKind::SkillCheck => write!(
+                        f,
+                        "{} {} check: {}{}{}",
+                        actor,
+                        detail,
+                        rolled,
+                        against("DC"),
+                        outcome(*success, "success", "failure")
+                    ),
+                    RollKind::SavingThrow => write!(
+                        f,
+                        "{} {} save: {}{}{}",
+                        actor,
+                        detail,
+                        rolled,
+                        against("DC"),
+                        outcome(*success, "success", "failure")
+                    ),
+                }
+            }
+            EncounterEvent::DamageApplied {
+                actor,
+                amount,
+                damage_type,
+                hitpoints,
+                max_hitpoints,
+                ..
+            } => write!(
+                f,
+                "{} takes {} {:?} damage ({}/{})",
+                actor, amount, damage_type, hitpoints, max_hitpoints
+            ),
+            EncounterEvent::Healed {
+                actor,
+                amount,
+                hitpoints,
+                max_hitpoints,
+                ..
+            } => write!(
+                f,
+                "{} regains {} hit points ({}/{})",
+                actor, amount, hitpoints, max_hitpoints
+            ),
+            EncounterEvent::ConditionChanged {
+                actor,
+                condition,
+                applied,
+                ..
+            } => match applied {
+                true => write!(f, "{} is {}", actor, condition),
+                false => write!(f, "{} is no longer {}", actor, condition),
+            },
+            EncounterEvent::Died { actor, .. } => write!(f, "{} dies", actor),
+            EncounterEvent::Note { text, .. } => write!(f, "{}", text),
+        }
+    }
+}
+
+// what an event is about, which decides who gets to hear of it
+pub enum EventSubject {
+    Actor(usize),
+    Place(Coordinate),
+    Everyone,
+}
+
+impl EncounterEvent {
+    pub fn subject(&self) -> EventSubject {
+        match self {
+            EncounterEvent::TurnStarted { actor_id, .. }
+            | EncounterEvent::TurnEnded { actor_id, .. }
+            | EncounterEvent::ActionDeclared { actor_id, .. }
+            | EncounterEvent::Roll { actor_id, .. }
+            | EncounterEvent::DamageApplied { actor_id, .. }
+            | EncounterEvent::Healed { actor_id, .. }
+            | EncounterEvent::ConditionChanged { actor_id, .. }
+            | EncounterEvent::Died { actor_id, .. }
+            | EncounterEvent::Note {
+                actor_id: Some(actor_id),
+                ..
+            } => EventSubject::Actor(*actor_id),
+            EncounterEvent::Note {
+                location: Some(location),
+                ..
+            } => EventSubject::Place(*location),
+            EncounterEvent::Note { .. } => EventSubject::Everyone,
+        }
+    }
+}
+
+// an event with the round it happened in
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    pub round: usize,
+    // the teams that saw it happen, if the log keeps track; see
+    // EncounterInstance::track_sight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seen_by: Option<BTreeSet<usize>>,
+    #[serde(flatten)]
+    pub event: EncounterEvent,
+}
+
+impl fmt::Display for LoggedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.event)
+    }
+}
+
+// where events go as they are logged
+#[derive(Clone, Default)]
+pub struct Subscribers(Vec<Sender<LoggedEvent>>);
+
+impl Subscribers {
+    pub fn add(&mut self) -> Receiver<LoggedEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.0.push(sender);
+        receiver
+    }
+
+    // receivers that have been dropped are let go of
+    pub fn send(&mut self, event: &LoggedEvent) {
+        self.0.retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
+
+// everything logged so far
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EventLog {
+    events: Vec<LoggedEvent>,
+    // whether each event notes who saw it; not saved, as whoever wants it
+    // asks again after loading
+    #[serde(skip)]
+    tracks_sight: bool,
+}
+
+impl EventLog {
+    pub fn events(&self) -> &[LoggedEvent] {
+        &self.events
+    }
+
+    pub fn push(&mut self, event: LoggedEvent) {
+        self.events.push(event);
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.events.truncate(len);
+    }
+
+    pub fn tracks_sight(&self) -> bool {
+        self.tracks_sight
+    }
+
+    pub fn track_sight(&mut self) {
+        self.tracks_sight = true;
+    }
+}
+
+// one JSON object per line, for reading back after the session; flushed
+// after each so that the file keeps up with a game still going on
+pub fn write_jsonl(
+    events: impl IntoIterator<Item = LoggedEvent>,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    for event in events {
+        serde_json::to_writer(&mut writer, &event)?;
+        writeln!(writer)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,366 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::combat::{roll_damage_dice, roll_saving_throw};
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::errors::EngineError;
+use crate::engine::events::EncounterEvent;
+use crate::engine::side_effects::{
+    ApplicableSideEffect, DealDamage, GiveResource, MoveActor, Resource, SavedSideEffect, set_prone,
+};
+use crate::engine::types::{AbilityScoreType, Coordinate, DamageType};
+use crate::engine::util::square_footprint;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum HazardKind {
+    Fire,
+    Acid,
+    Pit,
+    PressurePlate,
+}
+
+impl HazardKind {
+    // (dice, sides, type) dealt when the hazard goes off
+    pub fn damage(&self) -> (u8, u8, DamageType) {
+        match self {
+            HazardKind::Fire => (1, 10, DamageType::Fire),
+            HazardKind::Acid => (2, 6, DamageType::Acid),
+            // a 10 ft drop
+            HazardKind::Pit => (1, 6, DamageType::Bludgeoning),
+            HazardKind::PressurePlate => (2, 10, DamageType::Piercing),
+        }
+    }
+
+    // Dexterity save that avoids the hazard entirely
+    pub fn save_dc(&self) -> Option<i32> {
+        match self {
+            HazardKind::Pit => Some(10),
+            HazardKind::PressurePlate => Some(13),
+            _ => None,
+        }
+    }
+
+    // fire and acid also burn creatures that start their turn in them
+    pub fn triggers_at_turn_start(&self) -> bool {
+        matches!(self, HazardKind::Fire | HazardKind::Acid)
+    }
+
+    // share of a map's hazard budget one hazard of this kind takes up
+    pub fn budget_cost(&self) -> u32 {
+        match self {
+            HazardKind::Fire | HazardKind::Acid => 2,
+            HazardKind::Pit => 3,
+            HazardKind::PressurePlate => 4,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    // DC of the Perception or Investigation check that finds it; None once
+    // found, or if it was never hidden
+    pub hidden_dc: Option<i32>,
+}
+
+impl Hazard {
+    pub fn new(kind: HazardKind) -> Self {
+        Self {
+            kind,
+            hidden_dc: None,
+        }
+    }
+
+    pub fn hidden(kind: HazardKind, dc: i32) -> Self {
+        Self {
+            kind,
+            hidden_dc: Some(dc),
+        }
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden_dc.is_some()
+    }
+}
+
+// tiles with a hazard under an actor's footprint at the given location
+fn hazard_tiles_under(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    location: Coordinate,
+) -> Vec<Coordinate> {
+    let Some(actor) = encounter.actors.get(&actor_id) else {
+        return Vec::new();
+    };
+    // flyers and climbers off the ground pass over it
+    if actor.altitude() > 0. {
+        return Vec::new();
+    }
+    // squeezed actors cover less
+    let actor_width = encounter
+        .pass_width(actor_id, location)
+        .unwrap_or(actor.footprint_width());
+    square_footprint(location, actor_width)
+        .into_iter()
+        .filter(|&tile| {
+            encounter
+                .terrain_at(tile)
+                .is_some_and(|ti| ti.hazard.is_some())
+        })
+        .collect()
+}
+
+// hazards set off by moving along a path; each kind acts at most once per move
+pub fn hazards_on_path(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    path: &[Coordinate],
+    movement_paid: f32,
+) -> Vec<Box<dyn ApplicableSideEffect>> {
+    let mut seen: HashSet<HazardKind> = HashSet::new();
+    let mut side_effects: Vec<Box<dyn ApplicableSideEffect>> = Vec::new();
+    // the first position is where the move started
+    for (i, &location) in path.iter().enumerate().skip(1) {
+        for tile in hazard_tiles_under(encounter, actor_id, location) {
+            let Some(hazard) = encounter.terrain_at(tile).and_then(|ti| ti.hazard.as_ref()) else {
+                continue;
+            };
+            if seen.insert(hazard.kind) {
+                // only a pit stops the move where it is
+                let unspent_movement = if hazard.kind == HazardKind::Pit {
+                    (movement_paid - encounter.walk_cost(actor_id, &path[..=i])).max(0.)
+                } else {
+                    0.
+                };
+                side_effects.push(Box::new(TriggerHazard {
+                    actor_id,
+                    hazard_tile: tile,
+                    location,
+                    unspent_movement,
+                }));
+            }
+        }
+    }
+    side_effects
+}
+
+// fire and acid under an actor as its turn starts
+pub fn hazards_at_turn_start(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+) -> Vec<Box<dyn ApplicableSideEffect>> {
+    let Some(actor) = encounter.actors.get(&actor_id) else {
+        return Vec::new();
+    };
+    let location = actor.location();
+    let mut seen: HashSet<HazardKind> = HashSet::new();
+    let mut side_effects: Vec<Box<dyn ApplicableSideEffect>> = Vec::new();
+    for tile in hazard_tiles_under(encounter, actor_id, location) {
+        let Some(hazard) = encounter.terrain_at(tile).and_then(|ti| ti.hazard.as_ref()) else {
+            continue;
+        };
+        if hazard.kind.triggers_at_turn_start() && seen.insert(hazard.kind) {
+            side_effects.push(Box::new(TriggerHazard {
+                actor_id,
+                hazard_tile: tile,
+                location,
+                unspent_movement: 0.,
+            }));
+        }
+    }
+    side_effects
+}
+
+// reveals a hazard along with the rest of it on neighbouring tiles
+pub fn reveal_hazard(encounter: &mut EncounterInstance, coord: Coordinate) {
+    let Some(kind) = encounter
+        .terrain_at(coord)
+        .and_then(|ti| ti.hazard.as_ref())
+        .map(|h| h.kind)
+    else {
+        return;
+    };
+    let mut stack = vec![coord];
+    let mut visited: HashSet<Coordinate> = HashSet::from([coord]);
+    while let Some(tile) = stack.pop() {
+        if let Some(hazard) = encounter
+            .terrain_at_mut(tile)
+            .and_then(|ti| ti.hazard.as_mut())
+        {
+            hazard.hidden_dc = None;
+        }
+        for offset in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let next = tile + Coordinate::new(offset.0, offset.1);
+            if !visited.contains(&next)
+                && encounter
+                    .terrain_at(next)
+                    .and_then(|ti| ti.hazard.as_ref())
+                    .is_some_and(|h| h.kind == kind)
+            {
+                visited.insert(next);
+                stack.push(next);
+            }
+        }
+    }
+}
+
+// a hazard going off on an actor; location is where the actor was when it did
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriggerHazard {
+    pub actor_id: usize,
+    pub hazard_tile: Coordinate,
+    pub location: Coordinate,
+    // movement paid for the rest of the path, given back if this stops it
+    pub unspent_movement: f32,
+}
+
+impl ApplicableSideEffect for TriggerHazard {
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::TriggerHazard(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        let Some(hazard) = ei
+            .terrain_at(self.hazard_tile)
+            .and_then(|ti| ti.hazard.clone())
+        else {
+            return Ok(());
+        };
+        let Some(actor) = ei.actors.get(&self.actor_id) else {
+            return Ok(());
+        };
+        // gone up since the hazard was queued, e.g. by a reaction
+        if actor.altitude() > 0. {
+            return Ok(());
+        }
+        let actor_name = actor.name();
+        if hazard.is_hidden() {
+            ei.log(EncounterEvent::Note {
+                text: format!("{} sets off a hidden {:?}", actor_name, hazard.kind),
+                actor_id: Some(self.actor_id),
+                location: None,
+            });
+            reveal_hazard(ei, self.hazard_tile);
+        }
+
+        // the hazard is underfoot, so nothing gives cover from it
+        if let Some(dc) = hazard.kind.save_dc()
+            && roll_saving_throw(ei, None, self.actor_id, AbilityScoreType::Dexterity, dc)
+        {
+            return Ok(());
+        }
+
+        let (n_dice, sides, damage_type) = hazard.kind.damage();
+        let amount = roll_damage_dice(
+            ei,
+            self.actor_id,
+            n_dice,
+            sides,
+            format!("{:?}", hazard.kind),
+        );
+        if hazard.kind == HazardKind::Pit {
+            // the actor ends up in the pit it fell into, not past it
+            if ei.can_move_to(self.actor_id, self.location) {
+                MoveActor {
+                    actor_id: self.actor_id,
+                    target: self.location,
+                    path: Vec::new(),
+                    movement_paid: 0.,
+                }
+                .apply(ei)?;
+            }
+            if self.unspent_movement > 0. {
+                GiveResource {
+                    actor_id: self.actor_id,
+                    resource: Resource::Movement(self.unspent_movement),
+                }
+                .apply(ei)?;
+            }
+            ei.log(EncounterEvent::Note {
+                text: format!("{} falls into a pit", actor_name),
+                actor_id: Some(self.actor_id),
+                location: None,
+            });
+            set_prone(ei, self.actor_id, true)?;
+        }
+        DealDamage {
+            actor_id: self.actor_id,
+            amount,
+            damage_type,
+        }
+        .apply(ei)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::action_template::ActionExecutionInfo;
+    use crate::actions::default_actions::MOVE;
+    use crate::engine::test_util::{GIANT_BAT, encounter_from};
+
+    const MAP: &str =
+        "[map]\n..OO........\n..OO........\n[creatures]\nZombie 0,0 0\nZombie 10,0 1\n";
+
+    #[test]
+    fn a_pit_gives_back_the_movement_it_cut_short() {
+        for seed in 0..20 {
+            let mut encounter = encounter_from(MAP, seed);
+            encounter.actors.get_mut(&0).unwrap().reset_for_new_round();
+
+            let dest = Coordinate::new(6, 0);
+            let move_info = ActionExecutionInfo::new(&*MOVE, 0, None, Some(vec![dest]), None);
+            let path = encounter.find_path(0, dest).unwrap();
+            for side_effect in move_info.execute(&mut encounter).unwrap() {
+                side_effect.apply(&mut encounter).unwrap();
+            }
+            assert_eq!(encounter.actors[&0].remaining_movement(), 20. - path.cost);
+
+            let triggered = hazards_on_path(&encounter, 0, &path.tiles, path.cost);
+            assert_eq!(triggered.len(), 1);
+            for side_effect in triggered {
+                side_effect.apply(&mut encounter).unwrap();
+            }
+            let actor = &encounter.actors[&0];
+            if actor.is_prone() {
+                // stopped one step in, having paid for that step only
+                assert_eq!(actor.location(), Coordinate::new(1, 0));
+                assert_eq!(actor.remaining_movement(), 17.5);
+                return;
+            }
+            assert_eq!(actor.location(), dest);
+        }
+        panic!("no seed had the zombie fall in");
+    }
+
+    #[test]
+    fn a_flyer_passes_over_a_pit() {
+        let mut encounter = encounter_from(MAP, 0);
+        let bat = encounter
+            .instantiate_creature(&GIANT_BAT, Coordinate::new(6, 0), 0, 0)
+            .unwrap();
+        let actor = encounter.actors.get_mut(&bat).unwrap();
+        actor.set_altitude(10.);
+        let hitpoints = actor.hitpoints();
+
+        let path = encounter.find_path(bat, Coordinate::new(2, 0)).unwrap();
+        assert!(hazards_on_path(&encounter, bat, &path.tiles, path.cost).is_empty());
+        encounter.set_actor_map(bat, Coordinate::new(2, 0)).unwrap();
+        assert!(hazards_at_turn_start(&encounter, bat).is_empty());
+
+        TriggerHazard {
+            actor_id: bat,
+            hazard_tile: Coordinate::new(2, 0),
+            location: Coordinate::new(2, 0),
+            unspent_movement: 0.,
+        }
+        .apply(&mut encounter)
+        .unwrap();
+        let actor = &encounter.actors[&bat];
+        assert!(!actor.is_prone());
+        assert_eq!(actor.altitude(), 10.);
+        assert_eq!(actor.hitpoints(), hitpoints);
+        assert!(encounter.is_supported(bat));
+    }
+}
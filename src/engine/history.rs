@@ -0,0 +1,282 @@
+use crate::actions::action_template::ActionExecutionInfo;
+use crate::engine::controller::TeamControllers;
+use crate::engine::encounter::{EncounterInstance, EncounterSnapshot};
+use crate::engine::errors::EngineError;
+use crate::engine::events::{EncounterEvent, LoggedEvent};
+
+// the encounter as it was just before a prompt was answered
+struct Checkpoint {
+    round: usize,
+    actor_id: usize,
+    // the actor's first prompt of their turn
+    turn_start: bool,
+    // answered by a controller rather than by someone at the table
+    automated: bool,
+    // how much of the log had been written by then
+    events_len: usize,
+    snapshot: EncounterSnapshot,
+}
+
+// where undo and rewind go back to. Checkpoints are kept for the current
+// round, plus the last one before it that a person answered so their last
+// action can still be undone. Going back restores the dice as well, so
+// repeating an action rolls the same as it did the first time.
+#[derive(Default)]
+pub struct History {
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            checkpoints: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.checkpoints.clear();
+    }
+
+    // to be called with a prompt on top of the stack, right before it is
+    // answered; automated if a controller is about to answer it
+    pub fn record(&mut self, encounter: &EncounterInstance, automated: bool) {
+        if let Some(checkpoint) = self.checkpoint(encounter, automated) {
+            self.push(checkpoint);
+        }
+    }
+
+    // answers the prompt on top of the stack with the intent; a checkpoint
+    // is only kept if the engine takes it, so undo never lands on a prompt
+    // nobody got past
+    pub fn submit(
+        &mut self,
+        encounter: &mut EncounterInstance,
+        intent: ActionExecutionInfo,
+    ) -> Result<(), EngineError> {
+        let checkpoint = self.checkpoint(encounter, false);
+        encounter.submit(intent)?;
+        if let Some(checkpoint) = checkpoint {
+            self.push(checkpoint);
+        }
+        Ok(())
+    }
+
+    // answers the prompt on top of the stack with a line typed at the table,
+    // the same way from the terminal and over the network
+    pub fn submit_input(
+        &mut self,
+        encounter: &mut EncounterInstance,
+        input: &str,
+    ) -> Result<(), String> {
+        let prompt = encounter
+            .peek_prompt()
+            .ok_or_else(|| EngineError::NoPrompt.to_string())?;
+        let intent = prompt
+            .process_input(input.trim(), encounter)
+            .map_err(|e| e.to_string())?;
+        self.submit(encounter, intent).map_err(|e| e.to_string())
+    }
+
+    // runs the stack. An action the engine cannot resolve is taken back by
+    // going to the last checkpoint a person answered, so play carries on from
+    // before it; returns what to tell them if so, or the error if there was
+    // nothing to go back to
+    pub fn process_stack(
+        &mut self,
+        encounter: &mut EncounterInstance,
+    ) -> Result<Option<String>, EngineError> {
+        let Err(e) = encounter.process_stack() else {
+            return Ok(None);
+        };
+        let checkpoint = self.last_action().ok_or(e.clone())?;
+        self.rewind(encounter, checkpoint);
+        Ok(Some(format!("{}; the last action was taken back", e)))
+    }
+
+    // has the controllers answer the prompt on top of the stack, keeping a
+    // checkpoint if they did; returns whether an action was pushed
+    pub fn answer_prompt(
+        &mut self,
+        encounter: &mut EncounterInstance,
+        controllers: &mut TeamControllers,
+    ) -> bool {
+        let checkpoint = self.checkpoint(encounter, true);
+        let answered = controllers.answer_prompt(encounter);
+        if answered && let Some(checkpoint) = checkpoint {
+            self.push(checkpoint);
+        }
+        answered
+    }
+
+    fn checkpoint(&self, encounter: &EncounterInstance, automated: bool) -> Option<Checkpoint> {
+        let prompt = encounter.peek_prompt()?;
+        let round = encounter.round();
+        let actor_id = prompt.actor_id();
+        let turn_start = self
+            .checkpoints
+            .last()
+            .is_none_or(|c| c.round != round || c.actor_id != actor_id);
+        Some(Checkpoint {
+            round,
+            actor_id,
+            turn_start,
+            automated,
+            events_len: encounter.events().len(),
+            snapshot: encounter.snapshot(),
+        })
+    }
+
+    fn push(&mut self, checkpoint: Checkpoint) {
+        if let Some(last) = self.checkpoints.last()
+            && last.round < checkpoint.round
+        {
+            let keep = self
+                .checkpoints
+                .iter()
+                .rposition(|c| !c.automated)
+                .unwrap_or(self.checkpoints.len() - 1);
+            self.checkpoints.drain(..keep);
+            self.checkpoints.truncate(1);
+        }
+        self.checkpoints.push(checkpoint);
+    }
+
+    // the checkpoint undo goes back to: the last prompt a person answered.
+    // Going back to one a controller answered would only have it make the
+    // same choice again
+    pub fn last_action(&self) -> Option<usize> {
+        self.checkpoints.iter().rposition(|c| !c.automated)
+    }
+
+    // the checkpoint at the start of the actor's turn this round
+    pub fn turn_start(&self, encounter: &EncounterInstance, actor_id: usize) -> Option<usize> {
+        self.checkpoints
+            .iter()
+            .position(|c| c.turn_start && c.round == encounter.round() && c.actor_id == actor_id)
+    }
+
+    // everything logged since the checkpoint, i.e. what going back to it undoes
+    pub fn reverted<'a>(
+        &self,
+        encounter: &'a EncounterInstance,
+        checkpoint: usize,
+    ) -> &'a [LoggedEvent] {
+        let start = self.checkpoints[checkpoint]
+            .events_len
+            .min(encounter.events().len());
+        &encounter.events()[start..]
+    }
+
+    // a line describing what going back to the checkpoint undoes
+    pub fn describe(&self, encounter: &EncounterInstance, checkpoint: usize) -> String {
+        let reverted = self.reverted(encounter, checkpoint);
+        let actions: Vec<String> = reverted
+            .iter()
+            .filter(|e| matches!(e.event, EncounterEvent::ActionDeclared { .. }))
+            .map(|e| e.to_string())
+            .collect();
+        format!(
+            "reverts {} action(s) and {} log entries: {}",
+            actions.len(),
+            reverted.len(),
+            actions.join("; ")
+        )
+    }
+
+    // puts the encounter back to the checkpoint; it and everything after it
+    // are forgotten, as they get recorded again when play goes on
+    pub fn rewind(&mut self, encounter: &mut EncounterInstance, checkpoint: usize) {
+        encounter.restore(&self.checkpoints[checkpoint].snapshot);
+        self.checkpoints.truncate(checkpoint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::controller::pass_action;
+    use crate::engine::encounter::StackElementEntry;
+    use crate::engine::prompt::Prompt;
+    use crate::engine::side_effects::SetDoorState;
+    use crate::engine::terrain::DoorState;
+    use crate::engine::test_util::{FACING_ZOMBIES, encounter_from};
+    use crate::engine::types::Coordinate;
+
+    fn pass(encounter: &mut EncounterInstance) {
+        let prompt = encounter.peek_prompt().unwrap().clone();
+        encounter.submit(pass_action(&prompt).unwrap()).unwrap();
+        encounter.process_stack().unwrap();
+    }
+
+    #[test]
+    fn undo_skips_what_the_computer_answered() {
+        let mut encounter = encounter_from(FACING_ZOMBIES, 0);
+        let mut history = History::new();
+        encounter.process_stack().unwrap();
+        let first = encounter.peek_prompt().unwrap().actor_id();
+
+        history.record(&encounter, false);
+        pass(&mut encounter);
+        history.record(&encounter, true);
+        pass(&mut encounter);
+        history.record(&encounter, true);
+        pass(&mut encounter);
+
+        let checkpoint = history.last_action().unwrap();
+        assert_eq!(checkpoint, 0);
+        history.rewind(&mut encounter, checkpoint);
+        assert_eq!(encounter.peek_prompt().unwrap().actor_id(), first);
+        assert_eq!(history.last_action(), None);
+    }
+
+    #[test]
+    fn a_rejected_intent_leaves_no_checkpoint() {
+        let mut encounter = encounter_from(FACING_ZOMBIES, 0);
+        let mut history = History::new();
+        encounter.process_stack().unwrap();
+        let prompt = encounter.peek_prompt().unwrap().clone();
+        let other = *encounter
+            .actors
+            .keys()
+            .find(|&&id| id != prompt.actor_id())
+            .unwrap();
+
+        let wrong_actor = pass_action(&Prompt::new(other, prompt.actions().clone())).unwrap();
+        assert!(history.submit(&mut encounter, wrong_actor).is_err());
+        assert_eq!(history.last_action(), None);
+
+        history
+            .submit(&mut encounter, pass_action(&prompt).unwrap())
+            .unwrap();
+        assert_eq!(history.last_action(), Some(0));
+    }
+
+    #[test]
+    fn an_action_the_engine_cannot_resolve_is_taken_back() {
+        let mut encounter = encounter_from(FACING_ZOMBIES, 0);
+        let mut history = History::new();
+        assert!(history.process_stack(&mut encounter).unwrap().is_none());
+        let first = encounter.peek_prompt().unwrap().actor_id();
+
+        history.submit_input(&mut encounter, "skip").unwrap();
+        let broken = SetDoorState {
+            target: Coordinate::new(-1, 0),
+            state: DoorState::Open,
+        };
+        encounter.enqueue_event(StackElementEntry::SideEffect(Box::new(broken)), None);
+        let taken_back = history.process_stack(&mut encounter).unwrap().unwrap();
+        assert!(taken_back.ends_with("the last action was taken back"));
+        assert_eq!(encounter.peek_prompt().unwrap().actor_id(), first);
+
+        // with nothing left to go back to, the error is passed on
+        let broken = SetDoorState {
+            target: Coordinate::new(-1, 0),
+            state: DoorState::Open,
+        };
+        encounter.enqueue_event(StackElementEntry::SideEffect(Box::new(broken)), None);
+        assert!(matches!(
+            history.process_stack(&mut encounter),
+            Err(EngineError::OffMap(_))
+        ));
+    }
+}
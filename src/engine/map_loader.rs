@@ -0,0 +1,485 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::actors::actor_template::CreatureTemplate;
+use crate::actors::creatures::creature_template_by_name;
+use crate::engine::errors::{EngineError, MapParseError};
+use crate::engine::hazards::{Hazard, HazardKind};
+use crate::engine::terrain::{DoorState, TerrainInfo, TerrainType};
+use crate::engine::types::Coordinate;
+use crate::engine::util::{get_tiles_from_size, hazard_glyph, parse_coord, terrain_glyph};
+
+// A map file is split into sections by header lines:
+//
+//   [legend]     optional, one "<glyph> <terrain>" per line, e.g. "T tree";
+//                hazards like "fire" or "hidden_pit" sit on floor
+//   [map]        the rows of the map, top row first, as render_map draws them
+//   [creatures]  optional, one "<template> <x>,<y> <team> [name]" per line
+//
+// Glyphs default to the ones render_map uses, and the digits 0-9 mark floor
+// tiles belonging to that team's spawn zone. Outside the map section blank
+// lines and lines starting with '#' are ignored. Inside it every line up to
+// the next header is a row, so a row of ' ' is empty terrain; only empty
+// lines before the first row and after the last are left out.
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Preamble,
+    Legend,
+    Map,
+    Creatures,
+}
+
+pub struct PlacedCreature {
+    pub template: &'static CreatureTemplate,
+    pub location: Coordinate,
+    pub team_id: usize,
+    pub name: Option<String>,
+}
+
+pub struct MapDefinition {
+    pub width: usize,
+    pub height: usize,
+    pub terrain: Vec<TerrainInfo>,
+    pub spawn_zones: BTreeMap<usize, Vec<Coordinate>>,
+    pub creatures: Vec<PlacedCreature>,
+}
+
+const LEGEND_TERRAIN: [TerrainType; 14] = [
+    TerrainType::Empty,
+    TerrainType::Floor,
+    TerrainType::Wall,
+    TerrainType::Door(DoorState::Open),
+    TerrainType::Door(DoorState::Locked),
+    // closed doors share the '+' glyph with locked ones and take precedence
+    TerrainType::Door(DoorState::Closed),
+    TerrainType::Tree,
+    TerrainType::Boulder,
+    TerrainType::Bridge,
+    TerrainType::Rubble,
+    TerrainType::Undergrowth,
+    TerrainType::ShallowWater,
+    TerrainType::DeepWater,
+    TerrainType::Cliff,
+];
+
+const LEGEND_HAZARDS: [HazardKind; 4] = [
+    HazardKind::Fire,
+    HazardKind::Acid,
+    HazardKind::Pit,
+    HazardKind::PressurePlate,
+];
+
+// DC to find hazards marked hidden in the legend
+const HIDDEN_HAZARD_DC: i32 = 15;
+
+fn hazard_tile(hazard: Hazard) -> TerrainInfo {
+    let mut ti = TerrainInfo::new(TerrainType::Floor);
+    ti.hazard = Some(hazard);
+    ti
+}
+
+fn default_legend() -> HashMap<char, TerrainInfo> {
+    let mut legend = HashMap::new();
+    for tt in LEGEND_TERRAIN {
+        legend.insert(terrain_glyph(&tt), TerrainInfo::new(tt));
+    }
+    for kind in LEGEND_HAZARDS {
+        legend.insert(hazard_glyph(kind), hazard_tile(Hazard::new(kind)));
+    }
+    // plain ascii stand-ins for the box drawing glyphs
+    legend.insert('.', TerrainInfo::new(TerrainType::Floor));
+    legend.insert('#', TerrainInfo::new(TerrainType::Wall));
+    legend
+}
+
+fn tile_from_name(name: &str) -> Option<TerrainInfo> {
+    let name = name.to_ascii_lowercase();
+    let hazard = match name.as_str() {
+        "fire" => Some(Hazard::new(HazardKind::Fire)),
+        "acid" => Some(Hazard::new(HazardKind::Acid)),
+        "pit" => Some(Hazard::new(HazardKind::Pit)),
+        "pressure_plate" => Some(Hazard::new(HazardKind::PressurePlate)),
+        "hidden_pit" => Some(Hazard::hidden(HazardKind::Pit, HIDDEN_HAZARD_DC)),
+        "hidden_pressure_plate" => {
+            Some(Hazard::hidden(HazardKind::PressurePlate, HIDDEN_HAZARD_DC))
+        }
+        _ => None,
+    };
+    if let Some(hazard) = hazard {
+        return Some(hazard_tile(hazard));
+    }
+
+    let tt = match name.as_str() {
+        "empty" => TerrainType::Empty,
+        "floor" => TerrainType::Floor,
+        "wall" => TerrainType::Wall,
+        "open_door" => TerrainType::Door(DoorState::Open),
+        "door" | "closed_door" => TerrainType::Door(DoorState::Closed),
+        "locked_door" => TerrainType::Door(DoorState::Locked),
+        "tree" => TerrainType::Tree,
+        "boulder" => TerrainType::Boulder,
+        "bridge" => TerrainType::Bridge,
+        "rubble" => TerrainType::Rubble,
+        "undergrowth" => TerrainType::Undergrowth,
+        "shallow_water" => TerrainType::ShallowWater,
+        "deep_water" => TerrainType::DeepWater,
+        "cliff" => TerrainType::Cliff,
+        _ => return None,
+    };
+    Some(TerrainInfo::new(tt))
+}
+
+// whitespace separated tokens with their 1-based column
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    for (column, (byte, c)) in line.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (true, Some((col, b))) => {
+                tokens.push((col, &line[b..byte]));
+                start = None;
+            }
+            (false, None) => start = Some((column + 1, byte)),
+            _ => {}
+        }
+    }
+    if let Some((col, b)) = start {
+        tokens.push((col, &line[b..]));
+    }
+    tokens
+}
+
+fn parse_legend_line(
+    line: &str,
+    line_n: usize,
+    legend: &mut HashMap<char, TerrainInfo>,
+) -> Result<(), MapParseError> {
+    let tokens = tokenize(line);
+    let (_, glyph) = tokens[0];
+    let mut chars = glyph.chars();
+    let c = chars.next().unwrap();
+    if chars.next().is_some() {
+        return Err(MapParseError::new(
+            line_n,
+            1,
+            "legend glyph must be a single character",
+        ));
+    }
+    if c.is_ascii_digit() {
+        return Err(MapParseError::new(
+            line_n,
+            1,
+            "digits are reserved for spawn zones",
+        ));
+    }
+    let Some(&(column, name)) = tokens.get(1) else {
+        return Err(MapParseError::new(
+            line_n,
+            line.chars().count() + 1,
+            "expected a terrain name",
+        ));
+    };
+    let Some(ti) = tile_from_name(name) else {
+        return Err(MapParseError::new(
+            line_n,
+            column,
+            &format!("unknown terrain {:?}", name),
+        ));
+    };
+    legend.insert(c, ti);
+    Ok(())
+}
+
+struct CreatureLine {
+    line_n: usize,
+    coord_column: usize,
+    creature: PlacedCreature,
+}
+
+fn parse_creature_line(line: &str, line_n: usize) -> Result<CreatureLine, MapParseError> {
+    let tokens = tokenize(line);
+    let (_, template_name) = tokens[0];
+    let template = creature_template_by_name(template_name)
+        .map_err(|e| MapParseError::new(line_n, tokens[0].0, &e.to_string()))?;
+    let end = line.chars().count() + 1;
+
+    let Some(&(coord_column, coord_str)) = tokens.get(1) else {
+        return Err(MapParseError::new(line_n, end, "expected a coordinate x,y"));
+    };
+    let Some(location) = parse_coord(coord_str, Coordinate::new(0, 0))
+        .filter(|_| !coord_str.starts_with(['l', 'r']))
+    else {
+        return Err(MapParseError::new(
+            line_n,
+            coord_column,
+            &format!("invalid coordinate {:?}; expected x,y", coord_str),
+        ));
+    };
+
+    let Some(&(team_column, team_str)) = tokens.get(2) else {
+        return Err(MapParseError::new(line_n, end, "expected a team number"));
+    };
+    let Ok(team_id) = team_str.parse::<usize>() else {
+        return Err(MapParseError::new(
+            line_n,
+            team_column,
+            &format!("invalid team {:?}", team_str),
+        ));
+    };
+
+    // everything after the team is the creature's name
+    let name = tokens.get(3).map(|&(column, _)| {
+        line.chars()
+            .skip(column - 1)
+            .collect::<String>()
+            .trim()
+            .to_string()
+    });
+
+    Ok(CreatureLine {
+        line_n,
+        coord_column,
+        creature: PlacedCreature {
+            template,
+            location,
+            team_id,
+            name,
+        },
+    })
+}
+
+fn validate_creatures(
+    creature_lines: &[CreatureLine],
+    width: usize,
+    height: usize,
+    terrain: &[TerrainInfo],
+) -> Result<(), MapParseError> {
+    let mut occupied: Vec<bool> = vec![false; width * height];
+    for cl in creature_lines {
+        let creature = &cl.creature;
+        let actor_width = get_tiles_from_size(creature.template.size);
+        for x_off in 0..actor_width {
+            for y_off in 0..actor_width {
+                let x = creature.location.x as usize + x_off;
+                let y = creature.location.y as usize + y_off;
+                if x >= width || y >= height {
+                    return Err(MapParseError::new(
+                        cl.line_n,
+                        cl.coord_column,
+                        &format!(
+                            "{} at {} does not fit on the {}x{} map",
+                            creature.template.name, creature.location, width, height
+                        ),
+                    ));
+                }
+                let i = x + y * width;
+                if !terrain[i].terrain_type.is_passable() {
+                    return Err(MapParseError::new(
+                        cl.line_n,
+                        cl.coord_column,
+                        &format!(
+                            "{} at {} is placed on impassable terrain",
+                            creature.template.name, creature.location
+                        ),
+                    ));
+                }
+                if occupied[i] {
+                    return Err(MapParseError::new(
+                        cl.line_n,
+                        cl.coord_column,
+                        &format!(
+                            "{} at {} overlaps another creature",
+                            creature.template.name, creature.location
+                        ),
+                    ));
+                }
+                occupied[i] = true;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn parse_map(text: &str) -> Result<MapDefinition, MapParseError> {
+    let mut legend = default_legend();
+    let mut section = Section::Preamble;
+    // (line number, row) as they appear in the file
+    let mut rows: Vec<(usize, &str)> = Vec::new();
+    let mut creature_lines: Vec<CreatureLine> = Vec::new();
+
+    for (i, raw_line) in text.lines().enumerate() {
+        let line_n = i + 1;
+        let trimmed = raw_line.trim();
+        let header = match trimmed {
+            "[legend]" => Some(Section::Legend),
+            "[map]" => Some(Section::Map),
+            "[creatures]" => Some(Section::Creatures),
+            _ => None,
+        };
+        if let Some(next) = header {
+            if next == Section::Map && !rows.is_empty() {
+                return Err(MapParseError::new(line_n, 1, "duplicate [map] section"));
+            }
+            section = next;
+            continue;
+        }
+        // map rows keep their whitespace since ' ' is empty terrain
+        if section != Section::Map && (trimmed.is_empty() || trimmed.starts_with('#')) {
+            continue;
+        }
+
+        match section {
+            Section::Preamble => {
+                return Err(MapParseError::new(
+                    line_n,
+                    1,
+                    "expected a [legend], [map] or [creatures] section",
+                ));
+            }
+            Section::Legend => parse_legend_line(trimmed, line_n, &mut legend)?,
+            Section::Map => rows.push((line_n, raw_line.trim_end_matches('\r'))),
+            Section::Creatures => creature_lines.push(parse_creature_line(trimmed, line_n)?),
+        }
+    }
+
+    // blank lines around the rows only separate the sections
+    while rows.last().is_some_and(|(_, row)| row.is_empty()) {
+        rows.pop();
+    }
+    let n_leading = rows.iter().take_while(|(_, row)| row.is_empty()).count();
+    rows.drain(..n_leading);
+
+    let Some(&(first_line_n, first_row)) = rows.first() else {
+        return Err(MapParseError::new(
+            text.lines().count().max(1),
+            1,
+            "missing [map] section",
+        ));
+    };
+    let width = first_row.chars().count();
+    let height = rows.len();
+    let mut terrain: Vec<TerrainInfo> = vec![TerrainInfo::new(TerrainType::Empty); width * height];
+    let mut spawn_zones: BTreeMap<usize, Vec<Coordinate>> = BTreeMap::new();
+
+    for (row_i, &(line_n, row)) in rows.iter().enumerate() {
+        let row_width = row.chars().count();
+        if row_width != width {
+            return Err(MapParseError::new(
+                line_n,
+                row_width.min(width) + 1,
+                &format!(
+                    "row is {} tiles wide but the first row (line {}) is {}",
+                    row_width, first_line_n, width
+                ),
+            ));
+        }
+        // the first row is the top of the map
+        let y = height - 1 - row_i;
+        for (x, c) in row.chars().enumerate() {
+            let ti = if let Some(team_id) = c.to_digit(10) {
+                spawn_zones
+                    .entry(team_id as usize)
+                    .or_default()
+                    .push(Coordinate::new(x as isize, y as isize));
+                TerrainInfo::new(TerrainType::Floor)
+            } else if let Some(ti) = legend.get(&c) {
+                ti.clone()
+            } else {
+                return Err(MapParseError::new(
+                    line_n,
+                    x + 1,
+                    &format!("unknown glyph {:?}", c),
+                ));
+            };
+            terrain[x + y * width] = ti;
+        }
+    }
+    validate_creatures(&creature_lines, width, height, &terrain)?;
+
+    Ok(MapDefinition {
+        width,
+        height,
+        terrain,
+        spawn_zones,
+        creatures: creature_lines.into_iter().map(|cl| cl.creature).collect(),
+    })
+}
+
+pub fn load_map(path: &str) -> Result<MapDefinition, EngineError> {
+    let text = std::fs::read_to_string(path).map_err(|e| EngineError::MapLoad(e.to_string()))?;
+    Ok(parse_map(&text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_at(text: &str) -> (usize, usize) {
+        match parse_map(text) {
+            Ok(_) => panic!("expected an error"),
+            Err(e) => (e.line(), e.column()),
+        }
+    }
+
+    #[test]
+    fn rows_are_read_top_first() {
+        let map = parse_map("[map]\n###\n#0#\n###\n").unwrap();
+        assert_eq!((map.width, map.height), (3, 3));
+        assert_eq!(map.spawn_zones[&0], vec![Coordinate::new(1, 1)]);
+        assert!(map.terrain[0].terrain_type == TerrainType::Wall);
+        assert!(map.terrain[1 + 3].terrain_type == TerrainType::Floor);
+    }
+
+    #[test]
+    fn blank_rows_inside_the_map_are_kept() {
+        let map = parse_map("[map]\n\n###\n   \n###\n\n[creatures]\n").unwrap();
+        assert_eq!((map.width, map.height), (3, 3));
+        assert!(map.terrain[3].terrain_type == TerrainType::Empty);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_outside_the_map_are_ignored() {
+        let map = parse_map("# a comment\n\n[legend]\n# more\nT tree\n\n[map]\nT.\n").unwrap();
+        assert!(map.terrain[0].terrain_type == TerrainType::Tree);
+    }
+
+    #[test]
+    fn errors_point_at_the_line_and_column() {
+        assert_eq!(error_at("oops\n[map]\n#\n"), (1, 1));
+        assert_eq!(error_at("[map]\n###\n#?#\n"), (3, 2));
+        assert_eq!(error_at("[map]\n###\n##\n"), (3, 3));
+        assert_eq!(error_at("[legend]\nT tree\n"), (2, 1));
+        assert_eq!(error_at("[map]\n#\n[map]\n#\n"), (3, 1));
+    }
+
+    #[test]
+    fn the_bundled_map_loads() {
+        let map = parse_map(include_str!("../../maps/crossroads.map")).unwrap();
+        assert_eq!((map.width, map.height), (40, 15));
+        assert_eq!(map.creatures.len(), 3);
+    }
+
+    #[test]
+    fn creatures_must_fit_on_the_map() {
+        let map = "[map]\n......\n......\n......\n[creatures]\n";
+        assert!(parse_map(&format!("{}Zombie 0,0 0\nZombie 2,0 1\n", map)).is_ok());
+        assert_eq!(
+            error_at(&format!("{}Zombie 0,0 0\nZombie 1,0 1\n", map)).0,
+            7
+        );
+        assert_eq!(error_at(&format!("{}Zombie 5,0 0\n", map)).0, 6);
+        assert_eq!(error_at(&format!("{}Lich 0,0 0\n", map)).0, 6);
+    }
+
+    #[test]
+    fn load_failures_are_engine_errors() {
+        assert!(matches!(
+            load_map("maps/no_such.map"),
+            Err(EngineError::MapLoad(_))
+        ));
+        assert!(matches!(
+            creature_template_by_name("Lich"),
+            Err(EngineError::UnknownTemplate(name)) if name == "Lich"
+        ));
+    }
+}
@@ -1,12 +1,26 @@
 pub mod action_overrides;
 pub mod actor_gen;
+pub mod combat;
+pub mod controller;
+pub mod dice_roller;
+pub mod difficulty;
 pub mod encounter;
 pub mod errors;
+pub mod events;
+pub mod hazards;
+pub mod history;
+pub mod map_loader;
+pub mod pathfinding;
 pub mod prompt;
 pub mod roll_event;
+pub mod save;
 pub mod side_effects;
+pub mod spatial_index;
 pub mod terrain;
 pub mod terrain_gen;
+#[cfg(test)]
+pub mod test_util;
 pub mod triggers;
 pub mod types;
 pub mod util;
+pub mod visibility;
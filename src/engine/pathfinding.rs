@@ -0,0 +1,369 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::types::Coordinate;
+use crate::engine::util::FEET_PER_TILE;
+
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum DiagonalRule {
+    // every diagonal step costs the same as an orthogonal one
+    #[default]
+    Uniform,
+    // every second diagonal step costs double (5/10 ft variant)
+    Alternating,
+}
+
+impl DiagonalRule {
+    // as given on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "uniform" => Some(DiagonalRule::Uniform),
+            "alternating" => Some(DiagonalRule::Alternating),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Path {
+    // includes the start and destination tiles
+    pub tiles: Vec<Coordinate>,
+    pub cost: f32,
+}
+
+// search node; parity tracks whether the next diagonal is the expensive one
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct PathNode {
+    coord: Coordinate,
+    parity: bool,
+}
+
+#[derive(PartialEq, Eq)]
+struct OpenElement {
+    node: PathNode,
+    cost: u32,
+    estimate: u32,
+}
+
+impl Ord for OpenElement {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed so that the BinaryHeap pops the cheapest estimate first
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| other.cost.cmp(&self.cost))
+    }
+}
+
+impl PartialOrd for OpenElement {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn heuristic(c1: Coordinate, c2: Coordinate) -> u32 {
+    let diff = c1 - c2;
+    diff.x.unsigned_abs().max(diff.y.unsigned_abs()) as u32
+}
+
+fn step_cost(rule: DiagonalRule, diagonal: bool, parity: bool) -> (u32, bool) {
+    if !diagonal {
+        return (1, parity);
+    }
+    match rule {
+        DiagonalRule::Uniform => (1, parity),
+        DiagonalRule::Alternating => (if parity { 2 } else { 1 }, !parity),
+    }
+}
+
+// a value per search node, kept flat as most nodes get one; every node a
+// search reaches is on the map
+struct NodeMap<T> {
+    width: usize,
+    values: Vec<Option<T>>,
+}
+
+impl<T: Copy> NodeMap<T> {
+    fn new(encounter: &EncounterInstance) -> Self {
+        Self {
+            width: encounter.width,
+            values: vec![None; encounter.width * encounter.height * 2],
+        }
+    }
+
+    fn idx(&self, node: &PathNode) -> usize {
+        (node.coord.x as usize + node.coord.y as usize * self.width) * 2 + node.parity as usize
+    }
+
+    fn get(&self, node: &PathNode) -> Option<T> {
+        self.values[self.idx(node)]
+    }
+
+    fn insert(&mut self, node: PathNode, value: T) {
+        let i = self.idx(&node);
+        self.values[i] = Some(value);
+    }
+}
+
+// whether the actor can pass each tile and at what cost multiplier, worked
+// out the first time a search asks; a search looks at most tiles several
+// times over, from every side and for every diagonal around them
+struct StepCache {
+    width: usize,
+    // None until looked up, then the multiplier if the tile can be passed
+    tiles: Vec<Option<Option<u32>>>,
+}
+
+impl StepCache {
+    fn new(encounter: &EncounterInstance) -> Self {
+        Self {
+            width: encounter.width,
+            tiles: vec![None; encounter.width * encounter.height],
+        }
+    }
+
+    fn multiplier(
+        &mut self,
+        encounter: &EncounterInstance,
+        actor_id: usize,
+        coord: Coordinate,
+    ) -> Option<u32> {
+        if !encounter.in_bounds(coord) {
+            return None;
+        }
+        let i = coord.x as usize + coord.y as usize * self.width;
+        *self.tiles[i].get_or_insert_with(|| {
+            encounter
+                .can_pass_through(actor_id, coord)
+                .then(|| encounter.movement_multiplier(actor_id, coord))
+        })
+    }
+}
+
+// positions one step on from node, with the cost of the step in tiles
+fn neighbors(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    rule: DiagonalRule,
+    node: PathNode,
+    cache: &mut StepCache,
+) -> Vec<(PathNode, u32)> {
+    let mut result: Vec<(PathNode, u32)> = Vec::new();
+    for (dx, dy) in NEIGHBOR_OFFSETS {
+        let next_coord = node.coord + Coordinate::new(dx, dy);
+        let Some(multiplier) = cache.multiplier(encounter, actor_id, next_coord) else {
+            continue;
+        };
+        let diagonal = dx != 0 && dy != 0;
+        // no cutting corners around walls or hostile creatures
+        if diagonal
+            && (cache
+                .multiplier(encounter, actor_id, node.coord + Coordinate::new(dx, 0))
+                .is_none()
+                || cache
+                    .multiplier(encounter, actor_id, node.coord + Coordinate::new(0, dy))
+                    .is_none())
+        {
+            continue;
+        }
+
+        let (step, parity) = step_cost(rule, diagonal, node.parity);
+        let next = PathNode {
+            coord: next_coord,
+            parity,
+        };
+        result.push((next, step * multiplier));
+    }
+    result
+}
+
+pub fn find_path(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    dest: Coordinate,
+    rule: DiagonalRule,
+) -> Option<Path> {
+    let start = encounter.actors.get(&actor_id)?.location();
+    if !encounter.can_move_to(actor_id, dest) {
+        return None;
+    }
+
+    let start_node = PathNode {
+        coord: start,
+        parity: false,
+    };
+    let mut cache = StepCache::new(encounter);
+    let mut open: BinaryHeap<OpenElement> = BinaryHeap::new();
+    let mut best_cost: NodeMap<u32> = NodeMap::new(encounter);
+    let mut came_from: NodeMap<PathNode> = NodeMap::new(encounter);
+
+    best_cost.insert(start_node, 0);
+    open.push(OpenElement {
+        node: start_node,
+        cost: 0,
+        estimate: heuristic(start, dest),
+    });
+
+    while let Some(OpenElement { node, cost, .. }) = open.pop() {
+        if node.coord == dest {
+            let mut tiles: Vec<Coordinate> = vec![node.coord];
+            let mut curr = node;
+            while let Some(prev) = came_from.get(&curr) {
+                tiles.push(prev.coord);
+                curr = prev;
+            }
+            tiles.reverse();
+            return Some(Path {
+                tiles,
+                cost: cost as f32 * FEET_PER_TILE,
+            });
+        }
+        if best_cost.get(&node).is_some_and(|c| c < cost) {
+            continue;
+        }
+
+        for (next, step) in neighbors(encounter, actor_id, rule, node, &mut cache) {
+            let next_cost = cost + step;
+            if best_cost.get(&next).is_some_and(|c| c <= next_cost) {
+                continue;
+            }
+            best_cost.insert(next, next_cost);
+            came_from.insert(next, node);
+            open.push(OpenElement {
+                node: next,
+                cost: next_cost,
+                estimate: next_cost + heuristic(next.coord, dest),
+            });
+        }
+    }
+    None
+}
+
+// cost in feet of walking through the given positions in order, as
+// find_path would have charged for them
+pub fn walk_cost(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    tiles: &[Coordinate],
+    rule: DiagonalRule,
+) -> f32 {
+    let mut parity = false;
+    let mut cost: u32 = 0;
+    for step in tiles.windows(2) {
+        let diff = step[1] - step[0];
+        let (tiles_cost, next_parity) = step_cost(rule, diff.x != 0 && diff.y != 0, parity);
+        parity = next_parity;
+        cost += tiles_cost * encounter.movement_multiplier(actor_id, step[1]);
+    }
+    cost as f32 * FEET_PER_TILE
+}
+
+// cheapest cost in feet to every position the actor can pass through within
+// max_cost; ending a move there still needs can_move_to
+pub fn reachable(
+    encounter: &EncounterInstance,
+    actor_id: usize,
+    max_cost: f32,
+    rule: DiagonalRule,
+) -> HashMap<Coordinate, f32> {
+    let mut costs: HashMap<Coordinate, f32> = HashMap::new();
+    let Some(actor) = encounter.actors.get(&actor_id) else {
+        return costs;
+    };
+    let start_node = PathNode {
+        coord: actor.location(),
+        parity: false,
+    };
+    let max_tiles = (max_cost / FEET_PER_TILE).floor() as u32;
+    let mut cache = StepCache::new(encounter);
+    let mut open: BinaryHeap<OpenElement> = BinaryHeap::new();
+    let mut best_cost: NodeMap<u32> = NodeMap::new(encounter);
+    best_cost.insert(start_node, 0);
+    open.push(OpenElement {
+        node: start_node,
+        cost: 0,
+        estimate: 0,
+    });
+
+    while let Some(OpenElement { node, cost, .. }) = open.pop() {
+        if best_cost.get(&node).is_some_and(|c| c < cost) {
+            continue;
+        }
+        let feet = cost as f32 * FEET_PER_TILE;
+        costs
+            .entry(node.coord)
+            .and_modify(|c| *c = c.min(feet))
+            .or_insert(feet);
+
+        for (next, step) in neighbors(encounter, actor_id, rule, node, &mut cache) {
+            let next_cost = cost + step;
+            if next_cost > max_tiles || best_cost.get(&next).is_some_and(|c| c <= next_cost) {
+                continue;
+            }
+            best_cost.insert(next, next_cost);
+            open.push(OpenElement {
+                node: next,
+                cost: next_cost,
+                estimate: next_cost,
+            });
+        }
+    }
+    costs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_util::encounter_from;
+
+    const OPEN_FIELD: &str = "[map]\n..........\n..........\n..........\n..........\n..........\n..........\n..........\n..........\n[creatures]\nZombie 0,0 0\n";
+
+    fn cost_to(encounter: &EncounterInstance, x: isize, y: isize) -> f32 {
+        encounter.find_path(0, Coordinate::new(x, y)).unwrap().cost / FEET_PER_TILE
+    }
+
+    #[test]
+    fn every_second_diagonal_costs_double_when_alternating() {
+        let mut encounter = encounter_from(OPEN_FIELD, 0);
+        assert_eq!(cost_to(&encounter, 4, 4), 4.);
+        assert_eq!(cost_to(&encounter, 4, 2), 4.);
+
+        encounter.set_diagonal_rule(DiagonalRule::Alternating);
+        assert_eq!(cost_to(&encounter, 4, 0), 4.);
+        assert_eq!(cost_to(&encounter, 1, 1), 1.);
+        assert_eq!(cost_to(&encounter, 2, 2), 3.);
+        assert_eq!(cost_to(&encounter, 4, 4), 6.);
+        // two diagonals and two straight steps, whatever the order
+        assert_eq!(cost_to(&encounter, 4, 2), 5.);
+    }
+
+    #[test]
+    fn alternating_paths_cost_what_walking_them_costs() {
+        let mut encounter = encounter_from(OPEN_FIELD, 0);
+        encounter.set_diagonal_rule(DiagonalRule::Alternating);
+        let dest = Coordinate::new(7, 5);
+        let path = encounter.find_path(0, dest).unwrap();
+        assert_eq!(path.tiles.first(), Some(&Coordinate::new(0, 0)));
+        assert_eq!(path.tiles.last(), Some(&dest));
+        assert_eq!(encounter.walk_cost(0, &path.tiles), path.cost);
+
+        let reachable = encounter.reachable(0, path.cost);
+        assert_eq!(reachable.get(&dest), Some(&path.cost));
+        let short = encounter.reachable(0, path.cost - FEET_PER_TILE);
+        assert!(!short.contains_key(&dest));
+    }
+}
@@ -1,14 +1,22 @@
 use std::collections::LinkedList;
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     actions::action_template::{Action, ActionExecutionInfo},
+    actions::registry::action_refs,
     engine::{
-        encounter::EncounterInstance, errors::ParseError, types::Coordinate, util::parse_coord,
+        encounter::EncounterInstance,
+        errors::ParseError,
+        types::Coordinate,
+        util::{actor_char_count, parse_actor_char, parse_coord, tile_center_dist},
     },
 };
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Prompt {
     actor_id: usize,
+    #[serde(with = "action_refs")]
     actions: Vec<&'static (dyn Action + Send + Sync)>,
 }
 
@@ -25,7 +33,8 @@ impl Prompt {
         &self.actions
     }
 
-    pub fn process_input(
+    // builds the action from the input without checking that it is legal
+    fn parse_input(
         &self,
         input: &str,
         encounter_instance: &EncounterInstance,
@@ -33,12 +42,11 @@ impl Prompt {
         let actor = encounter_instance
             .actors
             .get(&self.actor_id)
-            .expect("missing actor");
+            .ok_or_else(|| ParseError::new(&format!("no actor with id {}", self.actor_id)))?;
         let mut tokens: LinkedList<&str> = input.split_whitespace().collect();
-        if tokens.is_empty() {
+        let Some(action_name) = tokens.pop_front() else {
             return Err(ParseError::new("EMPTY"));
-        }
-        let action_name: &str = tokens.pop_front().unwrap();
+        };
 
         let action: &(dyn Action + Send + Sync) = *self
             .actions
@@ -46,39 +54,82 @@ impl Prompt {
             .find(|e| action_name == e.name() || e.aliases().contains(&action_name))
             .ok_or_else(|| ParseError::new(&format!("could not find action {}", action_name)))?;
 
-        let target_ids: Vec<usize> = Vec::new();
+        let mut target_ids: Vec<usize> = Vec::new();
         let mut target_locations: Vec<Coordinate> = Vec::new();
 
-        while !tokens.is_empty() {
-            let token_trimmed = tokens.pop_front().unwrap().trim();
+        while let Some(token) = tokens.pop_front() {
+            let token_trimmed = token.trim();
             if let Some(coord) = parse_coord(token_trimmed, actor.location()) {
                 target_locations.push(coord);
+            } else if let Some(glyph_idx) = parse_actor_char(token_trimmed) {
+                // glyphs repeat once there are many actors, so take the closest match
+                let target_id = encounter_instance
+                    .actors
+                    .iter()
+                    .filter(|(id, _)| *id % actor_char_count() == glyph_idx)
+                    .min_by(|(a_id, a), (b_id, b)| {
+                        let dist = |id: usize, location| {
+                            let dz = encounter_instance.actor_elevation(id)
+                                - encounter_instance.actor_elevation(self.actor_id);
+                            tile_center_dist(location, actor.location(), dz)
+                        };
+                        dist(**a_id, a.location()).total_cmp(&dist(**b_id, b.location()))
+                    })
+                    .map(|(id, _)| *id)
+                    .ok_or_else(|| {
+                        ParseError::new(&format!("no actor shown as {}", token_trimmed))
+                    })?;
+                target_ids.push(target_id);
+            } else {
+                return Err(ParseError::new(&format!(
+                    "could not parse target {}",
+                    token_trimmed
+                )));
             }
         }
 
         let aei: ActionExecutionInfo = ActionExecutionInfo::new(
             action,
             self.actor_id,
-            if target_ids.len() > 0 {
+            if !target_ids.is_empty() {
                 Some(target_ids)
             } else {
                 None
             },
-            if target_locations.len() > 0 {
+            if !target_locations.is_empty() {
                 Some(target_locations)
             } else {
                 None
             },
             None, // TODO: overrides
         );
+        Ok(aei)
+    }
+
+    pub fn preview_input(
+        &self,
+        input: &str,
+        encounter_instance: &EncounterInstance,
+    ) -> Vec<Coordinate> {
+        match self.parse_input(input, encounter_instance) {
+            Ok(aei) => aei.preview(encounter_instance),
+            Err(_) => Vec::new(),
+        }
+    }
 
-        if !aei.validate(&encounter_instance) {
+    pub fn process_input(
+        &self,
+        input: &str,
+        encounter_instance: &EncounterInstance,
+    ) -> Result<ActionExecutionInfo, ParseError> {
+        let aei = self.parse_input(input, encounter_instance)?;
+        if !aei.validate(encounter_instance) {
             // TODO: better error for insufficient resources etc
             return Err(ParseError::new(&format!(
                 "argument validation failed for {}",
                 input
             )));
         }
-        return Ok(aei);
+        Ok(aei)
     }
 }
@@ -0,0 +1,84 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::errors::SaveVersionMismatch;
+
+// goes up whenever a change to the encounter state would make older saves
+// load wrongly
+const SAVE_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+    version: u32,
+    encounter: &'a EncounterInstance,
+}
+
+#[derive(Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct SaveFile {
+    encounter: EncounterInstance,
+}
+
+// writes everything needed to carry on the encounter exactly where it was,
+// dice included
+pub fn save_encounter(encounter: &EncounterInstance, path: &str) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(
+        &mut writer,
+        &SaveFileRef {
+            version: SAVE_VERSION,
+            encounter,
+        },
+    )?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn load_encounter(path: &str) -> Result<EncounterInstance, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path)?;
+    // the version is checked first so that an old save reports that rather
+    // than whatever field it happens to trip over
+    let SaveVersion { version } = serde_json::from_str(&text)?;
+    if version != SAVE_VERSION {
+        return Err(Box::new(SaveVersionMismatch::new(version, SAVE_VERSION)));
+    }
+    let save: SaveFile = serde_json::from_str(&text)?;
+    Ok(save.encounter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_util::{encounter_from, play};
+
+    fn log_lines(encounter: &EncounterInstance) -> Vec<String> {
+        encounter.events().iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn a_loaded_encounter_carries_on_as_the_saved_one_would() {
+        let mut encounter = encounter_from(include_str!("../../maps/crossroads.map"), 11);
+        play(&mut encounter, 10);
+
+        let path = std::env::temp_dir().join(format!("dnd-rs-save-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_encounter(&encounter, path).unwrap();
+        let mut loaded = load_encounter(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(log_lines(&loaded), log_lines(&encounter));
+
+        play(&mut encounter, 40);
+        play(&mut loaded, 40);
+        assert!(!loaded.events().is_empty());
+        assert_eq!(log_lines(&loaded), log_lines(&encounter));
+        assert_eq!(loaded.round(), encounter.round());
+    }
+}
@@ -1,11 +1,100 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::actions::search::RevealHazards;
+use crate::engine::combat::roll_damage_dice;
 use crate::engine::encounter::EncounterInstance;
-use crate::engine::types::Coordinate;
+use crate::engine::errors::EngineError;
+use crate::engine::events::EncounterEvent;
+use crate::engine::hazards::TriggerHazard;
+use crate::engine::terrain::{DoorState, TerrainInfo, TerrainType};
+use crate::engine::triggers::TriggerEvent;
+use crate::engine::types::{Coordinate, DamageType};
+use crate::engine::util::get_tiles_from_size;
+
+const MAX_FALL_DICE: u8 = 20;
+
+pub trait ApplicableSideEffect: CloneSideEffect {
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError>;
+
+    fn trigger_event(&self) -> Option<TriggerEvent> {
+        None
+    }
+
+    // the side effect as it is written to a saved encounter
+    fn saved(&self) -> SavedSideEffect;
+}
+
+// lets boxed side effects be cloned along with the encounter stack they sit on
+pub trait CloneSideEffect {
+    fn clone_box(&self) -> Box<dyn ApplicableSideEffect>;
+}
+
+impl<T: ApplicableSideEffect + Clone + 'static> CloneSideEffect for T {
+    fn clone_box(&self) -> Box<dyn ApplicableSideEffect> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn ApplicableSideEffect> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
 
-pub trait ApplicableSideEffect {
-    fn apply(&self, ei: &mut EncounterInstance);
+// every side effect that can be waiting on the stack of a saved encounter,
+// under the name it is saved by
+#[derive(Serialize, Deserialize)]
+pub enum SavedSideEffect {
+    ConsumeResource(ConsumeResource),
+    GiveResource(GiveResource),
+    MoveActor(MoveActor),
+    ChangeAltitude(ChangeAltitude),
+    KnockProne(KnockProne),
+    StandUp(StandUp),
+    Fall(Fall),
+    SkipTurn(SkipTurn),
+    DealDamage(DealDamage),
+    Heal(Heal),
+    SetDoorState(SetDoorState),
+    DamageTerrain(DamageTerrain),
+    TriggerHazard(TriggerHazard),
+    RevealHazards(RevealHazards),
 }
 
-#[derive(Clone, PartialEq, Copy)]
+impl SavedSideEffect {
+    pub fn into_box(self) -> Box<dyn ApplicableSideEffect> {
+        match self {
+            SavedSideEffect::ConsumeResource(se) => Box::new(se),
+            SavedSideEffect::GiveResource(se) => Box::new(se),
+            SavedSideEffect::MoveActor(se) => Box::new(se),
+            SavedSideEffect::ChangeAltitude(se) => Box::new(se),
+            SavedSideEffect::KnockProne(se) => Box::new(se),
+            SavedSideEffect::StandUp(se) => Box::new(se),
+            SavedSideEffect::Fall(se) => Box::new(se),
+            SavedSideEffect::SkipTurn(se) => Box::new(se),
+            SavedSideEffect::DealDamage(se) => Box::new(se),
+            SavedSideEffect::Heal(se) => Box::new(se),
+            SavedSideEffect::SetDoorState(se) => Box::new(se),
+            SavedSideEffect::DamageTerrain(se) => Box::new(se),
+            SavedSideEffect::TriggerHazard(se) => Box::new(se),
+            SavedSideEffect::RevealHazards(se) => Box::new(se),
+        }
+    }
+}
+
+impl Serialize for Box<dyn ApplicableSideEffect> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.saved().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<dyn ApplicableSideEffect> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(SavedSideEffect::deserialize(deserializer)?.into_box())
+    }
+}
+
+#[derive(Clone, PartialEq, Copy, Debug, Serialize, Deserialize)]
 pub enum Resource {
     Movement(f32),
     SpellSlot(u32),
@@ -13,54 +102,390 @@ pub enum Resource {
     BonusAction,
     Reaction,
     LegendaryAction,
+    ObjectInteraction,
 }
 
-#[derive(Clone, PartialEq, Copy)]
+#[derive(Clone, PartialEq, Copy, Serialize, Deserialize)]
 pub struct ConsumeResource {
     pub actor_id: usize,
     pub resource: Resource,
 }
 
 impl ApplicableSideEffect for ConsumeResource {
-    fn apply(&self, ei: &mut EncounterInstance) {
-        let actor = ei.actors.get_mut(&self.actor_id).unwrap();
-        actor.consume_resource(self.resource);
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::ConsumeResource(*self)
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        let actor = ei
+            .actors
+            .get_mut(&self.actor_id)
+            .ok_or(EngineError::MissingActor(self.actor_id))?;
+        actor.consume_resource(self.resource)
     }
 }
 
-#[derive(Clone, PartialEq, Copy)]
+#[derive(Clone, PartialEq, Copy, Serialize, Deserialize)]
 pub struct GiveResource {
     pub actor_id: usize,
     pub resource: Resource,
 }
 
 impl ApplicableSideEffect for GiveResource {
-    fn apply(&self, ei: &mut EncounterInstance) {
-        let actor = ei.actors.get_mut(&self.actor_id).unwrap();
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::GiveResource(*self)
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        let actor = ei
+            .actors
+            .get_mut(&self.actor_id)
+            .ok_or(EngineError::MissingActor(self.actor_id))?;
         actor.give_resource(self.resource);
+        Ok(())
     }
 }
 
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct MoveActor {
     pub actor_id: usize,
     pub target: Coordinate,
+    // positions passed on the way, from the start; empty when the actor is
+    // placed rather than moving, which crosses nothing
+    pub path: Vec<Coordinate>,
+    // movement spent on the move, given back in part if it is cut short
+    pub movement_paid: f32,
 }
 
 impl ApplicableSideEffect for MoveActor {
-    fn apply(&self, ei: &mut EncounterInstance) {
-        ei.set_actor_map(self.actor_id, self.target)
-            .expect("failed to move actor");
-        let actor = ei.actors.get_mut(&self.actor_id).expect("missing actor id");
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::MoveActor(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        if !ei.in_bounds(self.target) {
+            return Err(EngineError::OffMap(self.target));
+        }
+        let elevation_before = ei.actor_elevation(self.actor_id);
+        // a creature only squeezes where its own space does not fit
+        let squeezing = match (
+            ei.fit_width(self.actor_id, self.target),
+            ei.actors.get(&self.actor_id),
+        ) {
+            (Some(width), Some(actor)) => width < get_tiles_from_size(actor.size()),
+            _ => false,
+        };
+        ei.actors
+            .get_mut(&self.actor_id)
+            .ok_or(EngineError::MissingActor(self.actor_id))?
+            .set_squeezing(squeezing);
+        ei.set_actor_map(self.actor_id, self.target)?;
+        let actor = ei
+            .actors
+            .get_mut(&self.actor_id)
+            .ok_or(EngineError::MissingActor(self.actor_id))?;
         actor.set_location(self.target);
+        // flyers hold their height over rising and falling ground, everyone
+        // else follows it
+        if actor.altitude() > 0. && actor.fly_speed() > 0. {
+            let width = actor.footprint_width();
+            let ground = ei.ground_elevation(self.target, width);
+            let actor = ei
+                .actors
+                .get_mut(&self.actor_id)
+                .ok_or(EngineError::MissingActor(self.actor_id))?;
+            actor.set_altitude(elevation_before - ground);
+        }
+        Ok(())
+    }
+
+    fn trigger_event(&self) -> Option<TriggerEvent> {
+        if self.path.is_empty() {
+            return None;
+        }
+        Some(TriggerEvent::Moved {
+            actor_id: self.actor_id,
+            path: self.path.clone(),
+            movement_paid: self.movement_paid,
+        })
+    }
+}
+
+// climbing or flying up (positive) or down (negative)
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeAltitude {
+    pub actor_id: usize,
+    pub amount: f32,
+}
+
+impl ApplicableSideEffect for ChangeAltitude {
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::ChangeAltitude(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        let actor = ei
+            .actors
+            .get_mut(&self.actor_id)
+            .ok_or(EngineError::MissingActor(self.actor_id))?;
+        actor.set_altitude(actor.altitude() + self.amount);
+        Ok(())
+    }
+}
+
+// lays the actor down or stands it up, logging it only if that changes anything
+pub fn set_prone(
+    ei: &mut EncounterInstance,
+    actor_id: usize,
+    prone: bool,
+) -> Result<(), EngineError> {
+    let actor = ei
+        .actors
+        .get_mut(&actor_id)
+        .ok_or(EngineError::MissingActor(actor_id))?;
+    if actor.is_prone() == prone {
+        return Ok(());
+    }
+    actor.set_prone(prone);
+    ei.log(EncounterEvent::ConditionChanged {
+        actor_id,
+        actor: ei.actor_name(actor_id),
+        condition: "prone".to_string(),
+        applied: prone,
+    });
+    Ok(())
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnockProne {
+    pub actor_id: usize,
+}
+
+impl ApplicableSideEffect for KnockProne {
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::KnockProne(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        set_prone(ei, self.actor_id, true)
     }
 }
 
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct StandUp {
+    pub actor_id: usize,
+}
+
+impl ApplicableSideEffect for StandUp {
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::StandUp(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        set_prone(ei, self.actor_id, false)
+    }
+}
+
+// 1d6 bludgeoning per 10 ft fallen, up to 20d6, landing prone
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fall {
+    pub actor_id: usize,
+}
+
+impl ApplicableSideEffect for Fall {
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::Fall(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        let Some(actor) = ei.actors.get(&self.actor_id) else {
+            return Ok(());
+        };
+        let height = actor.altitude();
+        let n_dice = ((height / 10.).floor() as u8).min(MAX_FALL_DICE);
+        let amount = if n_dice > 0 {
+            roll_damage_dice(ei, self.actor_id, n_dice, 6, "falling".to_string())
+        } else {
+            0
+        };
+
+        let actor = ei
+            .actors
+            .get_mut(&self.actor_id)
+            .ok_or(EngineError::MissingActor(self.actor_id))?;
+        actor.set_altitude(0.);
+        let text = format!("{} falls {} ft", actor.name(), height);
+        ei.log(EncounterEvent::Note {
+            text,
+            actor_id: Some(self.actor_id),
+            location: None,
+        });
+        if amount > 0 {
+            set_prone(ei, self.actor_id, true)?;
+            DealDamage {
+                actor_id: self.actor_id,
+                amount,
+                damage_type: DamageType::Bludgeoning,
+            }
+            .apply(ei)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub struct SkipTurn {}
 
 impl ApplicableSideEffect for SkipTurn {
-    fn apply(&self, ei: &mut EncounterInstance) {
-        ei.skip_turn();
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::SkipTurn(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        ei.skip_turn()
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct DealDamage {
+    pub actor_id: usize,
+    pub amount: u32,
+    pub damage_type: DamageType,
+}
+
+impl ApplicableSideEffect for DealDamage {
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::DealDamage(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        let actor = ei
+            .actors
+            .get_mut(&self.actor_id)
+            .ok_or(EngineError::MissingActor(self.actor_id))?;
+        let was_down = actor.is_down();
+        actor.take_damage(self.amount);
+        let event = EncounterEvent::DamageApplied {
+            actor_id: self.actor_id,
+            actor: actor.name(),
+            amount: self.amount,
+            damage_type: self.damage_type.clone(),
+            hitpoints: actor.hitpoints(),
+            max_hitpoints: actor.max_hitpoints(),
+        };
+        let died = !was_down && actor.is_down();
+        ei.log(event);
+        if died {
+            ei.log(EncounterEvent::Died {
+                actor_id: self.actor_id,
+                actor: ei.actor_name(self.actor_id),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heal {
+    pub actor_id: usize,
+    pub amount: u32,
+}
+
+impl ApplicableSideEffect for Heal {
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::Heal(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        let actor = ei
+            .actors
+            .get_mut(&self.actor_id)
+            .ok_or(EngineError::MissingActor(self.actor_id))?;
+        actor.heal(self.amount);
+        let event = EncounterEvent::Healed {
+            actor_id: self.actor_id,
+            actor: actor.name(),
+            amount: self.amount,
+            hitpoints: actor.hitpoints(),
+            max_hitpoints: actor.max_hitpoints(),
+        };
+        ei.log(event);
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetDoorState {
+    pub target: Coordinate,
+    pub state: DoorState,
+}
+
+impl ApplicableSideEffect for SetDoorState {
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::SetDoorState(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        if !ei.in_bounds(self.target) {
+            return Err(EngineError::OffMap(self.target));
+        }
+        for coord in ei.door_tiles(self.target) {
+            if let Some(ti) = ei.terrain_at_mut(coord) {
+                ti.terrain_type = TerrainType::Door(self.state);
+            }
+        }
+        ei.log(EncounterEvent::Note {
+            text: format!("door at {} is now {:?}", self.target, self.state),
+            actor_id: None,
+            location: Some(self.target),
+        });
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct DamageTerrain {
+    pub target: Coordinate,
+    pub amount: u32,
+}
+
+impl ApplicableSideEffect for DamageTerrain {
+    fn saved(&self) -> SavedSideEffect {
+        SavedSideEffect::DamageTerrain(self.clone())
+    }
+
+    fn apply(&self, ei: &mut EncounterInstance) -> Result<(), EngineError> {
+        if !ei.in_bounds(self.target) {
+            return Err(EngineError::OffMap(self.target));
+        }
+        // every tile of a door shares the damage so the door breaks as a whole
+        let mut tiles = ei.door_tiles(self.target);
+        if tiles.is_empty() {
+            tiles.push(self.target);
+        }
+        for coord in tiles {
+            let Some(ti) = ei.terrain_at_mut(coord) else {
+                continue;
+            };
+            let Some(hp) = ti.hitpoints else {
+                continue;
+            };
+            let remaining = hp.saturating_sub(self.amount);
+            if remaining == 0 {
+                *ti = TerrainInfo::new(TerrainType::Rubble);
+            } else {
+                ti.hitpoints = Some(remaining);
+            }
+        }
+        let text = match ei.terrain_at(self.target).and_then(|ti| ti.hitpoints) {
+            Some(hp) => format!("{} takes {} damage ({} left)", self.target, self.amount, hp),
+            None => format!("{} is destroyed", self.target),
+        };
+        ei.log(EncounterEvent::Note {
+            text,
+            actor_id: None,
+            location: Some(self.target),
+        });
+        Ok(())
     }
 }
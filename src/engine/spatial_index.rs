@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::types::Coordinate;
+
+// Tiny creatures are often far smaller than the 2.5 ft they are given, so a
+// few allied ones can share a tile
+pub const TINY_PER_TILE: usize = 4;
+
+// which actors stand where; every actor's exact footprint is kept so that
+// neither lookup direction needs a scan of the map
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SpatialIndex {
+    width: usize,
+    height: usize,
+    // actors on each tile; several only where Tiny creatures share one
+    occupants: Vec<Vec<usize>>,
+    footprints: HashMap<usize, Vec<Coordinate>>,
+}
+
+impl SpatialIndex {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            occupants: vec![Vec::new(); width * height],
+            footprints: HashMap::new(),
+        }
+    }
+
+    fn idx(&self, coord: Coordinate) -> Option<usize> {
+        if coord.x < 0
+            || coord.y < 0
+            || coord.x as usize >= self.width
+            || coord.y as usize >= self.height
+        {
+            return None;
+        }
+        Some(coord.x as usize + coord.y as usize * self.width)
+    }
+
+    // actors on a tile, empty off the map
+    pub fn occupants(&self, coord: Coordinate) -> &[usize] {
+        match self.idx(coord) {
+            Some(i) => &self.occupants[i],
+            None => &[],
+        }
+    }
+
+    // tiles covered by an actor, empty if it is not on the map
+    pub fn footprint(&self, actor_id: usize) -> &[Coordinate] {
+        self.footprints
+            .get(&actor_id)
+            .map(|tiles| tiles.as_slice())
+            .unwrap_or(&[])
+    }
+
+    // replaces wherever the actor was with the given tiles; off map tiles are dropped
+    pub fn place(&mut self, actor_id: usize, tiles: Vec<Coordinate>) {
+        self.remove(actor_id);
+        let tiles: Vec<Coordinate> = tiles
+            .into_iter()
+            .filter(|&t| self.idx(t).is_some())
+            .collect();
+        for &tile in tiles.iter() {
+            let i = self.idx(tile).expect("filtered to the map");
+            self.occupants[i].push(actor_id);
+        }
+        self.footprints.insert(actor_id, tiles);
+    }
+
+    pub fn remove(&mut self, actor_id: usize) {
+        let Some(tiles) = self.footprints.remove(&actor_id) else {
+            return;
+        };
+        for tile in tiles {
+            if let Some(i) = self.idx(tile) {
+                self.occupants[i].retain(|&id| id != actor_id);
+            }
+        }
+    }
+}
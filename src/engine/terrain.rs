@@ -1,11 +1,139 @@
-#[derive(Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+use crate::engine::hazards::Hazard;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct TerrainInfo {
     pub terrain_type: TerrainType,
+    // None for terrain that cannot be destroyed
+    pub hitpoints: Option<u32>,
+    // feet of the ground surface above the base level of the map
+    pub elevation: f32,
+    pub hazard: Option<Hazard>,
+}
+
+impl TerrainInfo {
+    pub fn new(terrain_type: TerrainType) -> Self {
+        Self {
+            hitpoints: terrain_type.max_hitpoints(),
+            elevation: terrain_type.base_elevation(),
+            hazard: None,
+            terrain_type,
+        }
+    }
+
+    // feet above the map's base level of the top of whatever stands here
+    pub fn top(&self) -> f32 {
+        self.elevation + self.terrain_type.height()
+    }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DoorState {
+    Open,
+    Closed,
+    Locked,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TerrainType {
     Empty,
     Floor,
     Wall,
+    Door(DoorState),
+    Tree,
+    Boulder,
+    Bridge,
+    // difficult terrain
+    Rubble,
+    Undergrowth,
+    ShallowWater,
+    // need a climb or swim speed to cross at normal cost
+    Cliff,
+    DeepWater,
+}
+
+impl TerrainType {
+    pub fn is_passable(&self) -> bool {
+        !matches!(
+            self,
+            TerrainType::Empty
+                | TerrainType::Wall
+                | TerrainType::Tree
+                | TerrainType::Boulder
+                | TerrainType::Door(DoorState::Closed | DoorState::Locked)
+        )
+    }
+
+    // passable, or a door that can be opened, picked or forced
+    pub fn is_traversable(&self) -> bool {
+        self.is_passable() || matches!(self, TerrainType::Door(_))
+    }
+
+    pub fn blocks_sight(&self) -> bool {
+        matches!(
+            self,
+            TerrainType::Wall
+                | TerrainType::Tree
+                | TerrainType::Boulder
+                | TerrainType::Door(DoorState::Closed | DoorState::Locked)
+        )
+    }
+
+    pub fn is_difficult(&self) -> bool {
+        matches!(
+            self,
+            TerrainType::Rubble | TerrainType::Undergrowth | TerrainType::ShallowWater
+        )
+    }
+
+    pub fn needs_climbing(&self) -> bool {
+        matches!(self, TerrainType::Cliff)
+    }
+
+    pub fn needs_swimming(&self) -> bool {
+        matches!(self, TerrainType::DeepWater)
+    }
+
+    // cliffs are raised ground rather than an obstacle
+    pub fn base_elevation(&self) -> f32 {
+        match self {
+            TerrainType::Cliff => 10.,
+            _ => 0.,
+        }
+    }
+
+    // feet an obstacle rises above its tile's ground; also how high it can be climbed
+    pub fn height(&self) -> f32 {
+        match self {
+            TerrainType::Wall | TerrainType::Door(_) => 10.,
+            TerrainType::Tree => 15.,
+            TerrainType::Boulder => 5.,
+            _ => 0.,
+        }
+    }
+
+    pub fn is_climbable(&self) -> bool {
+        matches!(
+            self,
+            TerrainType::Wall | TerrainType::Tree | TerrainType::Boulder | TerrainType::Cliff
+        )
+    }
+
+    // object stats from the DMG: stone walls and wooden doors
+    pub fn armor_class(&self) -> Option<u32> {
+        match self {
+            TerrainType::Wall => Some(17),
+            TerrainType::Door(_) => Some(15),
+            _ => None,
+        }
+    }
+
+    pub fn max_hitpoints(&self) -> Option<u32> {
+        match self {
+            TerrainType::Wall => Some(27),
+            TerrainType::Door(_) => Some(18),
+            _ => None,
+        }
+    }
 }
@@ -1,4 +1,5 @@
-use crate::engine::terrain::{TerrainInfo, TerrainType};
+use crate::engine::terrain::{DoorState, TerrainInfo, TerrainType};
+use crate::engine::terrain_gen::{TerrainGenerator, idx, scatter_features};
 
 use rand::Rng;
 use rand::RngCore;
@@ -15,18 +16,20 @@ struct BSPNode {
     children: Option<(Box<BSPNode>, Box<BSPNode>)>,
 }
 
-pub struct TerrainGenParams {
+// rooms carved by binary space partitioning, joined by gaps and doors
+pub struct BspParams {
     pub width: usize,
     pub height: usize,
     pub branch_depth: usize,
     pub branch_prob: f32,
+    pub n_features: usize,
+    pub door_prob: f32,
+    pub locked_door_prob: f32,
+    pub hazard_budget: u32,
 }
 
 fn collect_leaves<'a>(node: &'a mut BSPNode, leaves: &mut Vec<&'a mut BSPNode>) {
-    let is_leaf = match &node.children {
-        None => true,
-        Some(_) => false,
-    };
+    let is_leaf = node.children.is_none();
 
     if is_leaf {
         leaves.push(node);
@@ -39,17 +42,13 @@ fn collect_leaves<'a>(node: &'a mut BSPNode, leaves: &mut Vec<&'a mut BSPNode>)
     }
 }
 
-fn get_leaves<'a>(root: &'a mut BSPNode) -> Vec<&'a mut BSPNode> {
+fn get_leaves(root: &mut BSPNode) -> Vec<&mut BSPNode> {
     let mut leaves = Vec::new();
     collect_leaves(root, &mut leaves);
     leaves
 }
 
-fn idx(x: usize, y: usize, params: &TerrainGenParams) -> usize {
-    x + y * params.width
-}
-
-fn binary_space_partition<R: RngCore>(params: &TerrainGenParams, rng: &mut R) -> Vec<TerrainInfo> {
+fn binary_space_partition(params: &BspParams, rng: &mut dyn RngCore) -> Vec<TerrainInfo> {
     let mut root = BSPNode {
         x: 0,
         y: 0,
@@ -118,61 +117,90 @@ fn binary_space_partition<R: RngCore>(params: &TerrainGenParams, rng: &mut R) ->
         }
     }
 
-    let mut terrain = vec![
-        TerrainInfo {
-            terrain_type: TerrainType::Empty
-        };
-        params.width * params.height
-    ];
+    let mut terrain = vec![TerrainInfo::new(TerrainType::Empty); params.width * params.height];
     let leaves = get_leaves(&mut root);
 
     for node in leaves {
         // fill floor
         for i in node.x..(node.x + node.width - 1) {
             for j in node.y..(node.y + node.height - 1) {
-                terrain[idx(i, j, params)].terrain_type = TerrainType::Floor;
+                terrain[idx(i, j, params.width)].terrain_type = TerrainType::Floor;
             }
         }
 
         // make walls
         for i in node.x..(node.x + node.width) {
-            terrain[idx(i, node.y + node.height - 1, params)].terrain_type = TerrainType::Wall;
+            terrain[idx(i, node.y + node.height - 1, params.width)].terrain_type =
+                TerrainType::Wall;
         }
         for j in node.y..(node.y + node.height) {
-            terrain[idx(node.x + node.width - 1, j, params)].terrain_type = TerrainType::Wall;
+            terrain[idx(node.x + node.width - 1, j, params.width)].terrain_type = TerrainType::Wall;
         }
 
-        // maker doors
-        let door_width_horizontal =
-            rng.random_range(MIN_WIDTH..(node.width - 1).min(MIN_WIDTH * 2));
-        let door_offset_horizontal = rng.random_range(0..node.width - door_width_horizontal);
-        for i in 0..door_width_horizontal {
-            terrain[idx(
-                node.x + i + door_offset_horizontal,
-                node.y + node.height - 1,
-                params,
-            )]
-            .terrain_type = TerrainType::Floor;
+        // make doors; walls on the map edge lead nowhere, and the last tile
+        // of each wall is the corner shared with the other wall
+        if node.y + node.height < params.height {
+            let door_type = gap_terrain(params, rng);
+            let door_width_horizontal =
+                rng.random_range(MIN_WIDTH..(node.width - 1).min(MIN_WIDTH * 2));
+            let door_offset_horizontal =
+                rng.random_range(0..node.width - 1 - door_width_horizontal);
+            for i in 0..door_width_horizontal {
+                terrain[idx(
+                    node.x + i + door_offset_horizontal,
+                    node.y + node.height - 1,
+                    params.width,
+                )]
+                .terrain_type = door_type.clone();
+            }
         }
 
-        let door_width_vertical = rng.random_range(MIN_WIDTH..(node.height - 1).min(MIN_WIDTH * 2));
-        let door_offset_vertical = rng.random_range(0..node.height - door_width_vertical);
-        for i in 0..door_width_vertical {
-            terrain[idx(
-                node.x + node.width - 1,
-                node.y + i + door_offset_vertical,
-                params,
-            )]
-            .terrain_type = TerrainType::Floor;
+        if node.x + node.width < params.width {
+            let door_type = gap_terrain(params, rng);
+            let door_width_vertical =
+                rng.random_range(MIN_WIDTH..(node.height - 1).min(MIN_WIDTH * 2));
+            let door_offset_vertical = rng.random_range(0..node.height - 1 - door_width_vertical);
+            for i in 0..door_width_vertical {
+                terrain[idx(
+                    node.x + node.width - 1,
+                    node.y + i + door_offset_vertical,
+                    params.width,
+                )]
+                .terrain_type = door_type.clone();
+            }
         }
     }
 
     terrain
 }
 
-pub fn generate_terrain(params: &TerrainGenParams) -> Vec<TerrainInfo> {
-    let mut rng = rand::rng();
-    let terrain = binary_space_partition(params, &mut rng);
-    // TODO: modify terrain
-    terrain
+// a gap in a wall is either left open or filled with a door
+fn gap_terrain(params: &BspParams, rng: &mut dyn RngCore) -> TerrainType {
+    if rng.random::<f32>() >= params.door_prob {
+        TerrainType::Floor
+    } else if rng.random::<f32>() < params.locked_door_prob {
+        TerrainType::Door(DoorState::Locked)
+    } else {
+        TerrainType::Door(DoorState::Closed)
+    }
+}
+
+impl TerrainGenerator for BspParams {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn hazard_budget(&self) -> u32 {
+        self.hazard_budget
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec<TerrainInfo> {
+        let mut terrain = binary_space_partition(self, rng);
+        scatter_features(&mut terrain, self.width, self.height, self.n_features, rng);
+        terrain
+    }
 }
@@ -0,0 +1,94 @@
+use crate::engine::terrain::{TerrainInfo, TerrainType};
+use crate::engine::terrain_gen::{TerrainGenerator, idx, scatter_features};
+
+use rand::Rng;
+use rand::RngCore;
+
+// a tile becomes wall when at least this many of its 8 neighbours are walls
+const WALL_NEIGHBOR_THRESHOLD: usize = 5;
+
+// cellular automaton caves: random noise smoothed into open caverns
+pub struct CaveParams {
+    pub width: usize,
+    pub height: usize,
+    pub fill_prob: f32,
+    pub n_iterations: usize,
+    pub n_features: usize,
+    pub hazard_budget: u32,
+}
+
+fn is_border(x: usize, y: usize, params: &CaveParams) -> bool {
+    x == 0 || y == 0 || x == params.width - 1 || y == params.height - 1
+}
+
+fn wall_neighbors(walls: &[bool], x: usize, y: usize, params: &CaveParams) -> usize {
+    let mut n: usize = 0;
+    for dx in -1..=1_isize {
+        for dy in -1..=1_isize {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            // off the map counts as wall so caves close up at the edges
+            let off_map =
+                nx < 0 || ny < 0 || nx as usize >= params.width || ny as usize >= params.height;
+            if off_map || walls[idx(nx as usize, ny as usize, params.width)] {
+                n += 1;
+            }
+        }
+    }
+    n
+}
+
+fn cellular_automaton(params: &CaveParams, rng: &mut dyn RngCore) -> Vec<TerrainInfo> {
+    let mut walls: Vec<bool> = vec![false; params.width * params.height];
+    for y in 0..params.height {
+        for x in 0..params.width {
+            walls[idx(x, y, params.width)] =
+                is_border(x, y, params) || rng.random::<f32>() < params.fill_prob;
+        }
+    }
+
+    for _ in 0..params.n_iterations {
+        let mut next = walls.clone();
+        for y in 0..params.height {
+            for x in 0..params.width {
+                next[idx(x, y, params.width)] = is_border(x, y, params)
+                    || wall_neighbors(&walls, x, y, params) >= WALL_NEIGHBOR_THRESHOLD;
+            }
+        }
+        walls = next;
+    }
+
+    walls
+        .into_iter()
+        .map(|wall| {
+            TerrainInfo::new(if wall {
+                TerrainType::Wall
+            } else {
+                TerrainType::Floor
+            })
+        })
+        .collect()
+}
+
+impl TerrainGenerator for CaveParams {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn hazard_budget(&self) -> u32 {
+        self.hazard_budget
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec<TerrainInfo> {
+        let mut terrain = cellular_automaton(self, rng);
+        scatter_features(&mut terrain, self.width, self.height, self.n_features, rng);
+        terrain
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+
+use crate::engine::terrain::{TerrainInfo, TerrainType};
+use crate::engine::terrain_gen::idx;
+
+// Regions are measured by where a creature of TRAVERSAL_WIDTH can stand: two
+// tiles are connected if a medium creature, which takes up 2x2 tiles even
+// when squeezing, can walk from one to the other. A one tile neck joins
+// nothing.
+pub const TRAVERSAL_WIDTH: usize = 2;
+
+fn neighbors(i: usize, width: usize, height: usize) -> impl Iterator<Item = usize> {
+    let x = i % width;
+    let y = i / width;
+    [
+        (x > 0).then(|| i - 1),
+        (x + 1 < width).then(|| i + 1),
+        (y > 0).then(|| i - width),
+        (y + 1 < height).then(|| i + width),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+// the tiles of a TRAVERSAL_WIDTH square with its low corner at i, if it is
+// on the map
+fn footprint(i: usize, width: usize, height: usize) -> Option<impl Iterator<Item = usize>> {
+    let x = i % width;
+    let y = i / width;
+    if x + TRAVERSAL_WIDTH > width || y + TRAVERSAL_WIDTH > height {
+        return None;
+    }
+    let tiles = (0..TRAVERSAL_WIDTH)
+        .flat_map(move |dx| (0..TRAVERSAL_WIDTH).map(move |dy| idx(x + dx, y + dy, width)));
+    Some(tiles)
+}
+
+fn fits(terrain: &[TerrainInfo], width: usize, height: usize, i: usize) -> bool {
+    footprint(i, width, height)
+        .is_some_and(|mut tiles| tiles.all(|t| terrain[t].terrain_type.is_traversable()))
+}
+
+// groups the places a medium creature can stand, by their low corner, into
+// 4-connected regions, largest first; diagonal steps are not enough since
+// movement cannot cut corners
+pub fn connected_regions(terrain: &[TerrainInfo], width: usize, height: usize) -> Vec<Vec<usize>> {
+    let mut visited: Vec<bool> = vec![false; terrain.len()];
+    let mut regions: Vec<Vec<usize>> = Vec::new();
+
+    for start in 0..terrain.len() {
+        if visited[start] || !fits(terrain, width, height, start) {
+            continue;
+        }
+        let mut region = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+        while let Some(i) = queue.pop_front() {
+            region.push(i);
+            for n in neighbors(i, width, height) {
+                if !visited[n] && fits(terrain, width, height, n) {
+                    visited[n] = true;
+                    queue.push_back(n);
+                }
+            }
+        }
+        regions.push(region);
+    }
+
+    // stable so ties keep scan order and stay reproducible
+    regions.sort_by_key(|r| std::cmp::Reverse(r.len()));
+    regions
+}
+
+// marks the tiles of the largest region, where encounters take place
+pub fn main_region(terrain: &[TerrainInfo], width: usize, height: usize) -> Vec<bool> {
+    let mut in_main = vec![false; terrain.len()];
+    if let Some(region) = connected_regions(terrain, width, height).first() {
+        for &i in region {
+            for t in footprint(i, width, height).into_iter().flatten() {
+                in_main[t] = true;
+            }
+        }
+    }
+    in_main
+}
+
+// shortest 4-connected path from the region to the nearest place outside it
+// where a medium creature fits, through anything
+fn path_to_nearest_region(
+    terrain: &[TerrainInfo],
+    width: usize,
+    height: usize,
+    region: &[usize],
+) -> Option<Vec<usize>> {
+    let mut in_region = vec![false; terrain.len()];
+    let mut came_from: Vec<Option<usize>> = vec![None; terrain.len()];
+    let mut visited = vec![false; terrain.len()];
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    for &i in region {
+        in_region[i] = true;
+        visited[i] = true;
+        queue.push_back(i);
+    }
+
+    while let Some(i) = queue.pop_front() {
+        if !in_region[i] && fits(terrain, width, height, i) {
+            let mut path = vec![i];
+            let mut curr = i;
+            while let Some(prev) = came_from[curr] {
+                path.push(prev);
+                curr = prev;
+            }
+            return Some(path);
+        }
+        for n in neighbors(i, width, height) {
+            if !visited[n] && footprint(n, width, height).is_some() {
+                visited[n] = true;
+                came_from[n] = Some(i);
+                queue.push_back(n);
+            }
+        }
+    }
+    None
+}
+
+// joins every region to the main one by carving floor corridors a medium
+// creature can walk down
+pub fn connect_regions(terrain: &mut [TerrainInfo], width: usize, height: usize) {
+    loop {
+        let regions = connected_regions(terrain, width, height);
+        if regions.len() <= 1 {
+            return;
+        }
+        let Some(path) = path_to_nearest_region(terrain, width, height, &regions[0]) else {
+            return;
+        };
+        for i in path {
+            for t in footprint(i, width, height).into_iter().flatten() {
+                if !terrain[t].terrain_type.is_traversable() {
+                    terrain[t] = TerrainInfo::new(TerrainType::Floor);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // two rooms joined by a corridor one tile wide
+    fn rooms() -> (Vec<TerrainInfo>, usize, usize) {
+        let rows = [
+            "###########",
+            "#...###...#",
+            "#.........#",
+            "#...###...#",
+            "###########",
+        ];
+        let (width, height) = (rows[0].len(), rows.len());
+        let terrain = rows
+            .iter()
+            .rev()
+            .flat_map(|row| row.chars())
+            .map(|c| match c {
+                '#' => TerrainInfo::new(TerrainType::Wall),
+                _ => TerrainInfo::new(TerrainType::Floor),
+            })
+            .collect();
+        (terrain, width, height)
+    }
+
+    #[test]
+    fn a_one_tile_neck_does_not_connect() {
+        let (terrain, width, height) = rooms();
+        assert_eq!(connected_regions(&terrain, width, height).len(), 2);
+        let in_main = main_region(&terrain, width, height);
+        assert!(in_main[idx(1, 1, width)] != in_main[idx(9, 1, width)]);
+        assert!(!in_main[idx(5, 2, width)]);
+    }
+
+    #[test]
+    fn carved_corridors_fit_a_medium_creature() {
+        let (mut terrain, width, height) = rooms();
+        connect_regions(&mut terrain, width, height);
+        assert_eq!(connected_regions(&terrain, width, height).len(), 1);
+        let in_main = main_region(&terrain, width, height);
+        assert!(in_main[idx(1, 1, width)] && in_main[idx(9, 1, width)]);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::engine::terrain::{TerrainInfo, TerrainType};
+use crate::engine::terrain_gen::{TerrainGenerator, idx, scatter_features};
+
+use rand::Rng;
+use rand::RngCore;
+
+// boulders are one 5 ft square
+const BOULDER_WIDTH: usize = 2;
+
+// open ground with scattered trees and boulders
+pub struct FieldParams {
+    pub width: usize,
+    pub height: usize,
+    pub tree_density: f32,
+    pub n_boulders: usize,
+    pub n_features: usize,
+    pub hazard_budget: u32,
+}
+
+// places trees and boulders over floor; shared with the river generator
+pub fn scatter_obstacles(
+    terrain: &mut [TerrainInfo],
+    width: usize,
+    height: usize,
+    tree_density: f32,
+    n_boulders: usize,
+    rng: &mut dyn RngCore,
+) {
+    for ti in terrain.iter_mut() {
+        if ti.terrain_type == TerrainType::Floor && rng.random::<f32>() < tree_density {
+            ti.terrain_type = TerrainType::Tree;
+        }
+    }
+
+    if width < BOULDER_WIDTH || height < BOULDER_WIDTH {
+        return;
+    }
+    for _ in 0..n_boulders {
+        let x = rng.random_range(0..=width - BOULDER_WIDTH);
+        let y = rng.random_range(0..=height - BOULDER_WIDTH);
+        for x_off in 0..BOULDER_WIDTH {
+            for y_off in 0..BOULDER_WIDTH {
+                let i = idx(x + x_off, y + y_off, width);
+                if terrain[i].terrain_type == TerrainType::Floor {
+                    terrain[i].terrain_type = TerrainType::Boulder;
+                }
+            }
+        }
+    }
+}
+
+impl TerrainGenerator for FieldParams {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn hazard_budget(&self) -> u32 {
+        self.hazard_budget
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec<TerrainInfo> {
+        let mut terrain = vec![TerrainInfo::new(TerrainType::Floor); self.width * self.height];
+        scatter_features(&mut terrain, self.width, self.height, self.n_features, rng);
+        scatter_obstacles(
+            &mut terrain,
+            self.width,
+            self.height,
+            self.tree_density,
+            self.n_boulders,
+            rng,
+        );
+        terrain
+    }
+}
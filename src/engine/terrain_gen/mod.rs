@@ -0,0 +1,203 @@
+pub mod bsp;
+pub mod cave;
+pub mod connectivity;
+pub mod field;
+pub mod river;
+
+use crate::engine::hazards::{Hazard, HazardKind};
+use crate::engine::terrain::{TerrainInfo, TerrainType};
+use crate::engine::terrain_gen::bsp::BspParams;
+use crate::engine::terrain_gen::cave::CaveParams;
+use crate::engine::terrain_gen::connectivity::connect_regions;
+use crate::engine::terrain_gen::field::FieldParams;
+use crate::engine::terrain_gen::river::RiverParams;
+
+use rand::Rng;
+use rand::RngCore;
+
+const MAX_FEATURE_RADIUS: i32 = 3;
+
+pub trait TerrainGenerator {
+    fn width(&self) -> usize;
+
+    fn height(&self) -> usize;
+
+    fn hazard_budget(&self) -> u32;
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec<TerrainInfo>;
+}
+
+// one variant per map style, each holding that generator's configuration
+pub enum TerrainGenParams {
+    Bsp(BspParams),
+    Cave(CaveParams),
+    Field(FieldParams),
+    River(RiverParams),
+}
+
+impl TerrainGenParams {
+    pub fn generator(&self) -> &dyn TerrainGenerator {
+        match self {
+            TerrainGenParams::Bsp(p) => p,
+            TerrainGenParams::Cave(p) => p,
+            TerrainGenParams::Field(p) => p,
+            TerrainGenParams::River(p) => p,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.generator().width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.generator().height()
+    }
+
+    pub fn hazard_budget(&self) -> u32 {
+        self.generator().hazard_budget()
+    }
+}
+
+fn idx(x: usize, y: usize, width: usize) -> usize {
+    x + y * width
+}
+
+// scatters blobs of difficult/climbable/swimmable terrain over existing floor
+fn scatter_features(
+    terrain: &mut [TerrainInfo],
+    width: usize,
+    height: usize,
+    n_features: usize,
+    rng: &mut dyn RngCore,
+) {
+    let floor_tiles: Vec<usize> = (0..terrain.len())
+        .filter(|&i| terrain[i].terrain_type == TerrainType::Floor)
+        .collect();
+    if floor_tiles.is_empty() {
+        return;
+    }
+
+    for _ in 0..n_features {
+        let center = floor_tiles[rng.random_range(0..floor_tiles.len())];
+        let cx = (center % width) as isize;
+        let cy = (center / width) as isize;
+        let radius = rng.random_range(1..=MAX_FEATURE_RADIUS) as isize;
+        let feature = match rng.random_range(0..5) {
+            0 => TerrainType::Rubble,
+            1 => TerrainType::Undergrowth,
+            2 => TerrainType::ShallowWater,
+            3 => TerrainType::DeepWater,
+            _ => TerrainType::Cliff,
+        };
+
+        for x in (cx - radius)..=(cx + radius) {
+            for y in (cy - radius)..=(cy + radius) {
+                if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+                    continue;
+                }
+                let dist_sq = (x - cx).pow(2) + (y - cy).pow(2);
+                if dist_sq > radius.pow(2) {
+                    continue;
+                }
+                let i = idx(x as usize, y as usize, width);
+                if terrain[i].terrain_type != TerrainType::Floor {
+                    continue;
+                }
+                // deep water is ringed by shallows
+                terrain[i].terrain_type =
+                    if feature == TerrainType::DeepWater && dist_sq > (radius - 1).pow(2) {
+                        TerrainType::ShallowWater
+                    } else {
+                        feature.clone()
+                    };
+            }
+        }
+    }
+}
+
+const HAZARD_KINDS: [HazardKind; 4] = [
+    HazardKind::Fire,
+    HazardKind::Acid,
+    HazardKind::Pit,
+    HazardKind::PressurePlate,
+];
+const HIDDEN_PIT_PROB: f32 = 0.5;
+const MIN_HIDDEN_DC: i32 = 10;
+const MAX_HIDDEN_DC: i32 = 16;
+
+// spends the budget on hazards over open floor; fire and acid spread over a
+// small patch, pits cover a 5 ft square, pressure plates a single hidden tile
+fn place_hazards(
+    terrain: &mut [TerrainInfo],
+    width: usize,
+    height: usize,
+    mut budget: u32,
+    rng: &mut dyn RngCore,
+) {
+    let floor_tiles: Vec<usize> = (0..terrain.len())
+        .filter(|&i| terrain[i].terrain_type == TerrainType::Floor)
+        .collect();
+    if floor_tiles.is_empty() {
+        return;
+    }
+
+    loop {
+        let affordable: Vec<HazardKind> = HAZARD_KINDS
+            .into_iter()
+            .filter(|k| k.budget_cost() <= budget)
+            .collect();
+        if affordable.is_empty() {
+            return;
+        }
+        let kind = affordable[rng.random_range(0..affordable.len())];
+        budget -= kind.budget_cost();
+
+        let center = floor_tiles[rng.random_range(0..floor_tiles.len())];
+        let cx = (center % width) as isize;
+        let cy = (center / width) as isize;
+        let hazard = match kind {
+            HazardKind::Pit if rng.random::<f32>() < HIDDEN_PIT_PROB => {
+                Hazard::hidden(kind, rng.random_range(MIN_HIDDEN_DC..=MAX_HIDDEN_DC))
+            }
+            HazardKind::PressurePlate => {
+                Hazard::hidden(kind, rng.random_range(MIN_HIDDEN_DC..=MAX_HIDDEN_DC))
+            }
+            _ => Hazard::new(kind),
+        };
+        let offsets: Vec<(isize, isize)> = match kind {
+            HazardKind::Fire | HazardKind::Acid => {
+                vec![(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)]
+            }
+            HazardKind::Pit => vec![(0, 0), (1, 0), (0, 1), (1, 1)],
+            HazardKind::PressurePlate => vec![(0, 0)],
+        };
+        for (dx, dy) in offsets {
+            let (x, y) = (cx + dx, cy + dy);
+            if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+                continue;
+            }
+            let ti = &mut terrain[idx(x as usize, y as usize, width)];
+            if ti.terrain_type == TerrainType::Floor && ti.hazard.is_none() {
+                ti.hazard = Some(hazard.clone());
+            }
+        }
+    }
+}
+
+pub fn generate_terrain(params: &TerrainGenParams, rng: &mut dyn RngCore) -> Vec<TerrainInfo> {
+    let mut terrain = params.generator().generate(rng);
+    // no region may be cut off, or teams could spawn where they never meet
+    connect_regions(&mut terrain, params.width(), params.height());
+    place_hazards(
+        &mut terrain,
+        params.width(),
+        params.height(),
+        params.hazard_budget(),
+        rng,
+    );
+    for ti in terrain.iter_mut() {
+        ti.hitpoints = ti.terrain_type.max_hitpoints();
+        ti.elevation = ti.terrain_type.base_elevation();
+    }
+    terrain
+}
@@ -0,0 +1,81 @@
+use crate::engine::terrain::{TerrainInfo, TerrainType};
+use crate::engine::terrain_gen::field::scatter_obstacles;
+use crate::engine::terrain_gen::{TerrainGenerator, idx};
+
+use rand::Rng;
+use rand::RngCore;
+
+// width of the shallows on either bank
+const BANK_WIDTH: usize = 1;
+
+// a river running left to right across open ground, crossed by one bridge
+pub struct RiverParams {
+    pub width: usize,
+    pub height: usize,
+    pub river_width: usize,
+    pub bridge_width: usize,
+    pub tree_density: f32,
+    pub n_boulders: usize,
+    pub hazard_budget: u32,
+}
+
+fn carve_river(params: &RiverParams, rng: &mut dyn RngCore) -> Vec<TerrainInfo> {
+    let mut terrain = vec![TerrainInfo::new(TerrainType::Floor); params.width * params.height];
+    let channel = params.river_width + 2 * BANK_WIDTH;
+    if channel >= params.height {
+        return terrain;
+    }
+
+    let bridge_width = params.bridge_width.min(params.width);
+    let bridge_x = rng.random_range(0..=params.width - bridge_width);
+
+    // lowest row of the channel drifts up and down as the river meanders
+    let mut bottom = (params.height - channel) / 2;
+    for x in 0..params.width {
+        // the bridge itself runs straight across
+        let on_bridge = x >= bridge_x && x < bridge_x + bridge_width;
+        match rng.random_range(0..4) {
+            0 if !on_bridge && bottom > 0 => bottom -= 1,
+            1 if !on_bridge && bottom + channel < params.height => bottom += 1,
+            _ => {}
+        }
+        for y_off in 0..channel {
+            let tt = if on_bridge {
+                TerrainType::Bridge
+            } else if y_off < BANK_WIDTH || y_off >= channel - BANK_WIDTH {
+                TerrainType::ShallowWater
+            } else {
+                TerrainType::DeepWater
+            };
+            terrain[idx(x, bottom + y_off, params.width)].terrain_type = tt;
+        }
+    }
+    terrain
+}
+
+impl TerrainGenerator for RiverParams {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn hazard_budget(&self) -> u32 {
+        self.hazard_budget
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec<TerrainInfo> {
+        let mut terrain = carve_river(self, rng);
+        scatter_obstacles(
+            &mut terrain,
+            self.width,
+            self.height,
+            self.tree_density,
+            self.n_boulders,
+            rng,
+        );
+        terrain
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use crate::actions::default_actions::DEFAULT_ACTIONS;
+use crate::actors::actor_template::CreatureTemplate;
+use crate::ai::profiles::AiProfiles;
+use crate::ai::tactical::ProfiledAi;
+use crate::engine::controller::TeamControllers;
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::map_loader::parse_map;
+use crate::engine::types::Size;
+
+// two zombies facing each other across open floor, each in its team's zone
+pub const FACING_ZOMBIES: &str =
+    "[map]\n00....11\n00....11\n[creatures]\nZombie 0,0 0\nZombie 6,0 1\n";
+
+// an encounter built from the text of a map file
+pub fn encounter_from(map: &str, seed: u64) -> EncounterInstance {
+    EncounterInstance::from_map(parse_map(map).unwrap(), None, seed).unwrap()
+}
+
+// lets the AI answer up to n prompts for both teams
+pub fn play(encounter: &mut EncounterInstance, n: usize) {
+    let mut controllers = TeamControllers::new();
+    for team_id in [0, 1] {
+        controllers.assign(team_id, Box::new(ProfiledAi::new(AiProfiles::builtin())));
+    }
+    for _ in 0..n {
+        encounter.process_stack().unwrap();
+        if !controllers.answer_prompt(encounter) {
+            break;
+        }
+    }
+    encounter.process_stack().unwrap();
+}
+
+// a medium flyer, to go over whatever is on the ground
+pub static GIANT_BAT: LazyLock<CreatureTemplate> = LazyLock::new(|| CreatureTemplate {
+    name: "Giant Bat",
+    n_instances: 0,
+    ac: 13,
+    hitpoints: "4d10".parse().unwrap(),
+    speed: 10.,
+    climb_speed: 0.,
+    swim_speed: 0.,
+    fly_speed: 60.,
+    strength: 15,
+    intelligence: 2,
+    dexterity: 16,
+    wisdom: 12,
+    constitution: 11,
+    charisma: 6,
+    skills: HashSet::new(),
+    items: Vec::new(),
+    senses: HashSet::new(),
+    languages: HashSet::new(),
+    cr: 0.25,
+    size: Size::Medium,
+    actions: DEFAULT_ACTIONS.clone(),
+    ai_profile: "skirmisher",
+});
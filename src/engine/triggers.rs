@@ -1,4 +1,17 @@
+use crate::engine::types::Coordinate;
+
 pub enum TriggerEventType {
     Enqueue,
     Execute,
 }
+
+// what a stack element did that other things may react to
+pub enum TriggerEvent {
+    // positions of the actor's footprint along the way, starting where it was
+    Moved {
+        actor_id: usize,
+        path: Vec<Coordinate>,
+        // movement spent on the whole path
+        movement_paid: f32,
+    },
+}
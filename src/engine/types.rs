@@ -1,7 +1,9 @@
 use std::fmt;
 use std::ops::{Add, Sub};
 
-#[derive(Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AbilityScoreType {
     Strength,
     Dexterity,
@@ -11,7 +13,7 @@ pub enum AbilityScoreType {
     Charisma,
 }
 
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Debug, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub enum Skill {
     Acrobatics,
     AnimalHandling,
@@ -33,7 +35,29 @@ pub enum Skill {
     Survival,
 }
 
-#[derive(Clone, PartialEq)]
+impl Skill {
+    pub fn ability(&self) -> AbilityScoreType {
+        match self {
+            Skill::Athletics => AbilityScoreType::Strength,
+            Skill::Acrobatics | Skill::SlightOfHand | Skill::Stealth => AbilityScoreType::Dexterity,
+            Skill::Arcana
+            | Skill::History
+            | Skill::Investigation
+            | Skill::Nature
+            | Skill::Religion => AbilityScoreType::Intelligence,
+            Skill::AnimalHandling
+            | Skill::Insight
+            | Skill::Medicine
+            | Skill::Perception
+            | Skill::Survival => AbilityScoreType::Wisdom,
+            Skill::Deception | Skill::Intimidation | Skill::Performance | Skill::Persuasion => {
+                AbilityScoreType::Charisma
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DamageType {
     Acid,
     Bludgeoning,
@@ -50,7 +74,7 @@ pub enum DamageType {
     Thunder,
 }
 
-#[derive(Clone, PartialEq, Copy)]
+#[derive(Clone, PartialEq, Copy, Serialize, Deserialize)]
 pub enum Size {
     Tiny,
     Small,
@@ -60,7 +84,7 @@ pub enum Size {
     Gargantuan,
 }
 
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub enum Language {
     Common,
     CommonSignLanguage,
@@ -83,7 +107,7 @@ pub enum Language {
     Undercommon,
 }
 
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub enum SpecialSense {
     Blindsight(u32),
     Darkvision(u32),
@@ -91,7 +115,7 @@ pub enum SpecialSense {
     Truesight(u32),
 }
 
-#[derive(Debug, Clone, PartialEq, Hash, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Hash, Eq, Copy, Serialize, Deserialize)]
 pub struct Coordinate {
     pub x: isize,
     pub y: isize,
@@ -99,7 +123,7 @@ pub struct Coordinate {
 
 impl Coordinate {
     pub fn new(x: isize, y: isize) -> Self {
-        Self { x: x, y: y }
+        Self { x, y }
     }
 }
 
@@ -1,8 +1,12 @@
-use ratatui::style::Color;
 use regex::Regex;
 
+use crate::engine::hazards::HazardKind;
+use crate::engine::terrain::{DoorState, TerrainType};
 use crate::engine::types::{Coordinate, Size};
 
+// a medium creature spans 2x2 tiles, so each tile is half of a 5 ft square
+pub const FEET_PER_TILE: f32 = 2.5;
+
 pub fn get_tiles_from_size(size: Size) -> usize {
     match size {
         Size::Tiny => 1,
@@ -14,32 +18,90 @@ pub fn get_tiles_from_size(size: Size) -> usize {
     }
 }
 
+// a creature can squeeze through a space wide enough for one a size smaller
+pub fn squeezed_tiles_from_size(size: Size) -> usize {
+    match size {
+        Size::Tiny => 1,
+        Size::Small => 1,
+        Size::Medium => 2,
+        Size::Large => 2,
+        Size::Huge => 4,
+        Size::Gargantuan => 6,
+    }
+}
+
+// tiles of a width x width footprint with its min corner at coord
+pub fn square_footprint(coord: Coordinate, width: usize) -> Vec<Coordinate> {
+    let mut tiles: Vec<Coordinate> = Vec::with_capacity(width * width);
+    for x_off in 0..width {
+        for y_off in 0..width {
+            tiles.push(coord + Coordinate::new(x_off as isize, y_off as isize));
+        }
+    }
+    tiles
+}
+
 const ACTOR_CHARS: [char; 28] = [
     'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
     't', 'u', 'v', 'w', 'x', 'y', 'z', '*', '!',
 ];
 
-pub fn get_colored_span(n: usize, team: usize) -> (String, Color, Color) {
-    let s = ACTOR_CHARS[n % ACTOR_CHARS.len()].to_string();
+// inverse of actor_char
+pub fn parse_actor_char(input: &str) -> Option<usize> {
+    let mut chars = input.chars();
+    let c = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    ACTOR_CHARS.iter().position(|&ac| ac == c)
+}
 
-    let team_u8: u8 = (team % 16) as u8;
-    let color = Color::Indexed(team_u8);
-    let bg = if team_u8 < 1 {
-        Color::LightCyan
-    } else {
-        Color::Black
-    };
+pub fn actor_char_count() -> usize {
+    ACTOR_CHARS.len()
+}
+
+pub fn actor_char(n: usize) -> char {
+    ACTOR_CHARS[n % ACTOR_CHARS.len()]
+}
 
-    (s, color, bg)
+// how terrain is drawn on the map, and written in map files
+pub fn terrain_glyph(terrain_type: &TerrainType) -> char {
+    match terrain_type {
+        TerrainType::Empty => ' ',
+        TerrainType::Floor => '░',
+        TerrainType::Wall => '█',
+        TerrainType::Door(DoorState::Open) => '/',
+        TerrainType::Door(DoorState::Closed) => '+',
+        TerrainType::Door(DoorState::Locked) => '+',
+        TerrainType::Tree => '♣',
+        TerrainType::Boulder => '●',
+        TerrainType::Bridge => '=',
+        TerrainType::Rubble => '▒',
+        TerrainType::Undergrowth => '"',
+        TerrainType::ShallowWater => '~',
+        TerrainType::DeepWater => '≈',
+        TerrainType::Cliff => '▲',
+    }
+}
+
+pub fn hazard_glyph(kind: HazardKind) -> char {
+    match kind {
+        HazardKind::Fire => '*',
+        HazardKind::Acid => '%',
+        HazardKind::Pit => 'O',
+        HazardKind::PressurePlate => '□',
+    }
 }
 
 pub fn modifier_from_score(score: u32) -> i32 {
     (score as i32 / 2) - 5
 }
 
-pub fn tile_center_dist(c1: Coordinate, c2: Coordinate) -> f32 {
+// dz is the difference in height in feet
+pub fn tile_center_dist(c1: Coordinate, c2: Coordinate, dz: f32) -> f32 {
     let diff = c1 - c2;
-    2.5 * ((diff.x.pow(2) + diff.y.pow(2)) as f32).sqrt()
+    let horizontal_sq = FEET_PER_TILE.powi(2) * (diff.x.pow(2) + diff.y.pow(2)) as f32;
+    (horizontal_sq + dz.powi(2)).sqrt()
 }
 
 pub fn parse_coord(input: &str, base_coord: Coordinate) -> Option<Coordinate> {
@@ -0,0 +1,358 @@
+use std::collections::{BTreeSet, HashSet};
+
+use crate::engine::combat::actor_span;
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::events::EventSubject;
+use crate::engine::types::Coordinate;
+
+// keeps lines that only graze an edge or corner from counting as blocked
+const GRAZE_EPSILON: f32 = 1e-4;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Cover {
+    None,
+    Half,
+    ThreeQuarters,
+    Total,
+}
+
+impl Cover {
+    pub fn ac_bonus(&self) -> i32 {
+        match self {
+            Cover::None => 0,
+            Cover::Half => 2,
+            Cover::ThreeQuarters => 5,
+            Cover::Total => 0,
+        }
+    }
+
+    pub fn dex_save_bonus(&self) -> i32 {
+        self.ac_bonus()
+    }
+}
+
+// a point in tile space; tile (x, y) spans [x, x + 1] x [y, y + 1]
+type Point = (f32, f32);
+
+fn tile_center(coord: Coordinate) -> Point {
+    (coord.x as f32 + 0.5, coord.y as f32 + 0.5)
+}
+
+fn tile_corners(coord: Coordinate) -> [Point; 4] {
+    let (x, y) = (coord.x as f32, coord.y as f32);
+    [(x, y), (x + 1., y), (x, y + 1.), (x + 1., y + 1.)]
+}
+
+// Liang-Barsky clip against the tile shrunk by GRAZE_EPSILON
+fn segment_enters_tile(p0: Point, p1: Point, tile: Coordinate) -> bool {
+    let min = (tile.x as f32 + GRAZE_EPSILON, tile.y as f32 + GRAZE_EPSILON);
+    let max = (
+        tile.x as f32 + 1. - GRAZE_EPSILON,
+        tile.y as f32 + 1. - GRAZE_EPSILON,
+    );
+    let d = (p1.0 - p0.0, p1.1 - p0.1);
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+    for (p, q) in [
+        (-d.0, p0.0 - min.0),
+        (d.0, max.0 - p0.0),
+        (-d.1, p0.1 - min.1),
+        (d.1, max.1 - p0.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return false;
+            }
+            continue;
+        }
+        let r = q / p;
+        if p < 0.0 {
+            t0 = t0.max(r);
+        } else {
+            t1 = t1.min(r);
+        }
+        if t0 > t1 {
+            return false;
+        }
+    }
+    true
+}
+
+// every tile the segment could pass through, column by column
+fn candidate_tiles(p0: Point, p1: Point) -> Vec<Coordinate> {
+    let mut tiles: Vec<Coordinate> = Vec::new();
+    let (min_x, max_x) = (p0.0.min(p1.0), p0.0.max(p1.0));
+    for cx in (min_x.floor() as isize)..=(max_x.floor() as isize) {
+        let (y_a, y_b) = if p0.0 == p1.0 {
+            (p0.1, p1.1)
+        } else {
+            let slope = (p1.1 - p0.1) / (p1.0 - p0.0);
+            let xa = (cx as f32).max(min_x);
+            let xb = (cx as f32 + 1.).min(max_x);
+            (p0.1 + slope * (xa - p0.0), p0.1 + slope * (xb - p0.0))
+        };
+        for cy in (y_a.min(y_b).floor() as isize)..=(y_a.max(y_b).floor() as isize) {
+            tiles.push(Coordinate::new(cx, cy));
+        }
+    }
+    tiles
+}
+
+// a line running along the edge between two rows or columns of tiles only
+// grazes them, so it is stopped wherever the tiles on both sides block
+fn seam_blocked<F: Fn(Coordinate) -> bool>(p0: Point, p1: Point, blocks: &F) -> bool {
+    // the tiles the seam runs past, from a to b, with those on either side
+    let blocked_along = |a: f32, b: f32, sides: &dyn Fn(isize) -> [Coordinate; 2]| {
+        let first = (a.min(b) + GRAZE_EPSILON).floor() as isize;
+        let last = (a.max(b) - GRAZE_EPSILON).ceil() as isize;
+        (first..last).any(|i| sides(i).iter().all(|&t| blocks(t)))
+    };
+    if p0.1 == p1.1 && p0.1.fract() == 0. {
+        let y = p0.1 as isize;
+        return blocked_along(p0.0, p1.0, &|x| {
+            [Coordinate::new(x, y - 1), Coordinate::new(x, y)]
+        });
+    }
+    if p0.0 == p1.0 && p0.0.fract() == 0. {
+        let x = p0.0 as isize;
+        return blocked_along(p0.1, p1.1, &|y| {
+            [Coordinate::new(x - 1, y), Coordinate::new(x, y)]
+        });
+    }
+    false
+}
+
+fn segment_blocked<F: Fn(Coordinate) -> bool>(p0: Point, p1: Point, blocks: F) -> bool {
+    seam_blocked(p0, p1, &blocks)
+        || candidate_tiles(p0, p1)
+            .into_iter()
+            .any(|tile| blocks(tile) && segment_enters_tile(p0, p1, tile))
+}
+
+// cover of a target against an effect whose origin is the given set of corners;
+// the origin picks its best corner and the best square of the target
+fn cover_from_corners(
+    encounter: &EncounterInstance,
+    origins: &[Point],
+    ignore: &[usize],
+    target_id: usize,
+) -> Cover {
+    let walls_block = |c: Coordinate| encounter.blocks_sight(c);
+    let creature_blocks = |c: Coordinate| {
+        if !encounter.in_bounds(c) {
+            return false;
+        }
+        encounter
+            .actors_at(c)
+            .iter()
+            .any(|id| *id != target_id && !ignore.contains(id))
+    };
+
+    let mut best = Cover::Total;
+    for &origin in origins.iter() {
+        for &tile in encounter.footprint(target_id) {
+            let mut walls_blocked: usize = 0;
+            let mut creature_blocked = false;
+            for corner in tile_corners(tile) {
+                if segment_blocked(origin, corner, walls_block) {
+                    walls_blocked += 1;
+                } else if segment_blocked(origin, corner, creature_blocks) {
+                    creature_blocked = true;
+                }
+            }
+            // the DMG's rule for grids: one or two blocked corners give half
+            // cover and three give three-quarters. It allows three-quarters
+            // for four as well while the attack can still reach the target,
+            // but only lines to the corners are checked here, so four blocked
+            // corners are always total cover
+            let cover = match walls_blocked {
+                0 if creature_blocked => Cover::Half,
+                0 => Cover::None,
+                1 | 2 => Cover::Half,
+                3 => Cover::ThreeQuarters,
+                _ => Cover::Total,
+            };
+            best = best.min(cover);
+            if best == Cover::None {
+                return best;
+            }
+        }
+    }
+    best
+}
+
+pub fn has_line_of_sight(encounter: &EncounterInstance, from: Coordinate, to: Coordinate) -> bool {
+    !segment_blocked(tile_center(from), tile_center(to), |c| {
+        encounter.blocks_sight(c)
+    })
+}
+
+// a tile a creature looks from, and the height in feet of its eyes there
+type Eye = (Coordinate, f32);
+
+// standing members of the team look from every tile they take up, from the
+// top of their height
+fn team_eyes(encounter: &EncounterInstance, team_id: usize) -> Vec<Eye> {
+    encounter
+        .actors
+        .iter()
+        .filter(|(_, a)| a.team() == team_id && !a.is_down())
+        .flat_map(|(&actor_id, _)| {
+            let height = actor_span(encounter, actor_id).1;
+            encounter
+                .footprint(actor_id)
+                .iter()
+                .map(move |&tile| (tile, height))
+        })
+        .collect()
+}
+
+// like line of sight, but the tile looked at may block sight itself, so walls
+// and closed doors can be seen, and obstacles the eye is above do not block it
+fn eye_sees_tile(encounter: &EncounterInstance, eye: Eye, to: Coordinate) -> bool {
+    let (from, height) = eye;
+    !segment_blocked(tile_center(from), tile_center(to), |c| {
+        c != to
+            && encounter.blocks_sight(c)
+            && encounter.terrain_at(c).is_none_or(|ti| ti.top() >= height)
+    })
+}
+
+// every tile some standing member of the team has in sight
+pub fn visible_to_team(encounter: &EncounterInstance, team_id: usize) -> HashSet<Coordinate> {
+    let eyes = team_eyes(encounter, team_id);
+    let mut visible: HashSet<Coordinate> = HashSet::new();
+    for x in 0..encounter.width {
+        for y in 0..encounter.height {
+            let coord = Coordinate::new(x as isize, y as isize);
+            if eyes.iter().any(|&eye| eye_sees_tile(encounter, eye, coord)) {
+                visible.insert(coord);
+            }
+        }
+    }
+    visible
+}
+
+// the teams that see what the event is about right now. A team always
+// knows what its own creatures do, as it sees them in its view regardless
+pub fn witnesses(encounter: &EncounterInstance, subject: &EventSubject) -> BTreeSet<usize> {
+    let teams: BTreeSet<usize> = encounter.actors.values().map(|a| a.team()).collect();
+    let (tiles, own_team) = match subject {
+        EventSubject::Everyone => return teams,
+        EventSubject::Actor(actor_id) => (
+            encounter.footprint(*actor_id).to_vec(),
+            encounter.actors.get(actor_id).map(|a| a.team()),
+        ),
+        EventSubject::Place(coord) => (vec![*coord], None),
+    };
+    teams
+        .into_iter()
+        .filter(|&team_id| {
+            own_team == Some(team_id)
+                || team_eyes(encounter, team_id)
+                    .iter()
+                    .any(|&eye| tiles.iter().any(|&t| eye_sees_tile(encounter, eye, t)))
+        })
+        .collect()
+}
+
+pub fn cover_between(encounter: &EncounterInstance, attacker_id: usize, target_id: usize) -> Cover {
+    let origins: Vec<Point> = encounter
+        .footprint(attacker_id)
+        .iter()
+        .copied()
+        .flat_map(tile_corners)
+        .collect();
+    cover_from_corners(encounter, &origins, &[attacker_id], target_id)
+}
+
+pub fn cover_from_point(
+    encounter: &EncounterInstance,
+    origin: Coordinate,
+    target_id: usize,
+) -> Cover {
+    cover_from_corners(encounter, &[tile_center(origin)], &[], target_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::events::EncounterEvent;
+    use crate::engine::test_util::encounter_from;
+
+    const MAP: &str = "[map]\n00..#..11\n00..#..11\n[creatures]\nZombie 0,0 0\nZombie 7,0 1\n";
+
+    fn note_at(encounter: &mut EncounterInstance, location: Coordinate) -> BTreeSet<usize> {
+        encounter.log(EncounterEvent::Note {
+            text: "something happens".to_string(),
+            actor_id: None,
+            location: Some(location),
+        });
+        encounter.events().last().unwrap().seen_by.clone().unwrap()
+    }
+
+    // a zombie of team 1 at 8,0, with a wall of the given rows between it
+    // and the left of the map
+    fn behind(rows: [&str; 5], between: &str) -> EncounterInstance {
+        let map = format!(
+            "[map]\n{}\n[creatures]\nZombie 8,0 1\n{}",
+            rows.join("\n"),
+            between
+        );
+        encounter_from(&map, 0)
+    }
+
+    const OPEN: &str = "..........";
+    const WALL: &str = "......#...";
+
+    #[test]
+    fn cover_counts_the_corners_walls_block() {
+        let from = |encounter: &EncounterInstance, y: isize| {
+            cover_from_point(encounter, Coordinate::new(1, y), 0)
+        };
+
+        let open = behind([OPEN; 5], "");
+        assert_eq!(from(&open, 0), Cover::None);
+
+        // a low wall hides the bottom corners of every square
+        let low_wall = behind([OPEN, OPEN, OPEN, OPEN, WALL], "");
+        assert_eq!(from(&low_wall, 0), Cover::Half);
+
+        // looking down through an arrow slit only one corner shows
+        let slit = behind([WALL, WALL, WALL, OPEN, WALL], "");
+        assert_eq!(from(&slit, 4), Cover::ThreeQuarters);
+
+        let full_wall = behind([WALL; 5], "");
+        for y in 0..5 {
+            assert_eq!(from(&full_wall, y), Cover::Total);
+        }
+
+        // creatures in the way give half cover at most
+        let crowded = behind([OPEN; 5], "Zombie 4,0 0\n");
+        assert_eq!(from(&crowded, 0), Cover::Half);
+    }
+
+    #[test]
+    fn lines_along_the_seams_of_a_wall_do_not_get_through() {
+        let encounter = encounter_from(MAP, 0);
+        assert_eq!(cover_between(&encounter, 0, 1), Cover::Total);
+        assert_eq!(cover_between(&encounter, 1, 0), Cover::Total);
+    }
+
+    #[test]
+    fn a_wall_hides_what_happens_behind_it_unless_seen_over() {
+        let mut encounter = encounter_from(MAP, 0);
+        encounter.track_sight();
+        let behind = Coordinate::new(8, 1);
+        assert!(!visible_to_team(&encounter, 0).contains(&behind));
+        assert_eq!(note_at(&mut encounter, behind), BTreeSet::from([1]));
+        assert_eq!(
+            witnesses(&encounter, &EventSubject::Actor(0)),
+            BTreeSet::from([0])
+        );
+
+        encounter.actors.get_mut(&0).unwrap().set_altitude(10.);
+        assert!(visible_to_team(&encounter, 0).contains(&behind));
+        assert_eq!(note_at(&mut encounter, behind), BTreeSet::from([0, 1]));
+    }
+}
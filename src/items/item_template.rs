@@ -1,2 +1,4 @@
-#[derive(PartialEq, Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
 pub struct Item {}
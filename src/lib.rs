@@ -0,0 +1,27 @@
+// The encounter engine, for the terminal game in main.rs and anything else
+// that wants to run encounters. The usual way through it:
+// - build an EncounterInstance with from_params or from_map, or load one
+//   with engine::save::load_encounter
+// - step it with process_stack until a prompt is on top, and read the state
+//   through its accessors
+// - answer the prompt with submit, or let a TeamControllers do it
+// - follow what happens with subscribe, or read events afterwards
+// net hosts an encounter for players on other machines.
+// Rendering lives in render, behind the tui feature, so that the engine can
+// be used without a terminal.
+pub mod actions;
+pub mod actors;
+pub mod ai;
+pub mod conditions;
+pub mod engine;
+pub mod items;
+pub mod net;
+#[cfg(feature = "tui")]
+pub mod render;
+pub mod simulation;
+
+pub use crate::actions::action_template::ActionExecutionInfo;
+pub use crate::engine::encounter::EncounterInstance;
+pub use crate::engine::errors::EngineError;
+pub use crate::engine::events::{EncounterEvent, LoggedEvent};
+pub use crate::engine::prompt::Prompt;
@@ -1,12 +1,26 @@
-pub mod actions;
-pub mod actors;
-pub mod conditions;
-pub mod engine;
-pub mod items;
-
-use crate::engine::actor_gen::ActorGenParams;
-use crate::engine::encounter::EncounterInstance;
-use crate::engine::terrain_gen::TerrainGenParams;
+use dnd_rs::actions::scripted::load_scripts;
+use dnd_rs::ai::profiles::{AiProfiles, load_profiles};
+use dnd_rs::ai::tactical::ProfiledAi;
+use dnd_rs::engine::actor_gen::{ActorGenParams, SpawnLayout};
+use dnd_rs::engine::controller::TeamControllers;
+use dnd_rs::engine::difficulty::Difficulty;
+use dnd_rs::engine::encounter::EncounterInstance;
+use dnd_rs::engine::events::write_jsonl;
+use dnd_rs::engine::history::History;
+use dnd_rs::engine::map_loader::load_map;
+use dnd_rs::engine::pathfinding::DiagonalRule;
+use dnd_rs::engine::save::{load_encounter, save_encounter};
+use dnd_rs::engine::terrain_gen::TerrainGenParams;
+use dnd_rs::engine::terrain_gen::bsp::BspParams;
+use dnd_rs::engine::terrain_gen::cave::CaveParams;
+use dnd_rs::engine::terrain_gen::field::FieldParams;
+use dnd_rs::engine::terrain_gen::river::RiverParams;
+use dnd_rs::net::client::run_client;
+use dnd_rs::net::server::serve;
+use dnd_rs::render::map::render_map;
+use dnd_rs::render::sideinfo::render_sideinfo;
+use dnd_rs::render::viewport::Viewport;
+use dnd_rs::simulation::batch::{BatchConfig, default_threads, run_batch};
 
 use crossterm::{
     event::{self, Event, KeyCode},
@@ -20,11 +34,337 @@ use ratatui::{
     text::{Span, Text},
     widgets::{Block, Borders, Paragraph},
 };
-use std::io;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::net::TcpListener;
+use std::thread;
+
+// the current actor's pane next to the map never gets narrower than this
+const MIN_SIDEINFO_WIDTH: u16 = 32;
 
-const HEIGHT: usize = 64;
+fn terrain_params_for(map_style: &str, width: usize, height: usize) -> Option<TerrainGenParams> {
+    match map_style {
+        "bsp" => Some(TerrainGenParams::Bsp(BspParams {
+            width,
+            height,
+            branch_depth: 8,
+            branch_prob: 0.5,
+            n_features: 6,
+            door_prob: 0.5,
+            locked_door_prob: 0.25,
+            hazard_budget: 6,
+        })),
+        "cave" => Some(TerrainGenParams::Cave(CaveParams {
+            width,
+            height,
+            fill_prob: 0.42,
+            n_iterations: 4,
+            n_features: 3,
+            hazard_budget: 6,
+        })),
+        "field" => Some(TerrainGenParams::Field(FieldParams {
+            width,
+            height,
+            tree_density: 0.04,
+            n_boulders: 4,
+            n_features: 4,
+            hazard_budget: 6,
+        })),
+        "river" => Some(TerrainGenParams::River(RiverParams {
+            width,
+            height,
+            river_width: 4,
+            bridge_width: 4,
+            tree_density: 0.03,
+            n_boulders: 2,
+            hazard_budget: 6,
+        })),
+        _ => None,
+    }
+}
+
+// a comma-separated list of team ids; exits with the example on anything else
+fn parse_teams(s: &str, example: &str) -> Vec<usize> {
+    s.split(',')
+        .map(|team| match team.trim().parse::<usize>() {
+            Ok(team_id) => team_id,
+            Err(_) => {
+                eprintln!("invalid team {:?}; expected e.g. {}", team, example);
+                std::process::exit(1);
+            }
+        })
+        .collect()
+}
 
 fn main() -> io::Result<()> {
+    // TODO: proper argument parsing
+    let args: Vec<String> = std::env::args().collect();
+    let arg_value = |flag: &str| -> Option<&str> {
+        let i = args.iter().position(|a| a == flag)?;
+        Some(args.get(i + 1).map(|s| s.as_str()).unwrap_or(""))
+    };
+    let map_style: &str = arg_value("--map").unwrap_or("bsp");
+    // generated map size as WxH, e.g. 150x150
+    let (width, height): (usize, usize) = match arg_value("--size") {
+        Some(s) => match s
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+        {
+            Some((w, h)) if w >= 10 && h >= 10 => (w, h),
+            _ => {
+                eprintln!("invalid size {:?}; expected WxH of at least 10x10", s);
+                std::process::exit(1);
+            }
+        },
+        None => (40, 20),
+    };
+    let unknown_style = |style: &str| -> ! {
+        eprintln!(
+            "unknown map style {:?}; expected bsp, cave, field or river",
+            style
+        );
+        std::process::exit(1);
+    };
+    // the same seed and inputs replay the same encounter
+    let seed_arg: Option<u64> = arg_value("--seed").map(|s| match s.parse() {
+        Ok(seed) => seed,
+        Err(_) => {
+            eprintln!("invalid seed {:?}; expected an unsigned integer", s);
+            std::process::exit(1);
+        }
+    });
+
+    // each team is balanced as a fight for a party of these levels, e.g.
+    // --party 3,3,4 --difficulty hard
+    let party_levels: Vec<u32> = match arg_value("--party") {
+        Some(s) => match s
+            .split(',')
+            .map(|level| {
+                level
+                    .trim()
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|l| (1..=20).contains(l))
+            })
+            .collect::<Option<Vec<u32>>>()
+        {
+            Some(levels) => levels,
+            None => {
+                eprintln!("invalid party {:?}; expected levels 1-20 like 3,3,4", s);
+                std::process::exit(1);
+            }
+        },
+        None => vec![2; 4],
+    };
+    let difficulty = match arg_value("--difficulty") {
+        Some(s) => match Difficulty::from_name(s) {
+            Some(difficulty) => difficulty,
+            None => {
+                eprintln!(
+                    "unknown difficulty {:?}; expected easy, medium, hard or deadly",
+                    s
+                );
+                std::process::exit(1);
+            }
+        },
+        None => Difficulty::Medium,
+    };
+    let actor_params = ActorGenParams {
+        party_levels,
+        difficulty,
+        n_teams: 2,
+        spawn_layout: SpawnLayout::OppositeEdges,
+        min_team_distance: 30.0,
+    };
+    // each creature fights by its template's profile; --ai-profiles <file>
+    // tunes them, see profiles/default.ai
+    let profiles = match arg_value("--ai-profiles") {
+        Some(path) => match load_profiles(path) {
+            Ok(profiles) => profiles,
+            Err(e) => {
+                eprintln!("failed to load AI profiles {:?}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => AiProfiles::builtin(),
+    };
+
+    // how diagonal steps are charged: --diagonals uniform (the default, 5 ft
+    // each) or --diagonals alternating (5/10 ft)
+    let diagonal_rule = match arg_value("--diagonals") {
+        Some(s) => match DiagonalRule::from_name(s) {
+            Some(rule) => rule,
+            None => {
+                eprintln!(
+                    "invalid diagonal rule {:?}; expected uniform or alternating",
+                    s
+                );
+                std::process::exit(1);
+            }
+        },
+        None => DiagonalRule::default(),
+    };
+
+    // homebrew actions written in Rhai, e.g. --scripts scripts; see
+    // scripts/shove.rhai for what a script looks like
+    if let Some(dir) = arg_value("--scripts")
+        && let Err(e) = load_scripts(dir)
+    {
+        eprintln!("failed to load scripts from {:?}: {}", dir, e);
+        std::process::exit(1);
+    }
+
+    // plays teams on someone else's encounter instead of hosting one, e.g.
+    // --connect 192.168.1.5:7777 --teams 1
+    if let Some(address) = arg_value("--connect") {
+        let teams = parse_teams(arg_value("--teams").unwrap_or(""), "--teams 1");
+        return run_client(address, &teams);
+    }
+
+    // headless AI-vs-AI runs for balance testing, e.g. --simulate 1000 with
+    // --map bsp,cave for one report per map style; --threads sets how many
+    // run at once and --max-rounds when a fight counts as a draw
+    if let Some(s) = arg_value("--simulate") {
+        let Ok(runs) = s.parse::<usize>() else {
+            eprintln!("invalid run count {:?}; expected e.g. --simulate 1000", s);
+            std::process::exit(1);
+        };
+        let parse_count = |flag: &str, default: usize| -> usize {
+            match arg_value(flag) {
+                Some(s) => match s.parse::<usize>() {
+                    Ok(n) if n > 0 => n,
+                    _ => {
+                        eprintln!("invalid {} {:?}; expected a positive integer", flag, s);
+                        std::process::exit(1);
+                    }
+                },
+                None => default,
+            }
+        };
+        let n_threads = parse_count("--threads", default_threads());
+        let max_rounds = parse_count("--max-rounds", 100);
+        for style in map_style.split(',').map(|s| s.trim()) {
+            let Some(terrain_params) = terrain_params_for(style, width, height) else {
+                unknown_style(style);
+            };
+            let config = BatchConfig {
+                name: format!(
+                    "{} {}x{} {}",
+                    style,
+                    width,
+                    height,
+                    actor_params.difficulty.name()
+                ),
+                terrain_params,
+                actor_params: actor_params.clone(),
+                profiles: profiles.clone(),
+                runs,
+                first_seed: seed_arg.unwrap_or(0),
+                max_rounds,
+                diagonal_rule,
+            };
+            print!("{}", run_batch(&config, n_threads));
+        }
+        return Ok(());
+    }
+
+    let Some(terrain_params) = terrain_params_for(map_style, width, height) else {
+        unknown_style(map_style);
+    };
+    let seed: u64 = seed_arg.unwrap_or_else(rand::random);
+    let mut encounter_instance: EncounterInstance = match arg_value("--map-file") {
+        Some(path) => {
+            let map = match load_map(path) {
+                Ok(map) => map,
+                Err(e) => {
+                    eprintln!("{:?}: {}", path, e);
+                    std::process::exit(1);
+                }
+            };
+            // maps that place their own creatures are played as authored
+            let actor_params = map.creatures.is_empty().then_some(&actor_params);
+            match EncounterInstance::from_map(map, actor_params, seed) {
+                Ok(ei) => ei,
+                Err(e) => {
+                    eprintln!("failed to set up map {:?}: {}", path, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => match EncounterInstance::from_params(&terrain_params, &actor_params, seed) {
+            Ok(ei) => ei,
+            Err(e) => {
+                eprintln!("failed to set up the encounter: {}", e);
+                std::process::exit(1);
+            }
+        },
+    };
+    encounter_instance.set_diagonal_rule(diagonal_rule);
+    let (mut map_width, mut map_height) = (encounter_instance.width(), encounter_instance.height());
+    // teams played by the computer, e.g. --ai 1 or --ai 0,1; the rest are
+    // played from this terminal
+    let mut controllers = TeamControllers::new();
+    if let Some(s) = arg_value("--ai") {
+        for team_id in parse_teams(s, "--ai 1 or --ai 0,1") {
+            let mut ai = ProfiledAi::new(profiles.clone());
+            // lookahead_ms only keeps the terminal responsive; a served game
+            // is left to decide the same way every time
+            if arg_value("--serve").is_none() {
+                ai = ai.with_time_limits();
+            }
+            controllers.assign(team_id, Box::new(ai));
+        }
+    }
+    // everything that happens is written there as JSON lines as it happens,
+    // e.g. --event-log session.jsonl
+    let mut event_log = match arg_value("--event-log") {
+        Some(path) => match File::create(path) {
+            Ok(file) => {
+                let mut writer = BufWriter::new(file);
+                write_jsonl(encounter_instance.events().iter().cloned(), &mut writer)?;
+                Some((writer, encounter_instance.subscribe()))
+            }
+            Err(e) => {
+                eprintln!("failed to create event log {:?}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    // players place their creatures in their spawn zones before initiative
+    // is rolled; teams under --ai keep where they were generated
+    if args.iter().any(|a| a == "--deploy") {
+        let teams: BTreeSet<usize> = encounter_instance
+            .actors()
+            .values()
+            .map(|a| a.team())
+            .filter(|&team_id| !controllers.is_automated(team_id))
+            .collect();
+        let teams: Vec<usize> = teams.into_iter().collect();
+        if let Err(e) = encounter_instance.begin_deployment(&teams) {
+            eprintln!("failed to start deployment: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // hosts the encounter for --connect clients instead of playing it here,
+    // e.g. --serve 0.0.0.0:7777; teams not under --ai wait for a client
+    if let Some(address) = arg_value("--serve") {
+        let listener = TcpListener::bind(address)?;
+        println!("serving on {}", listener.local_addr()?);
+        // the writer runs until the encounter, and with it the sender, is gone
+        let log_writer = event_log
+            .map(|(writer, receiver)| thread::spawn(move || write_jsonl(receiver, writer)));
+        let result = serve(encounter_instance, controllers, listener);
+        if let Some(handle) = log_writer {
+            handle
+                .join()
+                .map_err(|_| io::Error::other("the event log writer panicked"))??;
+        }
+        return result;
+    }
+
     // Setup terminal
     terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -37,34 +377,61 @@ fn main() -> io::Result<()> {
     // TODO: move to handler obj
     let mut input_str: String = String::new();
     let mut tmp_message: String = String::new();
+    let mut viewport = Viewport::new();
+    let mut followed_actor: Option<usize> = None;
+    let mut history = History::new();
+    // the checkpoint an undo or rewind goes back to once confirmed
+    let mut pending_rewind: Option<usize> = None;
+    // set once play went back to a turn the computer plays, which it would
+    // only play the same way again; cleared by continue
+    let mut automation_paused = false;
 
-    let terrain_params = TerrainGenParams {
-        width: 40,
-        height: 20,
-        branch_depth: 8,
-        branch_prob: 0.5,
-    };
+    while running {
+        if let Some((writer, receiver)) = &mut event_log {
+            write_jsonl(receiver.try_iter(), writer)?;
+        }
+        // an action the engine cannot resolve is taken back, so the session
+        // carries on from before it
+        match history.process_stack(&mut encounter_instance) {
+            Ok(None) => {}
+            Ok(Some(taken_back)) => {
+                tmp_message = taken_back;
+                automation_paused = controllers.has_prompt(&encounter_instance);
+                pending_rewind = None;
+            }
+            Err(e) => {
+                tmp_message = e.to_string();
+                pending_rewind = None;
+            }
+        }
 
-    let mut encounter_instance: EncounterInstance = EncounterInstance::from_params(
-        &terrain_params,
-        &ActorGenParams {
-            cr_target: 1.0,
-            n_teams: 2,
-        },
-    );
+        // show e.g. the path of a move while it is being typed
+        let preview = match encounter_instance.peek_prompt() {
+            Some(prompt) => prompt.preview_input(input_str.trim(), &encounter_instance),
+            None => Vec::new(),
+        };
 
-    while running {
-        encounter_instance.process_stack();
+        // the camera goes back to following whenever the turn passes on
+        if let Some(prompt) = encounter_instance.peek_prompt() {
+            let actor_id = prompt.actor_id();
+            if followed_actor != Some(actor_id) {
+                followed_actor = Some(actor_id);
+                viewport.follow = true;
+            }
+            if let Some(actor) = encounter_instance.actor(actor_id) {
+                viewport.follow_coord(actor.location());
+            }
+        }
 
         // Draw UI
         terminal.draw(|f| {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
-                    Constraint::Length(HEIGHT.min(terrain_params.height) as u16 + 2), // Map area
-                    Constraint::Length(3),                                            // Input area
+                    Constraint::Min(8),    // Map area
+                    Constraint::Length(3), // Input area
                     Constraint::Length(3), // Temp message
-                    Constraint::Min(1),    // Message log
+                    Constraint::Length(7), // Message log
                 ])
                 .split(f.area());
 
@@ -72,12 +439,12 @@ fn main() -> io::Result<()> {
             let info_area = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints([
-                    Constraint::Length(terrain_params.width as u16 + 2),
-                    Constraint::Min(1),
+                    Constraint::Max(map_width as u16 + 2),
+                    Constraint::Min(MIN_SIDEINFO_WIDTH),
                 ])
                 .split(chunks[0]);
-            encounter_instance.render_map(f, info_area[0]);
-            encounter_instance.render_sideinfo(f, info_area[1]);
+            render_map(&encounter_instance, f, info_area[0], &preview, &viewport);
+            render_sideinfo(&encounter_instance, f, info_area[1]);
 
             // Input
             let input_widget: Paragraph<'_> = Paragraph::new(input_str.as_str())
@@ -90,51 +457,158 @@ fn main() -> io::Result<()> {
 
             // Messages
             let messages_text: Text = encounter_instance
-                .messages()
+                .events()
                 .iter()
                 .rev()
                 .take(5)
-                .map(|m| Span::raw(m.clone()))
+                .map(|e| Span::raw(e.to_string()))
                 .collect();
             let messages_widget = Paragraph::new(messages_text)
                 .block(Block::default().borders(Borders::ALL).title("Log"));
             f.render_widget(messages_widget, chunks[3]);
         })?;
 
+        // the computer takes one action per frame so that its turns can be
+        // followed, and waits while an undo is being confirmed or after a
+        // rewind until told to continue
+        if pending_rewind.is_none()
+            && !automation_paused
+            && controllers.has_prompt(&encounter_instance)
+        {
+            history.answer_prompt(&mut encounter_instance, &mut controllers);
+        }
+
         // Handle input
-        if event::poll(std::time::Duration::from_millis(200))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char(c) => input_str.push(c),
-                        KeyCode::Backspace => {
-                            input_str.pop();
+        if event::poll(std::time::Duration::from_millis(200))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char(c) => input_str.push(c),
+                KeyCode::Backspace => {
+                    input_str.pop();
+                }
+                KeyCode::Enter => {
+                    let trimmed = input_str.trim();
+                    if trimmed == "quit" {
+                        running = false;
+                    }
+                    if let Some(checkpoint) = pending_rewind.take() {
+                        tmp_message = match trimmed {
+                            "yes" | "y" => {
+                                history.rewind(&mut encounter_instance, checkpoint);
+                                automation_paused = controllers.has_prompt(&encounter_instance);
+                                match automation_paused {
+                                    true => "reverted; enter continue to let the computer play on"
+                                        .to_string(),
+                                    false => "reverted".to_string(),
+                                }
+                            }
+                            _ => "nothing reverted".to_string(),
+                        };
+                        input_str.clear();
+                        continue;
+                    }
+                    if trimmed == "continue" {
+                        automation_paused = false;
+                        tmp_message.clear();
+                        input_str.clear();
+                        continue;
+                    }
+                    // undo takes back the last action you took; rewind [actor] goes back
+                    // to the start of the actor's turn this round, the current
+                    // actor's if none is named
+                    let rewind_to = trimmed
+                        .strip_prefix("rewind")
+                        .filter(|name| name.is_empty() || name.starts_with(' '));
+                    let checkpoint = match rewind_to {
+                        _ if trimmed == "undo" => {
+                            Some(history.last_action().ok_or("nothing to undo".to_string()))
+                        }
+                        Some(name) => {
+                            let name = name.trim();
+                            let actor_id = match name {
+                                "" => encounter_instance.peek_prompt().map(|p| p.actor_id()),
+                                _ => encounter_instance.actors().keys().copied().find(|&id| {
+                                    encounter_instance.actor_name(id).eq_ignore_ascii_case(name)
+                                }),
+                            };
+                            Some(
+                                actor_id
+                                    .and_then(|id| history.turn_start(&encounter_instance, id))
+                                    .ok_or("no turn of theirs to rewind to this round".to_string()),
+                            )
                         }
-                        KeyCode::Enter => {
-                            let trimmed = input_str.trim();
-                            if trimmed == "quit" {
-                                running = false;
+                        None => None,
+                    };
+                    if let Some(checkpoint) = checkpoint {
+                        tmp_message = match checkpoint {
+                            Ok(checkpoint) => {
+                                pending_rewind = Some(checkpoint);
+                                format!(
+                                    "{} - enter yes to confirm",
+                                    history.describe(&encounter_instance, checkpoint)
+                                )
                             }
-                            if let Some(prompt) = encounter_instance.peek_prompt() {
-                                match prompt.process_input(trimmed, &encounter_instance) {
-                                    Ok(aei) => {
-                                        encounter_instance.pop_prompt();
-                                        encounter_instance.push_action(aei);
-                                        input_str.clear();
-                                        tmp_message.clear();
-                                    }
-                                    Err(e) => {
-                                        tmp_message.clear();
-                                        tmp_message.push_str(&e.to_string());
-                                    }
+                            Err(e) => e,
+                        };
+                        input_str.clear();
+                        continue;
+                    }
+                    // save <file> and load <file> work at any prompt; the
+                    // teams the computer plays stay as they were set
+                    if let Some(path) = trimmed.strip_prefix("save ") {
+                        let path = path.trim();
+                        tmp_message = match save_encounter(&encounter_instance, path) {
+                            Ok(()) => format!("saved to {}", path),
+                            Err(e) => format!("failed to save to {}: {}", path, e),
+                        };
+                        input_str.clear();
+                        continue;
+                    }
+                    if let Some(path) = trimmed.strip_prefix("load ") {
+                        let path = path.trim();
+                        tmp_message = match load_encounter(path) {
+                            Ok(loaded) => {
+                                encounter_instance = loaded;
+                                // carries on with what the loaded encounter logs
+                                if let Some((_, receiver)) = &mut event_log {
+                                    *receiver = encounter_instance.subscribe();
                                 }
+                                (map_width, map_height) =
+                                    (encounter_instance.width(), encounter_instance.height());
+                                viewport = Viewport::new();
+                                followed_actor = None;
+                                history.clear();
+                                automation_paused = false;
+                                format!("loaded {}", path)
                             }
-                            // game.process_command(input_str);
+                            Err(e) => format!("failed to load {}: {}", path, e),
+                        };
+                        input_str.clear();
+                        continue;
+                    }
+                    if encounter_instance.peek_prompt().is_some() {
+                        match history.submit_input(&mut encounter_instance, trimmed) {
+                            Ok(()) => {
+                                input_str.clear();
+                                tmp_message.clear();
+                            }
+                            Err(e) => tmp_message = e,
                         }
-                        KeyCode::Esc => running = false,
-                        _ => {}
                     }
+                    // game.process_command(input_str);
                 }
+                KeyCode::Left => viewport.pan(-1, 0, map_width, map_height),
+                KeyCode::Right => viewport.pan(1, 0, map_width, map_height),
+                KeyCode::Up => viewport.pan(0, 1, map_width, map_height),
+                KeyCode::Down => viewport.pan(0, -1, map_width, map_height),
+                KeyCode::Home => viewport.follow = true,
+                KeyCode::PageUp => viewport.zoom_out(),
+                KeyCode::PageDown => viewport.zoom_in(),
+                KeyCode::F(2) => viewport.show_minimap = !viewport.show_minimap,
+                KeyCode::Esc => running = false,
+                _ => {}
             }
         }
     }
@@ -142,5 +616,8 @@ fn main() -> io::Result<()> {
     // Restore terminal
     terminal::disable_raw_mode()?;
     execute!(terminal.backend_mut(), terminal::LeaveAlternateScreen)?;
+    if let Some((writer, receiver)) = &mut event_log {
+        write_jsonl(receiver.try_iter(), writer)?;
+    }
     Ok(())
 }
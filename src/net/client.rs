@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader};
+use std::net::TcpStream;
+use std::thread;
+
+use crate::engine::types::Coordinate;
+use crate::engine::util::{actor_char, hazard_glyph, terrain_glyph};
+use crate::net::protocol::{ClientMessage, ServerMessage, TeamView, receive, send};
+
+// the view as lines of text, top row first like the terminal map; tiles out
+// of sight are left blank
+pub fn render_view(view: &TeamView) -> Vec<String> {
+    let mut cells: HashMap<Coordinate, char> = HashMap::new();
+    for tile in view.tiles.iter() {
+        let c = match tile.hazard {
+            Some(kind) => hazard_glyph(kind),
+            None => terrain_glyph(&tile.terrain),
+        };
+        cells.insert(tile.coord, c);
+    }
+    for actor in view.actors.iter() {
+        let width = actor.footprint_width as isize;
+        for x in 0..width {
+            for y in 0..width {
+                let coord = actor.location + Coordinate::new(x, y);
+                cells.insert(coord, actor_char(actor.actor_id));
+            }
+        }
+    }
+    (0..view.height)
+        .rev()
+        .map(|y| {
+            (0..view.width)
+                .map(|x| {
+                    let coord = Coordinate::new(x as isize, y as isize);
+                    cells.get(&coord).copied().unwrap_or(' ')
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn print_view(view: &TeamView) {
+    println!("round {}", view.round);
+    for line in render_view(view) {
+        println!("{}", line);
+    }
+    for actor in view.actors.iter() {
+        let mut status = format!(
+            "{} {} (team {})",
+            actor_char(actor.actor_id),
+            actor.name,
+            actor.team
+        );
+        if let Some((hp, max_hp)) = actor.hitpoints {
+            status.push_str(&format!(" HP {}/{}", hp, max_hp));
+        }
+        if actor.altitude > 0. {
+            status.push_str(&format!(" {} ft up", actor.altitude));
+        }
+        if actor.prone {
+            status.push_str(" prone");
+        }
+        if actor.down {
+            status.push_str(" down");
+        }
+        println!("{}", status);
+    }
+}
+
+// plays the given teams on a server from this terminal: lines typed are sent
+// as commands, and everything the server says is printed as it comes
+pub fn run_client(address: &str, teams: &[usize]) -> io::Result<()> {
+    let mut stream = TcpStream::connect(address)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    send(
+        &mut stream,
+        &ClientMessage::Join {
+            teams: teams.to_vec(),
+        },
+    )?;
+
+    let mut writer = stream.try_clone()?;
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(text) = line else {
+                return;
+            };
+            if send(&mut writer, &ClientMessage::Input { text }).is_err() {
+                return;
+            }
+        }
+    });
+
+    while let Some(message) = receive::<ServerMessage>(&mut reader)? {
+        match message {
+            ServerMessage::Welcome { teams } => println!("playing teams {:?}", teams),
+            ServerMessage::Rejected { reason } => println!("rejected: {}", reason),
+            ServerMessage::View { view } => print_view(&view),
+            ServerMessage::Event { event } => println!("{}", event),
+            ServerMessage::Prompt {
+                actor_id,
+                actor,
+                actions,
+            } => println!(
+                "{} ({}) is up: {}",
+                actor,
+                actor_char(actor_id),
+                actions.join(", ")
+            ),
+            ServerMessage::Ended { winner } => {
+                match winner {
+                    Some(team_id) => println!("team {} wins", team_id),
+                    None => println!("nobody wins"),
+                }
+                return Ok(());
+            }
+        }
+    }
+    println!("the server hung up");
+    Ok(())
+}
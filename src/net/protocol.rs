@@ -0,0 +1,268 @@
+use std::collections::{BTreeSet, HashSet};
+use std::io::{self, BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::events::{EventSubject, LoggedEvent};
+use crate::engine::hazards::HazardKind;
+use crate::engine::terrain::TerrainType;
+use crate::engine::types::Coordinate;
+use crate::engine::visibility::visible_to_team;
+
+// Every message is one line of JSON with a "type" field. A client joins with
+// the teams it plays, then sends the same commands as the terminal's input
+// line whenever one of its actors is prompted.
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Join { teams: Vec<usize> },
+    Input { text: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Welcome {
+        teams: Vec<usize>,
+    },
+    Rejected {
+        reason: String,
+    },
+    View {
+        view: TeamView,
+    },
+    Event {
+        event: LoggedEvent,
+    },
+    // one of the client's actors is up
+    Prompt {
+        actor_id: usize,
+        actor: String,
+        actions: Vec<String>,
+    },
+    // None for a draw
+    Ended {
+        winner: Option<usize>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VisibleTile {
+    pub coord: Coordinate,
+    pub terrain: TerrainType,
+    // only hazards that have been found
+    pub hazard: Option<HazardKind>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VisibleActor {
+    pub actor_id: usize,
+    pub name: String,
+    pub team: usize,
+    pub location: Coordinate,
+    pub footprint_width: usize,
+    pub altitude: f32,
+    pub prone: bool,
+    pub down: bool,
+    // hit points are only shown for the client's own creatures
+    pub hitpoints: Option<(u32, u32)>,
+}
+
+// the encounter as a client's teams see it: the tiles any of their standing
+// creatures has in sight and whoever stands on them, plus their own creatures
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TeamView {
+    pub round: usize,
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<VisibleTile>,
+    pub actors: Vec<VisibleActor>,
+}
+
+pub fn visible_tiles(
+    encounter: &EncounterInstance,
+    teams: &BTreeSet<usize>,
+) -> HashSet<Coordinate> {
+    teams
+        .iter()
+        .flat_map(|&team_id| visible_to_team(encounter, team_id))
+        .collect()
+}
+
+pub fn team_view(
+    encounter: &EncounterInstance,
+    teams: &BTreeSet<usize>,
+    visible: &HashSet<Coordinate>,
+) -> TeamView {
+    let mut tiles: Vec<VisibleTile> = visible
+        .iter()
+        .filter_map(|&coord| {
+            let terrain = encounter.terrain_at(coord)?;
+            Some(VisibleTile {
+                coord,
+                terrain: terrain.terrain_type.clone(),
+                hazard: terrain
+                    .hazard
+                    .as_ref()
+                    .filter(|h| !h.is_hidden())
+                    .map(|h| h.kind),
+            })
+        })
+        .collect();
+    tiles.sort_by_key(|t| (t.coord.y, t.coord.x));
+
+    let actors = encounter
+        .actors()
+        .iter()
+        .filter(|&(&actor_id, actor)| {
+            teams.contains(&actor.team())
+                || encounter
+                    .footprint(actor_id)
+                    .iter()
+                    .any(|t| visible.contains(t))
+        })
+        .map(|(&actor_id, actor)| VisibleActor {
+            actor_id,
+            name: actor.name(),
+            team: actor.team(),
+            location: actor.location(),
+            footprint_width: actor.footprint_width(),
+            altitude: actor.altitude(),
+            prone: actor.is_prone(),
+            down: actor.is_down(),
+            hitpoints: teams
+                .contains(&actor.team())
+                .then(|| (actor.hitpoints(), actor.max_hitpoints())),
+        })
+        .collect();
+
+    TeamView {
+        round: encounter.round(),
+        width: encounter.width(),
+        height: encounter.height(),
+        tiles,
+        actors,
+    }
+}
+
+// whether the teams get to hear of the event: anything a creature does, or
+// that happens somewhere, is only told if they saw it when it was logged.
+// Events logged without noting that are judged by what is in view now
+pub fn event_visible(event: &LoggedEvent, teams: &BTreeSet<usize>, view: &TeamView) -> bool {
+    if let Some(seen_by) = &event.seen_by {
+        return !seen_by.is_disjoint(teams);
+    }
+    match event.event.subject() {
+        EventSubject::Actor(actor_id) => view.actors.iter().any(|a| a.actor_id == actor_id),
+        EventSubject::Place(coord) => view.tiles.iter().any(|t| t.coord == coord),
+        EventSubject::Everyone => true,
+    }
+}
+
+pub fn send<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, message)?;
+    writeln!(writer)?;
+    writer.flush()
+}
+
+// None once the other side hangs up
+pub fn receive<T: DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::events::EncounterEvent;
+    use crate::engine::hazards::reveal_hazard;
+    use crate::engine::test_util::encounter_from;
+
+    // team 0 sees the hidden pit's tile, a visible pit and one of the two
+    // creatures of team 1; the other stands behind the wall
+    const BEHIND_A_WALL: &str = "[legend]\n^ hidden_pit\no pit\n[map]\n\
+        ..........##..\n\
+        ..........##..\n\
+        ..........##..\n\
+        ....^.o...##..\n\
+        [creatures]\nZombie 0,0 0\nZombie 8,2 1\nZombie 12,0 1\n";
+
+    fn view_of(encounter: &EncounterInstance, teams: &BTreeSet<usize>) -> TeamView {
+        team_view(encounter, teams, &visible_tiles(encounter, teams))
+    }
+
+    fn note(actor_id: Option<usize>, seen_by: Option<BTreeSet<usize>>) -> LoggedEvent {
+        LoggedEvent {
+            round: 1,
+            seen_by,
+            event: EncounterEvent::Note {
+                text: String::new(),
+                actor_id,
+                location: None,
+            },
+        }
+    }
+
+    #[test]
+    fn a_team_sees_found_hazards_and_only_its_own_hit_points() {
+        let mut encounter = encounter_from(BEHIND_A_WALL, 0);
+        let teams = BTreeSet::from([0]);
+        let hazard_at = |view: &TeamView, x: isize| {
+            view.tiles
+                .iter()
+                .find(|t| t.coord == Coordinate::new(x, 0))
+                .and_then(|t| t.hazard)
+        };
+
+        let view = view_of(&encounter, &teams);
+        assert_eq!(hazard_at(&view, 4), None);
+        assert_eq!(hazard_at(&view, 6), Some(HazardKind::Pit));
+        let actor = |id: usize| view.actors.iter().find(|a| a.actor_id == id);
+        assert!(actor(0).unwrap().hitpoints.is_some());
+        assert!(actor(1).unwrap().hitpoints.is_none());
+        assert!(actor(2).is_none());
+
+        reveal_hazard(&mut encounter, Coordinate::new(4, 0));
+        let view = view_of(&encounter, &teams);
+        assert_eq!(hazard_at(&view, 4), Some(HazardKind::Pit));
+    }
+
+    #[test]
+    fn events_are_told_to_the_teams_that_saw_them() {
+        let mut encounter = encounter_from(BEHIND_A_WALL, 0);
+        let teams = BTreeSet::from([0]);
+        let view = view_of(&encounter, &teams);
+
+        // noted when logged, whatever is in view now
+        assert!(event_visible(
+            &note(Some(2), Some(BTreeSet::from([0]))),
+            &teams,
+            &view
+        ));
+        assert!(!event_visible(
+            &note(Some(1), Some(BTreeSet::from([1]))),
+            &teams,
+            &view
+        ));
+        // otherwise judged by the view
+        assert!(event_visible(&note(Some(1), None), &teams, &view));
+        assert!(!event_visible(&note(Some(2), None), &teams, &view));
+        assert!(event_visible(&note(None, None), &teams, &view));
+
+        encounter.track_sight();
+        encounter.log(EncounterEvent::Note {
+            text: "out of sight".to_string(),
+            actor_id: Some(2),
+            location: None,
+        });
+        let logged = encounter.events().last().unwrap();
+        assert!(!event_visible(logged, &teams, &view));
+        assert!(event_visible(logged, &BTreeSet::from([1]), &view));
+    }
+}
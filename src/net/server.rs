@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, BufReader};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use crate::engine::controller::TeamControllers;
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::events::LoggedEvent;
+use crate::engine::history::History;
+use crate::net::protocol::{
+    ClientMessage, ServerMessage, event_visible, receive, send, team_view, visible_tiles,
+};
+
+// what the connection threads tell the game
+enum NetEvent {
+    Connected(usize, TcpStream),
+    Message(usize, ClientMessage),
+    Disconnected(usize),
+}
+
+struct Client {
+    stream: TcpStream,
+    teams: BTreeSet<usize>,
+    // how much of the log this client has been sent, None until it has
+    // been sent a view at all
+    events_sent: Option<usize>,
+    // how far the log was when the client was last prompted
+    prompted_at: Option<usize>,
+}
+
+fn accept_clients(listener: TcpListener, net_events: Sender<NetEvent>) {
+    for (client_id, stream) in listener.incoming().enumerate() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let Ok(reader) = stream.try_clone() else {
+            continue;
+        };
+        if net_events
+            .send(NetEvent::Connected(client_id, stream))
+            .is_err()
+        {
+            return;
+        }
+        let net_events = net_events.clone();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            // a line that does not parse ends the connection like hanging up
+            while let Ok(Some(message)) = receive::<ClientMessage>(&mut reader) {
+                if net_events
+                    .send(NetEvent::Message(client_id, message))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+            let _ = net_events.send(NetEvent::Disconnected(client_id));
+        });
+    }
+}
+
+fn teams_standing(encounter: &EncounterInstance) -> BTreeSet<usize> {
+    encounter
+        .actors()
+        .values()
+        .filter(|a| !a.is_down())
+        .map(|a| a.team())
+        .collect()
+}
+
+// hosts the encounter for clients on the network. Each team is played by the
+// client that joined with it, or by its controller; prompts for a team
+// nobody plays yet wait until someone joins with it. Returns once one team
+// is left standing.
+pub fn serve(
+    mut encounter: EncounterInstance,
+    mut controllers: TeamControllers,
+    listener: TcpListener,
+) -> io::Result<()> {
+    let (net_events, incoming) = mpsc::channel();
+    thread::spawn(move || accept_clients(listener, net_events));
+
+    // clients are told what their teams saw as it happened, not what is in
+    // view once the stack has run
+    encounter.track_sight();
+    let mut clients: BTreeMap<usize, Client> = BTreeMap::new();
+    let mut history = History::new();
+    let mut printed: usize = 0;
+    loop {
+        // an action the engine cannot resolve is taken back, as in the terminal
+        if let Some(taken_back) = history
+            .process_stack(&mut encounter)
+            .map_err(io::Error::other)?
+        {
+            println!("{}", taken_back);
+        }
+        for event in encounter.events().iter().skip(printed) {
+            println!("{}", event);
+        }
+        printed = encounter.events().len();
+        for client in clients.values_mut() {
+            update(&encounter, client);
+        }
+
+        let standing = teams_standing(&encounter);
+        if standing.len() <= 1 {
+            let winner = standing.first().copied();
+            for client in clients.values_mut() {
+                let _ = send(&mut client.stream, &ServerMessage::Ended { winner });
+            }
+            return Ok(());
+        }
+
+        let Some(prompt) = encounter.peek_prompt().cloned() else {
+            continue;
+        };
+        let actor_id = prompt.actor_id();
+        let Some(team_id) = encounter.actor(actor_id).map(|a| a.team()) else {
+            continue;
+        };
+        if controllers.is_automated(team_id) {
+            history.answer_prompt(&mut encounter, &mut controllers);
+            continue;
+        }
+        let owner = clients
+            .iter()
+            .find(|(_, c)| c.teams.contains(&team_id))
+            .map(|(&id, _)| id);
+        if let Some(owner) = owner
+            && let Some(client) = clients.get_mut(&owner)
+            && client.prompted_at != Some(encounter.events().len())
+        {
+            client.prompted_at = Some(encounter.events().len());
+            let message = ServerMessage::Prompt {
+                actor_id,
+                actor: encounter.actor_name(actor_id),
+                actions: prompt
+                    .actions()
+                    .iter()
+                    .map(|a| a.name().to_string())
+                    .collect(),
+            };
+            let _ = send(&mut client.stream, &message);
+        }
+
+        // nothing happens until a client says something
+        let Ok(net_event) = incoming.recv() else {
+            return Ok(());
+        };
+        match net_event {
+            NetEvent::Connected(client_id, stream) => {
+                clients.insert(
+                    client_id,
+                    Client {
+                        stream,
+                        teams: BTreeSet::new(),
+                        events_sent: None,
+                        prompted_at: None,
+                    },
+                );
+            }
+            NetEvent::Disconnected(client_id) => {
+                if let Some(client) = clients.remove(&client_id) {
+                    println!(
+                        "client {} left, teams {:?} are free",
+                        client_id, client.teams
+                    );
+                }
+            }
+            NetEvent::Message(client_id, ClientMessage::Join { teams }) => {
+                let reply = join(&encounter, &controllers, &clients, &teams);
+                if let Some(client) = clients.get_mut(&client_id) {
+                    if let ServerMessage::Welcome { teams } = &reply {
+                        client.teams.extend(teams.iter().copied());
+                        println!("client {} plays teams {:?}", client_id, client.teams);
+                    }
+                    let _ = send(&mut client.stream, &reply);
+                }
+            }
+            NetEvent::Message(client_id, ClientMessage::Input { text }) => {
+                let Some(client) = clients.get_mut(&client_id) else {
+                    continue;
+                };
+                if owner != Some(client_id) {
+                    let reason = "none of your creatures is up".to_string();
+                    let _ = send(&mut client.stream, &ServerMessage::Rejected { reason });
+                    continue;
+                }
+                if let Err(reason) = history.submit_input(&mut encounter, &text) {
+                    let _ = send(&mut client.stream, &ServerMessage::Rejected { reason });
+                }
+            }
+        }
+    }
+}
+
+// teams can be joined if they are in the encounter and nobody plays them yet
+fn join(
+    encounter: &EncounterInstance,
+    controllers: &TeamControllers,
+    clients: &BTreeMap<usize, Client>,
+    teams: &[usize],
+) -> ServerMessage {
+    for &team_id in teams {
+        let exists = encounter.actors().values().any(|a| a.team() == team_id);
+        let taken = controllers.is_automated(team_id)
+            || clients.values().any(|c| c.teams.contains(&team_id));
+        if !exists || taken {
+            return ServerMessage::Rejected {
+                reason: format!("team {} cannot be joined", team_id),
+            };
+        }
+    }
+    ServerMessage::Welcome {
+        teams: teams.to_vec(),
+    }
+}
+
+// sends the client what its teams see now and what they saw happen since
+// the last update
+fn update(encounter: &EncounterInstance, client: &mut Client) {
+    let n_events = encounter.events().len();
+    if client.teams.is_empty() || client.events_sent == Some(n_events) {
+        return;
+    }
+    let visible = visible_tiles(encounter, &client.teams);
+    let view = team_view(encounter, &client.teams, &visible);
+    // what happened before the client joined is not replayed
+    let sent = client.events_sent.unwrap_or(n_events);
+    for event in encounter.events().iter().skip(sent) {
+        if event_visible(event, &client.teams, &view) {
+            // who else saw it is none of the client's business
+            let message = ServerMessage::Event {
+                event: LoggedEvent {
+                    seen_by: None,
+                    ..event.clone()
+                },
+            };
+            let _ = send(&mut client.stream, &message);
+        }
+    }
+    client.events_sent = Some(n_events);
+    let _ = send(&mut client.stream, &ServerMessage::View { view });
+}
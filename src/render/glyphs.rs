@@ -0,0 +1,72 @@
+use ratatui::style::Color;
+
+use crate::engine::hazards::HazardKind;
+use crate::engine::terrain::{DoorState, TerrainType};
+use crate::engine::util::{actor_char, hazard_glyph, terrain_glyph};
+
+pub fn get_colored_span(n: usize, team: usize) -> (String, Color, Color) {
+    let s = actor_char(n).to_string();
+
+    let team_u8: u8 = (team % 16) as u8;
+    let color = Color::Indexed(team_u8);
+    let bg = if team_u8 < 1 {
+        Color::LightCyan
+    } else {
+        Color::Black
+    };
+
+    (s, color, bg)
+}
+
+pub fn get_terrain_span(terrain_type: &TerrainType) -> (String, Color) {
+    let color = match terrain_type {
+        TerrainType::Empty | TerrainType::Floor | TerrainType::Wall => Color::Reset,
+        TerrainType::Door(DoorState::Open) | TerrainType::Door(DoorState::Closed) => Color::Yellow,
+        TerrainType::Door(DoorState::Locked) => Color::Red,
+        TerrainType::Tree => Color::Green,
+        TerrainType::Boulder => Color::Gray,
+        TerrainType::Bridge => Color::Yellow,
+        TerrainType::Rubble => Color::Gray,
+        TerrainType::Undergrowth => Color::Green,
+        TerrainType::ShallowWater => Color::Cyan,
+        TerrainType::DeepWater => Color::Blue,
+        TerrainType::Cliff => Color::Yellow,
+    };
+    (terrain_glyph(terrain_type).to_string(), color)
+}
+
+// which terrain stands for a cell that aggregates several tiles when zoomed
+// out, so that thin walls and doors do not disappear
+pub fn render_priority(terrain_type: &TerrainType) -> u8 {
+    match terrain_type {
+        TerrainType::Empty => 0,
+        TerrainType::Floor => 1,
+        TerrainType::Rubble
+        | TerrainType::Undergrowth
+        | TerrainType::ShallowWater
+        | TerrainType::Bridge => 2,
+        TerrainType::DeepWater | TerrainType::Cliff => 3,
+        TerrainType::Tree | TerrainType::Boulder => 4,
+        TerrainType::Wall => 5,
+        TerrainType::Door(_) => 6,
+    }
+}
+
+pub fn get_hazard_span(kind: HazardKind) -> (String, Color) {
+    let color = match kind {
+        HazardKind::Fire => Color::Red,
+        HazardKind::Acid => Color::LightGreen,
+        HazardKind::Pit => Color::DarkGray,
+        HazardKind::PressurePlate => Color::Magenta,
+    };
+    (hazard_glyph(kind).to_string(), color)
+}
+
+// altitude in 5 ft steps, '^' once it no longer fits in one digit
+pub fn altitude_marker(altitude: f32) -> String {
+    let steps = (altitude / 5.).ceil() as u32;
+    match char::from_digit(steps, 10) {
+        Some(c) => c.to_string(),
+        None => "^".to_string(),
+    }
+}
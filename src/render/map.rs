@@ -0,0 +1,177 @@
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::terrain::TerrainType;
+use crate::engine::types::Coordinate;
+use crate::render::glyphs::{
+    altitude_marker, get_colored_span, get_hazard_span, get_terrain_span, render_priority,
+};
+use crate::render::viewport::{Viewport, VisibleArea};
+
+// one map cell covering the given tiles: an actor if any stands there,
+// then the move preview, then visible hazards, then the most prominent
+// terrain, or the most common one for the coarse minimap
+fn cell_span(
+    encounter: &EncounterInstance,
+    tiles: &[Coordinate],
+    preview: &[Coordinate],
+    most_common: bool,
+) -> Span<'static> {
+    let tiles: Vec<Coordinate> = tiles
+        .iter()
+        .copied()
+        .filter(|&t| encounter.in_bounds(t))
+        .collect();
+    if tiles.is_empty() {
+        return Span::raw(" ");
+    }
+
+    if let Some((coord, actor_id, actor)) = tiles.iter().find_map(|&t| {
+        let actor_id = encounter.actor_id_at(t)?;
+        Some((t, actor_id, encounter.actors.get(&actor_id)?))
+    }) {
+        let (mut s, c, bg): (String, Color, Color) = get_colored_span(actor_id, actor.team());
+        // the top right tile of an airborne actor shows its altitude
+        let actor_width = actor.footprint_width() as isize;
+        let corner = actor.location() + Coordinate::new(actor_width - 1, actor_width - 1);
+        if actor.altitude() > 0. && coord == corner {
+            s = altitude_marker(actor.altitude());
+        }
+        return Span::styled(s, Style::default().fg(c).bg(bg));
+    }
+
+    if tiles.iter().any(|t| preview.contains(t)) {
+        return Span::styled("·", Style::default().fg(Color::Yellow).bg(Color::DarkGray));
+    }
+
+    if let Some(hazard) = tiles
+        .iter()
+        .filter_map(|&t| encounter.terrain_at(t)?.hazard.as_ref())
+        .find(|h| !h.is_hidden())
+    {
+        let (s, c): (String, Color) = get_hazard_span(hazard.kind);
+        return Span::styled(s, Style::default().fg(c));
+    }
+
+    let terrain_types = tiles
+        .iter()
+        .filter_map(|&t| encounter.terrain_at(t))
+        .map(|ti| &ti.terrain_type);
+    let Some(terrain_type) = (if most_common {
+        let mut counts: Vec<(&TerrainType, usize)> = Vec::new();
+        for tt in terrain_types {
+            match counts.iter_mut().find(|(t, _)| *t == tt) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((tt, 1)),
+            }
+        }
+        counts
+            .into_iter()
+            .max_by_key(|&(tt, n)| (n, render_priority(tt)))
+            .map(|(tt, _)| tt)
+    } else {
+        terrain_types.max_by_key(|tt| render_priority(tt))
+    }) else {
+        return Span::raw(" ");
+    };
+    let (s, c): (String, Color) = get_terrain_span(terrain_type);
+    Span::styled(s, Style::default().fg(c))
+}
+
+pub fn render_map(
+    encounter: &EncounterInstance,
+    frame: &mut Frame,
+    area: Rect,
+    preview: &[Coordinate],
+    viewport: &Viewport,
+) {
+    let visible = viewport.visible_area(
+        encounter.width,
+        encounter.height,
+        area.width.saturating_sub(2) as usize,
+        area.height.saturating_sub(2) as usize,
+    );
+    let mut text: Vec<Line> = Vec::new();
+
+    for row_n in 0..visible.rows {
+        let mut row: Vec<Span> = Vec::new();
+        for col_n in 0..visible.cols {
+            row.push(cell_span(
+                encounter,
+                &visible.cell_tiles(col_n, row_n),
+                preview,
+                false,
+            ));
+        }
+        text.push(Line::from(row));
+    }
+
+    let mut title = format!("Map (seed {})", encounter.seed());
+    if visible.zoom > 1 {
+        title.push_str(&format!(" {}x", visible.zoom));
+    }
+    frame.render_widget(
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(title)),
+        area,
+    );
+
+    if viewport.show_minimap {
+        render_minimap(encounter, frame, area, &visible);
+    }
+}
+
+// the whole map shrunk into the top right corner of the map pane, with
+// the part in view highlighted
+fn render_minimap(
+    encounter: &EncounterInstance,
+    frame: &mut Frame,
+    map_area: Rect,
+    visible: &VisibleArea,
+) {
+    let max_cols = (map_area.width as usize / 3).max(1);
+    let max_rows = (map_area.height as usize / 3).max(1);
+    let scale = encounter
+        .width
+        .div_ceil(max_cols)
+        .max(encounter.height.div_ceil(max_rows))
+        .max(1);
+    let cols = encounter.width.div_ceil(scale);
+    let rows = encounter.height.div_ceil(scale);
+
+    let mut text: Vec<Line> = Vec::new();
+    for row_n in (0..rows).rev() {
+        let mut row: Vec<Span> = Vec::new();
+        for col_n in 0..cols {
+            let mut tiles: Vec<Coordinate> = Vec::new();
+            for x in col_n * scale..(col_n + 1) * scale {
+                for y in row_n * scale..(row_n + 1) * scale {
+                    tiles.push(Coordinate::new(x as isize, y as isize));
+                }
+            }
+            let mut span = cell_span(encounter, &tiles, &[], true);
+            if tiles.iter().any(|&t| visible.contains(t)) {
+                span = span.patch_style(Style::default().bg(Color::DarkGray));
+            }
+            row.push(span);
+        }
+        text.push(Line::from(row));
+    }
+
+    let width = (cols as u16 + 2).min(map_area.width);
+    let height = (rows as u16 + 2).min(map_area.height);
+    let minimap_area = Rect::new(
+        map_area.x + map_area.width - width,
+        map_area.y,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, minimap_area);
+    frame.render_widget(
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Minimap")),
+        minimap_area,
+    );
+}
@@ -0,0 +1,6 @@
+// drawing an encounter in the terminal; the engine itself has no idea how it
+// is shown, so this is only built with the tui feature
+pub mod glyphs;
+pub mod map;
+pub mod sideinfo;
+pub mod viewport;
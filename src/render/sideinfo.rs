@@ -0,0 +1,74 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+
+use crate::engine::encounter::EncounterInstance;
+use crate::render::glyphs::get_colored_span;
+
+pub fn render_sideinfo(encounter: &EncounterInstance, frame: &mut Frame, area: Rect) {
+    let area_split = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Min(1), Constraint::Min(1)])
+        .split(area);
+
+    if let Some(prmpt) = encounter.peek_prompt()
+        && let Some(curr_actor) = encounter.actors.get(&prmpt.actor_id())
+    {
+        let curr_actor_id = prmpt.actor_id();
+        let (s, c, bg): (String, Color, Color) = get_colored_span(curr_actor_id, curr_actor.team());
+
+        let mut initiative_bar: Vec<Span> = Vec::new();
+        initiative_bar.push(Span::from(format!("Current actor: {} ", curr_actor.name())));
+        initiative_bar.push(Span::styled(s, Style::default().fg(c).bg(bg)));
+        let txt: Vec<Line> = vec![Line::from(initiative_bar)];
+
+        let mut stats_info: String = String::new();
+        stats_info.push_str(&format!(
+            "HP: {}/{}\n",
+            curr_actor.hitpoints(),
+            curr_actor.max_hitpoints()
+        ));
+        stats_info.push_str(&format!("AC: {}\n", curr_actor.armor_class()));
+        stats_info.push_str(&format!("Movement: {}\n", curr_actor.remaining_movement()));
+        if curr_actor.altitude() > 0. {
+            stats_info.push_str(&format!("Altitude: {} ft\n", curr_actor.altitude()));
+        }
+        if curr_actor.is_squeezing() {
+            stats_info.push_str("Squeezing\n");
+        }
+        stats_info.push_str(&format!(
+            "Actions: {} Bonus Actions: {}\n",
+            curr_actor.action_slots(),
+            curr_actor.bonus_action_slots()
+        ));
+
+        let mut action_info: String = String::new();
+        for &action in prmpt.actions().iter() {
+            action_info.push_str(action.name());
+            action_info.push('\n');
+        }
+
+        frame.render_widget(
+            Paragraph::new(txt).block(Block::default().borders(Borders::ALL).title("Initiative")),
+            area_split[0],
+        );
+        frame.render_widget(
+            Paragraph::new(stats_info)
+                .block(Block::default().borders(Borders::ALL).title("Resources")),
+            area_split[1],
+        );
+        frame.render_widget(
+            Paragraph::new(action_info)
+                .block(Block::default().borders(Borders::ALL).title("Actions")),
+            area_split[2],
+        );
+    } else {
+        frame.render_widget(
+            Paragraph::new("ERROR")
+                .block(Block::default().borders(Borders::ALL).title("Initiative")),
+            area_split[0],
+        );
+    }
+}
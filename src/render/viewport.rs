@@ -0,0 +1,117 @@
+use crate::engine::types::Coordinate;
+
+// tiles per map cell at each zoom level
+const ZOOM_LEVELS: [usize; 4] = [1, 2, 4, 8];
+// cells moved per pan key press
+const PAN_STEP: isize = 4;
+
+// the part of the map shown in the map pane
+pub struct Viewport {
+    pub center: Coordinate,
+    zoom_idx: usize,
+    // recentred on the current actor every frame until panned away
+    pub follow: bool,
+    pub show_minimap: bool,
+}
+
+// tiles covered by the viewport; x0, y0 is the bottom left corner
+pub struct VisibleArea {
+    pub x0: usize,
+    pub y0: usize,
+    pub cols: usize,
+    pub rows: usize,
+    pub zoom: usize,
+}
+
+impl VisibleArea {
+    // tiles aggregated into the cell at the given column and row from the top
+    pub fn cell_tiles(&self, col: usize, row: usize) -> Vec<Coordinate> {
+        let x_start = self.x0 + col * self.zoom;
+        let y_start = self.y0 + (self.rows - 1 - row) * self.zoom;
+        let mut tiles = Vec::with_capacity(self.zoom * self.zoom);
+        // top row of the cell first so the first tile is its top left
+        for y in (y_start..y_start + self.zoom).rev() {
+            for x in x_start..x_start + self.zoom {
+                tiles.push(Coordinate::new(x as isize, y as isize));
+            }
+        }
+        tiles
+    }
+
+    pub fn contains(&self, coord: Coordinate) -> bool {
+        coord.x >= self.x0 as isize
+            && coord.y >= self.y0 as isize
+            && coord.x < (self.x0 + self.cols * self.zoom) as isize
+            && coord.y < (self.y0 + self.rows * self.zoom) as isize
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Viewport {
+    pub fn new() -> Self {
+        Self {
+            center: Coordinate::new(0, 0),
+            zoom_idx: 0,
+            follow: true,
+            show_minimap: false,
+        }
+    }
+
+    pub fn zoom(&self) -> usize {
+        ZOOM_LEVELS[self.zoom_idx]
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom_idx = self.zoom_idx.saturating_sub(1);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom_idx = (self.zoom_idx + 1).min(ZOOM_LEVELS.len() - 1);
+    }
+
+    // moves by whole steps of cells and stops following the current actor
+    pub fn pan(&mut self, dx: isize, dy: isize, map_width: usize, map_height: usize) {
+        self.follow = false;
+        let step = PAN_STEP * self.zoom() as isize;
+        self.center = Coordinate::new(
+            (self.center.x + dx * step).clamp(0, map_width as isize - 1),
+            (self.center.y + dy * step).clamp(0, map_height as isize - 1),
+        );
+    }
+
+    pub fn follow_coord(&mut self, coord: Coordinate) {
+        if self.follow {
+            self.center = coord;
+        }
+    }
+
+    // cells that fit in a pane of cols x rows, kept on the map
+    pub fn visible_area(
+        &self,
+        map_width: usize,
+        map_height: usize,
+        cols: usize,
+        rows: usize,
+    ) -> VisibleArea {
+        let zoom = self.zoom();
+        let cols = cols.min(map_width.div_ceil(zoom));
+        let rows = rows.min(map_height.div_ceil(zoom));
+        let first = |center: isize, cells: usize, map_len: usize| -> usize {
+            let span = (cells * zoom) as isize;
+            let max_start = (map_len as isize - span).max(0);
+            (center - span / 2).clamp(0, max_start) as usize
+        };
+        VisibleArea {
+            x0: first(self.center.x, cols, map_width),
+            y0: first(self.center.y, rows, map_height),
+            cols,
+            rows,
+            zoom,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::ai::profiles::AiProfiles;
+use crate::ai::tactical::ProfiledAi;
+use crate::engine::actor_gen::ActorGenParams;
+use crate::engine::controller::TeamControllers;
+use crate::engine::encounter::EncounterInstance;
+use crate::engine::errors::EngineError;
+use crate::engine::pathfinding::DiagonalRule;
+use crate::engine::terrain_gen::TerrainGenParams;
+use crate::simulation::report::BatchReport;
+
+// one kind of encounter to play over and over, every team by the computer
+pub struct BatchConfig {
+    pub name: String,
+    pub terrain_params: TerrainGenParams,
+    pub actor_params: ActorGenParams,
+    pub profiles: AiProfiles,
+    pub runs: usize,
+    // run i is generated and rolled from first_seed + i
+    pub first_seed: u64,
+    // encounters still going after this many rounds count as draws
+    pub max_rounds: usize,
+    pub diagonal_rule: DiagonalRule,
+}
+
+pub struct EncounterOutcome {
+    pub seed: u64,
+    // None for a draw
+    pub winner: Option<usize>,
+    pub rounds: usize,
+    // hit points left and the most there were, per team
+    pub hitpoints: BTreeMap<usize, (u32, u32)>,
+}
+
+fn teams_standing(encounter: &EncounterInstance) -> BTreeSet<usize> {
+    encounter
+        .actors
+        .values()
+        .filter(|a| !a.is_down())
+        .map(|a| a.team())
+        .collect()
+}
+
+// plays one encounter to the end with every team run by the AI
+pub fn run_encounter(config: &BatchConfig, seed: u64) -> Result<EncounterOutcome, EngineError> {
+    let mut encounter =
+        EncounterInstance::from_params(&config.terrain_params, &config.actor_params, seed)?;
+    encounter.set_diagonal_rule(config.diagonal_rule);
+    let teams: BTreeSet<usize> = encounter.actors.values().map(|a| a.team()).collect();
+    let mut controllers = TeamControllers::new();
+    for &team_id in teams.iter() {
+        controllers.assign(team_id, Box::new(ProfiledAi::new(config.profiles.clone())));
+    }
+
+    while teams_standing(&encounter).len() > 1 && encounter.round() <= config.max_rounds {
+        encounter.process_stack()?;
+        // nothing left the AI can answer, so nothing more can happen
+        if !controllers.answer_prompt(&mut encounter) {
+            break;
+        }
+    }
+
+    let standing = teams_standing(&encounter);
+    let winner = match standing.len() {
+        1 => standing.first().copied(),
+        _ => None,
+    };
+    let mut hitpoints: BTreeMap<usize, (u32, u32)> =
+        teams.iter().map(|&team_id| (team_id, (0, 0))).collect();
+    for actor in encounter.actors.values() {
+        let (hp, max_hp) = hitpoints.entry(actor.team()).or_default();
+        *hp += actor.hitpoints();
+        *max_hp += actor.max_hitpoints();
+    }
+    Ok(EncounterOutcome {
+        seed,
+        winner,
+        rounds: encounter.round().min(config.max_rounds),
+        hitpoints,
+    })
+}
+
+// plays every run of the config spread over n_threads; outcomes come back in
+// seed order, so the report is the same however many threads there are
+pub fn run_batch(config: &BatchConfig, n_threads: usize) -> BatchReport {
+    let next_run = AtomicUsize::new(0);
+    let outcomes: Mutex<Vec<Option<Result<EncounterOutcome, EngineError>>>> =
+        Mutex::new((0..config.runs).map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..n_threads.clamp(1, config.runs.max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let run = next_run.fetch_add(1, Ordering::Relaxed);
+                    if run >= config.runs {
+                        break;
+                    }
+                    let outcome = run_encounter(config, config.first_seed + run as u64);
+                    outcomes.lock().expect("a simulation thread panicked")[run] = Some(outcome);
+                }
+            });
+        }
+    });
+    let mut report = BatchReport::new(&config.name, Vec::new());
+    let outcomes = outcomes.into_inner().expect("a simulation thread panicked");
+    for (run, outcome) in outcomes.into_iter().enumerate() {
+        match outcome.expect("every run is played") {
+            Ok(outcome) => report.outcomes.push(outcome),
+            Err(e) => report
+                .failures
+                .push((config.first_seed + run as u64, e.to_string())),
+        }
+    }
+    report
+}
+
+// threads to use when not told otherwise
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::simulation::batch::EncounterOutcome;
+
+// how a batch of encounters went, per team
+pub struct BatchReport {
+    pub name: String,
+    pub outcomes: Vec<EncounterOutcome>,
+    // seeds of the runs the engine gave up on, with why; they count for nothing
+    pub failures: Vec<(u64, String)>,
+}
+
+impl BatchReport {
+    pub fn new(name: &str, outcomes: Vec<EncounterOutcome>) -> Self {
+        Self {
+            name: name.to_string(),
+            outcomes,
+            failures: Vec::new(),
+        }
+    }
+
+    pub fn runs(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    pub fn teams(&self) -> BTreeSet<usize> {
+        self.outcomes
+            .iter()
+            .flat_map(|o| o.hitpoints.keys().copied())
+            .collect()
+    }
+
+    fn share<F: Fn(&EncounterOutcome) -> bool>(&self, f: F) -> f32 {
+        if self.outcomes.is_empty() {
+            return 0.;
+        }
+        self.outcomes.iter().filter(|o| f(o)).count() as f32 / self.outcomes.len() as f32
+    }
+
+    pub fn win_rate(&self, team_id: usize) -> f32 {
+        self.share(|o| o.winner == Some(team_id))
+    }
+
+    pub fn draw_rate(&self) -> f32 {
+        self.share(|o| o.winner.is_none())
+    }
+
+    pub fn average_rounds(&self) -> f32 {
+        if self.outcomes.is_empty() {
+            return 0.;
+        }
+        self.outcomes.iter().map(|o| o.rounds as f32).sum::<f32>() / self.outcomes.len() as f32
+    }
+
+    // share of its hit points a team has left at the end, averaged over runs
+    pub fn average_hp_remaining(&self, team_id: usize) -> f32 {
+        let fractions: Vec<f32> = self
+            .outcomes
+            .iter()
+            .filter_map(|o| o.hitpoints.get(&team_id))
+            .map(|&(hp, max_hp)| hp as f32 / max_hp.max(1) as f32)
+            .collect();
+        if fractions.is_empty() {
+            return 0.;
+        }
+        fractions.iter().sum::<f32>() / fractions.len() as f32
+    }
+
+    // the same, counting only the runs the team won
+    pub fn average_hp_remaining_on_win(&self, team_id: usize) -> Option<f32> {
+        let fractions: Vec<f32> = self
+            .outcomes
+            .iter()
+            .filter(|o| o.winner == Some(team_id))
+            .filter_map(|o| o.hitpoints.get(&team_id))
+            .map(|&(hp, max_hp)| hp as f32 / max_hp.max(1) as f32)
+            .collect();
+        if fractions.is_empty() {
+            return None;
+        }
+        Some(fractions.iter().sum::<f32>() / fractions.len() as f32)
+    }
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} runs, {:.1} rounds on average, {:.1}% draws",
+            self.name,
+            self.runs(),
+            self.average_rounds(),
+            self.draw_rate() * 100.
+        )?;
+        writeln!(f, "  team   wins   hp left   hp left on a win")?;
+        for team_id in self.teams() {
+            let on_win = match self.average_hp_remaining_on_win(team_id) {
+                Some(fraction) => format!("{:.1}%", fraction * 100.),
+                None => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "  {:>4} {:>5.1}% {:>8.1}% {:>18}",
+                team_id,
+                self.win_rate(team_id) * 100.,
+                self.average_hp_remaining(team_id) * 100.,
+                on_win
+            )?;
+        }
+        for (seed, error) in self.failures.iter() {
+            writeln!(f, "  run with seed {} failed: {}", seed, error)?;
+        }
+        Ok(())
+    }
+}